@@ -0,0 +1,70 @@
+//! Time-limited cache of L1 accounts whose data does not change between cycles.
+
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Accounts configured as static, each kept for `ttl` after it was fetched
+pub struct AccountCache {
+    keys: HashSet<Pubkey>,
+    ttl: Duration,
+    entries: Mutex<HashMap<Pubkey, (Instant, Account)>>,
+}
+
+impl AccountCache {
+    pub fn new(keys: impl IntoIterator<Item = Pubkey>, ttl: Duration) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns true for accounts that may be served from the cache
+    pub fn is_static(&self, pubkey: &Pubkey) -> bool {
+        self.keys.contains(pubkey)
+    }
+
+    /// The cached account, unless it was never fetched or its entry expired
+    pub fn get(&self, pubkey: &Pubkey) -> Option<Account> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(pubkey) {
+            Some((fetched, account)) if fetched.elapsed() < self.ttl => Some(account.clone()),
+            Some(_) => {
+                entries.remove(pubkey);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Caches a fetched account when it is static
+    pub fn insert(&self, pubkey: Pubkey, account: &Account) {
+        if self.is_static(&pubkey) {
+            self.entries.lock().unwrap().insert(pubkey, (Instant::now(), account.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caches_static_accounts_until_expiry() {
+        let (fixed, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let account = Account::new(1, 0, &Pubkey::new_unique());
+
+        let cache = AccountCache::new([fixed], Duration::from_secs(60));
+        cache.insert(fixed, &account);
+        cache.insert(other, &account);
+        assert_eq!(cache.get(&fixed), Some(account.clone()));
+        assert_eq!(cache.get(&other), None);
+
+        let cache = AccountCache::new([fixed], Duration::ZERO);
+        cache.insert(fixed, &account);
+        assert_eq!(cache.get(&fixed), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Webhook notifications when submissions keep failing and when they recover.
+
+use crate::bridge::CycleReport;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Alerting as read from the optional `[alerts]` config section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AlertsConfig {
+    /// Endpoint receiving the JSON alert payloads; alerting is disabled when unset
+    pub webhook_url: Option<String>,
+    /// Consecutive failed cycles before an alert is sent
+    pub alert_after_failures: u64,
+    /// Minimum seconds between repeated alerts while failures continue
+    pub repeat_after_secs: u64,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            alert_after_failures: 3,
+            repeat_after_secs: 3_600,
+        }
+    }
+}
+
+/// Body posted to the webhook
+///
+/// `text` and `content` carry the same summary so Slack and Discord webhooks accept it as is.
+#[derive(Debug, Serialize)]
+struct AlertPayload {
+    event: &'static str,
+    text: String,
+    content: String,
+    slot: Option<u64>,
+    error: Option<String>,
+    consecutive_failures: u64,
+    timestamp: u64,
+}
+
+/// Tracks whether an alert is outstanding and sends the webhook notifications
+pub struct Alerter {
+    config: AlertsConfig,
+    client: reqwest::Client,
+    /// When the last failure alert went out, while one is outstanding
+    alerted_at: Option<Instant>,
+    /// When the last low runway alert went out, while the runway stays low
+    runway_alerted_at: Option<Instant>,
+}
+
+impl Alerter {
+    pub fn new(config: AlertsConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            alerted_at: None,
+            runway_alerted_at: None,
+        }
+    }
+
+    /// Replaces the configuration, keeping any outstanding alert
+    pub fn reconfigure(&mut self, config: AlertsConfig) {
+        self.config = config;
+    }
+
+    /// Records a cycle outcome, given the breaker's failure count after recording it
+    ///
+    /// Alerts once the count reaches `alert_after_failures`, repeats at most every
+    /// `repeat_after_secs`, and sends a recovery notice on the first success after an alert.
+    pub fn record(&mut self, failure: Option<(Option<u64>, String)>, consecutive_failures: u64) {
+        let Some(webhook_url) = self.config.webhook_url.clone() else {
+            return;
+        };
+
+        let payload = match failure {
+            None => {
+                if self.alerted_at.take().is_none() {
+                    return;
+                }
+                AlertPayload::new("recovered", "Root submissions recovered".to_string(), None, None, 0)
+            }
+            Some((slot, error)) => {
+                if consecutive_failures < self.config.alert_after_failures.max(1) {
+                    return;
+                }
+                let repeat_after = Duration::from_secs(self.config.repeat_after_secs);
+                if self.alerted_at.is_some_and(|at| at.elapsed() < repeat_after) {
+                    return;
+                }
+                self.alerted_at = Some(Instant::now());
+                let text = format!("Root submissions failing ({} consecutive): {}", consecutive_failures, error);
+                AlertPayload::new("failing", text, slot, Some(error), consecutive_failures)
+            }
+        };
+
+        self.dispatch(webhook_url, payload);
+    }
+
+    /// Posts the payload in the background, never holding up the next cycle on a slow webhook
+    fn dispatch(&self, webhook_url: String, payload: AlertPayload) {
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            match send(&client, &webhook_url, &payload).await {
+                Ok(()) => info!(event = payload.event, "alert sent"),
+                Err(e) => warn!(event = payload.event, error = %e, "failed to send alert"),
+            }
+        });
+    }
+
+    /// Alerts right away that a leaf chunk account is missing, repeating at most every `repeat_after_secs`
+    ///
+    /// Like a failure alert, it is followed by a recovery notice on the next success.
+    pub fn leaf_chunk_missing(&mut self, error: String, consecutive_failures: u64) {
+        let Some(webhook_url) = self.config.webhook_url.clone() else {
+            return;
+        };
+        let repeat_after = Duration::from_secs(self.config.repeat_after_secs);
+        if self.alerted_at.is_some_and(|at| at.elapsed() < repeat_after) {
+            return;
+        }
+        self.alerted_at = Some(Instant::now());
+        let text = format!("Leaf chunk account missing, needs operator attention: {}", error);
+        let payload = AlertPayload::new("leaf_chunk_missing", text, None, Some(error), consecutive_failures);
+        self.dispatch(webhook_url, payload);
+    }
+
+    /// Alerts when the fee payer's projected runway drops below `low_runway_hours`, repeating at most every `repeat_after_secs`
+    ///
+    /// `estimate` is the balance and runway from the last balance check.
+    pub fn low_runway(&mut self, estimate: Option<(u64, Option<f64>)>, low_runway_hours: Option<f64>) {
+        let Some(webhook_url) = self.config.webhook_url.clone() else {
+            return;
+        };
+        let (Some((balance, Some(runway_hours))), Some(low_runway_hours)) = (estimate, low_runway_hours) else {
+            return;
+        };
+        if runway_hours >= low_runway_hours {
+            self.runway_alerted_at = None;
+            return;
+        }
+        let repeat_after = Duration::from_secs(self.config.repeat_after_secs);
+        if self.runway_alerted_at.is_some_and(|at| at.elapsed() < repeat_after) {
+            return;
+        }
+        self.runway_alerted_at = Some(Instant::now());
+        let text = format!(
+            "Fee payer runs dry in about {:.1} hours at the current spend rate ({} lamports left), top up",
+            runway_hours, balance
+        );
+        self.dispatch(webhook_url, AlertPayload::new("low_runway", text, None, None, 0));
+    }
+}
+
+impl AlertPayload {
+    fn new(event: &'static str, text: String, slot: Option<u64>, error: Option<String>, consecutive_failures: u64) -> Self {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        Self {
+            event,
+            content: text.clone(),
+            text,
+            slot,
+            error,
+            consecutive_failures,
+            timestamp,
+        }
+    }
+}
+
+async fn send(client: &reqwest::Client, webhook_url: &str, payload: &AlertPayload) -> Result<()> {
+    client
+        .post(webhook_url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(payload)?)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Summarises a failed cycle as its slot, when known, and the first error
+pub fn describe_failure(result: &Result<CycleReport>) -> Option<(Option<u64>, String)> {
+    match result {
+        Ok(report) => report
+            .targets
+            .iter()
+            .find_map(|target| target.result.as_ref().err().map(|e| format!("{}: {}", target.target, e)))
+            .map(|error| (Some(report.roots.slot), error)),
+        Err(e) => Some((None, e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn alerts_on_low_runway_until_it_recovers() {
+        let config = AlertsConfig {
+            webhook_url: Some("http://127.0.0.1:9/".to_string()),
+            ..AlertsConfig::default()
+        };
+        let mut alerter = Alerter::new(config.clone());
+        alerter.low_runway(Some((1_000, Some(48.0))), Some(24.0));
+        assert!(alerter.runway_alerted_at.is_none());
+
+        alerter.low_runway(Some((1_000, Some(12.0))), Some(24.0));
+        let alerted_at = alerter.runway_alerted_at.unwrap();
+
+        // Repeats are held back for `repeat_after_secs`
+        alerter.low_runway(Some((900, Some(11.0))), Some(24.0));
+        assert_eq!(alerter.runway_alerted_at, Some(alerted_at));
+        alerter.reconfigure(AlertsConfig { repeat_after_secs: 0, ..config });
+        alerter.low_runway(Some((800, Some(10.0))), Some(24.0));
+        assert!(alerter.runway_alerted_at.unwrap() > alerted_at);
+
+        alerter.low_runway(Some((10_000, Some(24.0))), Some(24.0));
+        assert!(alerter.runway_alerted_at.is_none());
+    }
+}
@@ -0,0 +1,180 @@
+//! Tamper-evident local record of every submission, hash-chained line to line.
+
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use solana_sdk::hash::{hashv, Hash};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// Audit settings as read from the optional `[audit]` config section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Append-only audit file; auditing is disabled when unset
+    pub path: Option<String>,
+}
+
+/// One submission, stored as a JSON line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Unix time of the confirmation, in seconds
+    pub timestamp: u64,
+    pub slot: u64,
+    /// Hex-encoded merkle tree root
+    pub mt_root: String,
+    /// Hex-encoded world state root
+    pub ws_root: String,
+    pub signature: String,
+    /// Hash over the previous entry's hash and this entry's fields
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// The chain hash of this entry's fields following `prev`
+    fn chain_hash(&self, prev: &Hash) -> Hash {
+        hashv(&[
+            prev.as_ref(),
+            &self.timestamp.to_le_bytes(),
+            &self.slot.to_le_bytes(),
+            self.mt_root.as_bytes(),
+            self.ws_root.as_bytes(),
+            self.signature.as_bytes(),
+        ])
+    }
+}
+
+/// Where verifying an audit file's hash chain failed
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChainCheck {
+    /// Every entry chains onto the one before it
+    Intact { entries: usize },
+    /// The entry on this 1-based line is unreadable or does not chain onto the one before it
+    Broken { line: usize },
+}
+
+/// The audit file, remembering the hash of its last entry
+pub struct AuditLog {
+    path: PathBuf,
+    last_hash: Mutex<Hash>,
+}
+
+impl AuditLog {
+    /// Opens the audit file at `path`, verifying its chain and warning when it is broken
+    ///
+    /// New entries chain onto the last readable entry either way, so a broken
+    /// chain stays visible to every later verification.
+    pub fn open(path: &str) -> Result<Self> {
+        let path = PathBuf::from(shellexpand::tilde(path).as_ref());
+        let contents = read_audit(&path)?;
+        match verify_chain(&contents) {
+            ChainCheck::Intact { entries } => info!(path = %path.display(), entries, "audit hash chain verified"),
+            ChainCheck::Broken { line } => {
+                warn!(path = %path.display(), line, "audit hash chain broken, the file may have been tampered with")
+            }
+        }
+        // Start a fresh line after one cut short by a crash, so the next entry stays readable
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            OpenOptions::new().append(true).open(&path)?.write_all(b"\n")?;
+        }
+
+        let last_hash = contents
+            .lines()
+            .rev()
+            .find_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .and_then(|entry| entry.hash.parse().ok())
+            .unwrap_or_default();
+        Ok(Self {
+            path,
+            last_hash: Mutex::new(last_hash),
+        })
+    }
+
+    /// Appends a submission to the chain, syncing it to disk
+    pub fn append(&self, slot: u64, mt_root: &[u8], ws_root: &[u8], signature: &str) -> Result<()> {
+        let mut last_hash = self.last_hash.lock().unwrap();
+        let mut entry = AuditEntry {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+            slot,
+            mt_root: hex::encode(mt_root),
+            ws_root: hex::encode(ws_root),
+            signature: signature.to_string(),
+            hash: String::new(),
+        };
+        let hash = entry.chain_hash(&last_hash);
+        entry.hash = hash.to_string();
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .wrap_err_with(|| format!("audit file {}", self.path.display()))?;
+        file.write_all(&line)?;
+        file.sync_data()?;
+
+        *last_hash = hash;
+        Ok(())
+    }
+}
+
+/// Checks every entry chains onto the one before it, the first onto the default hash
+pub fn verify_chain(contents: &str) -> ChainCheck {
+    let mut prev = Hash::default();
+    let mut entries = 0;
+    for (number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(entry) = serde_json::from_str::<AuditEntry>(line) else {
+            return ChainCheck::Broken { line: number + 1 };
+        };
+        let hash = entry.chain_hash(&prev);
+        if entry.hash != hash.to_string() {
+            return ChainCheck::Broken { line: number + 1 };
+        }
+        prev = hash;
+        entries += 1;
+    }
+    ChainCheck::Intact { entries }
+}
+
+/// Reads the audit file, treating a missing file as empty
+fn read_audit(path: &std::path::Path) -> Result<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(eyre::eyre!("audit file {}: {}", path.display(), e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_tampered_entries() {
+        let path = std::env::temp_dir().join(format!("sol-proposer-audit-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let audit = AuditLog::open(path.to_str().unwrap()).unwrap();
+        audit.append(7, &[1; 32], &[2; 32], "first").unwrap();
+        audit.append(8, &[3; 32], &[4; 32], "second").unwrap();
+
+        // Reopening continues the chain
+        let audit = AuditLog::open(path.to_str().unwrap()).unwrap();
+        audit.append(9, &[5; 32], &[6; 32], "third").unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(verify_chain(&contents), ChainCheck::Intact { entries: 3 });
+
+        let tampered = contents.replacen("\"slot\":8", "\"slot\":80", 1);
+        assert_eq!(verify_chain(&tampered), ChainCheck::Broken { line: 2 });
+        let removed: Vec<&str> = contents.lines().enumerate().filter(|(i, _)| *i != 0).map(|(_, l)| l).collect();
+        assert_eq!(verify_chain(&removed.join("\n")), ChainCheck::Broken { line: 1 });
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+//! Fee payer balance checks.
+
+use crate::bridge::BridgeContext;
+use crate::error::BridgeError;
+use crate::metrics;
+use eyre::Result;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Lamports charged per transaction signature
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Priority fee charged for a compute budget, in lamports
+pub fn priority_fee(compute_unit_price: u64, compute_unit_limit: u32) -> u64 {
+    (compute_unit_price as u128 * compute_unit_limit as u128).div_ceil(1_000_000) as u64
+}
+
+/// Estimates the fee of a transaction from its signature count and compute budget
+pub fn estimate_fee(signatures: u64, compute_unit_price: u64, compute_unit_limit: u32) -> u64 {
+    signatures * LAMPORTS_PER_SIGNATURE + priority_fee(compute_unit_price, compute_unit_limit)
+}
+
+/// Checks the fee payer can cover `fee_estimate`, warning when it runs low
+pub async fn check_fee_payer_balance(ctx: &BridgeContext, fee_payer: &Pubkey, fee_estimate: u64) -> Result<u64> {
+    let settings = &ctx.config.settings;
+    let (client, address) = (ctx.l1_client.clone(), *fee_payer);
+    let balance = ctx.rpc_call(move || Ok(client.get_balance(&address)?)).await?;
+
+    let need_estimate = settings.min_balance_lamports.max(fee_estimate);
+    if balance < need_estimate {
+        return Err(BridgeError::InsufficientBalance {
+            have: balance,
+            need_estimate,
+        }
+        .into());
+    }
+
+    if balance < settings.low_balance_lamports {
+        warn!(
+            %fee_payer,
+            balance,
+            low_watermark = settings.low_balance_lamports,
+            "fee payer balance is low, top up soon"
+        );
+    }
+    estimate_runway(ctx, fee_payer, balance);
+
+    Ok(balance)
+}
+
+/// Projects how long `balance` lasts at the recent spend rate, exporting it as `estimated_runway_hours`
+fn estimate_runway(ctx: &BridgeContext, fee_payer: &Pubkey, balance: u64) {
+    let (lamports_per_hour, runway_hours) = ctx.spend.record_balance(balance);
+    metrics::ESTIMATED_RUNWAY_HOURS.set(runway_hours.unwrap_or(f64::INFINITY));
+    let Some(runway_hours) = runway_hours else {
+        return;
+    };
+    match ctx.config.settings.low_runway_hours {
+        Some(low_runway_hours) if runway_hours < low_runway_hours => warn!(
+            %fee_payer,
+            balance,
+            lamports_per_hour,
+            runway_hours,
+            low_runway_hours,
+            "fee payer runs dry soon at the current spend rate, top up"
+        ),
+        _ => info!(%fee_payer, balance, lamports_per_hour, runway_hours, "estimated fee payer runway"),
+    }
+}
+
+/// Fees paid over the last `settings.runway_window_secs`, for projecting the fee payer's runway
+pub struct SpendTracker {
+    window: Duration,
+    started: Instant,
+    /// When each fee was paid and how much, oldest first
+    fees: Mutex<VecDeque<(Instant, u64)>>,
+    /// Fee payer balance at the last check and the runway projected from it
+    last_estimate: Mutex<Option<(u64, Option<f64>)>>,
+}
+
+/// Shortest period spend is averaged over, so the first fees after startup
+/// do not project a runway of minutes
+const MIN_RATE_PERIOD: Duration = Duration::from_secs(3_600);
+
+impl SpendTracker {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            started: Instant::now(),
+            fees: Mutex::new(VecDeque::new()),
+            last_estimate: Mutex::new(None),
+        }
+    }
+
+    /// Takes over the fees and last estimate of `previous`, so a config reload keeps the history
+    pub fn inherit(&mut self, previous: &SpendTracker) {
+        self.started = self.started.min(previous.started);
+        *self.fees.get_mut().unwrap() = previous.fees.lock().unwrap().clone();
+        *self.last_estimate.get_mut().unwrap() = *previous.last_estimate.lock().unwrap();
+    }
+
+    /// Records a fee paid now
+    pub fn record_fee(&self, fee: u64) {
+        self.record_fee_at(Instant::now(), fee);
+    }
+
+    fn record_fee_at(&self, at: Instant, fee: u64) {
+        self.fees.lock().unwrap().push_back((at, fee));
+    }
+
+    /// Average spend over the window, or since startup when that is shorter, but at least an hour
+    pub fn lamports_per_hour(&self) -> f64 {
+        self.lamports_per_hour_at(Instant::now())
+    }
+
+    fn lamports_per_hour_at(&self, now: Instant) -> f64 {
+        let mut fees = self.fees.lock().unwrap();
+        while fees.front().is_some_and(|(at, _)| now.duration_since(*at) > self.window) {
+            fees.pop_front();
+        }
+        let spent: u64 = fees.iter().map(|(_, fee)| fee).sum();
+        let period = now.duration_since(self.started).min(self.window).max(MIN_RATE_PERIOD);
+        spent as f64 / (period.as_secs_f64() / 3_600.0)
+    }
+
+    /// Records the fee payer's balance, returning the spend rate and the hours the balance
+    /// lasts at it; `None` when nothing was spent
+    pub fn record_balance(&self, balance: u64) -> (f64, Option<f64>) {
+        let rate = self.lamports_per_hour();
+        let runway_hours = (rate > 0.0).then(|| balance as f64 / rate);
+        *self.last_estimate.lock().unwrap() = Some((balance, runway_hours));
+        (rate, runway_hours)
+    }
+
+    /// Balance and projected runway at the last balance check
+    pub fn last_estimate(&self) -> Option<(u64, Option<f64>)> {
+        *self.last_estimate.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projects_runway_from_recent_spend() {
+        let tracker = SpendTracker::new(Duration::from_secs(4 * 3_600));
+        assert_eq!(tracker.record_balance(1_000), (0.0, None));
+
+        // Fees of the first hour are averaged over a full hour
+        let now = tracker.started + Duration::from_secs(600);
+        tracker.record_fee_at(now, 3_000);
+        assert_eq!(tracker.lamports_per_hour_at(now), 3_000.0);
+
+        let now = tracker.started + Duration::from_secs(2 * 3_600);
+        tracker.record_fee_at(now, 1_000);
+        assert_eq!(tracker.lamports_per_hour_at(now), 2_000.0);
+
+        // The first fee falls out of the window
+        let now = tracker.started + Duration::from_secs(5 * 3_600);
+        assert_eq!(tracker.lamports_per_hour_at(now), 250.0);
+    }
+}
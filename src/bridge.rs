@@ -0,0 +1,2806 @@
+//! Reading roots from L2 and submitting them to L1.
+
+use crate::account_cache::AccountCache;
+use crate::audit::AuditLog;
+use crate::balance::{self, SpendTracker};
+use crate::client::{L1Submitter, L2Reader};
+use crate::config::{
+    parse_discriminator, parse_hash, parse_leaf_chunks, parse_pubkey, parse_root, Commitment, Config, RootField, SettingsConfig,
+    SlotSource, TxVersion,
+};
+use crate::confirm::{self, ConfirmOptions, ConfirmationOutcome};
+use crate::error::BridgeError;
+use crate::fees;
+use crate::health;
+use crate::jito::JitoClient;
+use crate::leaf_chunk::{concat_chunks, extract_bytes, extract_slot, LeafChunk, LeafChunkLayout};
+use crate::metrics;
+use crate::multisig::Multisig;
+use crate::nonce::DurableNonce;
+use crate::pending::PendingRoots;
+use crate::program::{AccountRole, DataPart, InstructionLayout};
+use crate::program_errors::{self, ErrorAction};
+use crate::retry::{is_transient, with_retry, RetryPolicy};
+use crate::rpc::{self, FailoverRpcClient};
+use crate::state;
+use crate::submission_log::{LogEntry, SubmissionLog};
+use crate::target::L1Target;
+use crate::wallet::{load_fee_payer, load_wallet, WalletSigner};
+use crate::world_state::{VecPrefix, WorldStateLayout, WorldStateReader};
+use eyre::Result;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::client_error::ClientError;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_sdk::{
+    account::Account,
+    clock::MAX_PROCESSING_AGE,
+    commitment_config::CommitmentConfig,
+    address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount},
+    instruction::{AccountMeta, Instruction},
+    message::{v0, Message, VersionedMessage},
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    hash::Hash,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::VersionedTransaction,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Long-lived state shared by every cycle: parsed addresses, the wallet and RPC clients
+pub struct BridgeContext {
+    pub config: Config,
+    /// Retries of RPC reads
+    pub retry_policy: RetryPolicy,
+    /// Retries of the `sendTransaction` call
+    pub send_retry_policy: RetryPolicy,
+    /// Retries of submissions that did not confirm, re-signed each time
+    pub confirm_retry_policy: RetryPolicy,
+    pub rpc_timeout: Duration,
+    pub l1_client: Arc<dyn L1Submitter>,
+    pub l2_client: Arc<dyn L2Reader>,
+    /// Authority signing the bridge instruction
+    pub wallet: WalletSigner,
+    /// Separate transaction fee payer; the wallet pays when unset
+    pub fee_payer: Option<Arc<Keypair>>,
+    /// Durable nonce signed with instead of a recent blockhash
+    pub nonce: Option<DurableNonce>,
+    /// Block engine bundles are sent to instead of the L1 RPC
+    pub jito: Option<JitoClient>,
+    /// Squads multisig the bridge instruction is proposed to instead of being signed by the wallet
+    pub multisig: Option<Multisig>,
+    /// Address lookup table referenced by `v0` transactions
+    pub lookup_table: Option<Pubkey>,
+    /// L1 accounts in `settings.static_accounts`, refetched once their entry expires
+    pub account_cache: AccountCache,
+    /// Merkle tree root from `--mt-root`, submitted instead of the leaf chunk's
+    pub mt_root_override: Option<Vec<u8>>,
+    /// World state root from `--ws-root`, submitted without reading the world state account
+    pub ws_root_override: Option<[u8; 32]>,
+    /// Leaf chunk accounts, in the order their data is concatenated; empty when `account.leaf_chunk_filter` finds it
+    pub leaf_chunks: Vec<Pubkey>,
+    /// Leaf chunk last found by `account.leaf_chunk_filter`, with when it was looked up
+    pub resolved_leaf_chunk: std::sync::Mutex<Option<(Pubkey, Instant)>>,
+    pub leaf_chunk_owner: Option<Pubkey>,
+    pub world_state: Option<WorldStateReader>,
+    pub leaf_chunk_layout: LeafChunkLayout,
+    /// L1 programs receiving the roots, primary first
+    pub targets: Vec<L1Target>,
+    /// Roots awaiting each target while its L1 is unreachable, in the order of `targets`
+    pub pending: Vec<PendingRoots>,
+    /// Highest L2 slot read so far, seeded from the state files
+    pub max_seen_slot: AtomicU64,
+    /// Recently paid fees, projecting how long the fee payer's balance lasts
+    pub spend: SpendTracker,
+    /// Every confirmed submission, when `settings.submission_log` is set
+    pub submission_log: Option<SubmissionLog>,
+    /// Hash-chained record of every confirmed submission, when `[audit]` has a path
+    pub audit_log: Option<AuditLog>,
+    /// Held while a service cycle runs, so cycles never overlap
+    pub cycle_lock: tokio::sync::Mutex<()>,
+}
+
+impl BridgeContext {
+    /// Builds the context from a validated configuration
+    pub async fn new(config: Config) -> Result<Self> {
+        let rpc_timeout = Duration::from_secs(config.settings.rpc_timeout_secs);
+        let network = &config.network;
+        let rate_limit = &config.rate_limit;
+        let auth = &network.endpoint_auth;
+        let aliases = &network.endpoint_aliases;
+        let stats_window = Duration::from_secs(network.rpc_stats_window_secs);
+        let client = |urls: &[String], commitment: Commitment| {
+            FailoverRpcClient::new(urls, commitment.into(), rpc_timeout, rate_limit, auth, aliases, stats_window)
+        };
+        let l1_client = client(&network.l1_rpc_url, network.l1_commitment)?;
+        let l2_client = client(&network.l2_rpc_url, network.l2_commitment)?;
+        Self::with_clients(config, Box::new(l1_client), Box::new(l2_client)).await
+    }
+
+    /// Builds the context around already constructed L1 and L2 clients
+    pub async fn with_clients(
+        config: Config,
+        l1_client: Box<dyn L1Submitter>,
+        l2_client: Box<dyn L2Reader>,
+    ) -> Result<Self> {
+        let wallet = load_wallet(&config.wallet).await?;
+        let fee_payer = load_fee_payer(&config.wallet)?;
+
+        let leaf_chunks = match &config.account.leaf_chunk_filter {
+            Some(_) => Vec::new(),
+            None => parse_leaf_chunks(&config.account.leaf_chunk_address)?,
+        };
+        let leaf_chunk_owner = config
+            .account
+            .expected_leaf_chunk_owner
+            .as_deref()
+            .map(|address| parse_pubkey("account.expected_leaf_chunk_owner", address))
+            .transpose()?;
+        let world_state = config
+            .account
+            .world_state_account
+            .as_deref()
+            .map(|address| parse_pubkey("account.world_state_account", address))
+            .transpose()?
+            .map(|account| WorldStateReader {
+                account,
+                layout: WorldStateLayout {
+                    root_offset: config.account.world_state_offset,
+                    vec_prefix: config.account.world_state_vec_offset.map(|offset| VecPrefix {
+                        offset,
+                        item_len: config.account.world_state_vec_item_len,
+                    }),
+                },
+            });
+        let leaf_chunk_layout = LeafChunkLayout {
+            discriminator: config
+                .account
+                .leaf_chunk_discriminator
+                .as_deref()
+                .map(|value| parse_discriminator("account.leaf_chunk_discriminator", value))
+                .transpose()?,
+            mt_root_offset: config.account.mt_root_offset,
+            root_len: config.account.root_len,
+            slot_offset: match config.account.slot_source {
+                SlotSource::Account => config.account.slot_data_offset,
+                SlotSource::RpcContext => None,
+            },
+        };
+
+        // The wallet and fee payer are the only keys we can sign with
+        let targets = L1Target::all(&config)?;
+        let can_sign = |pubkey: &Pubkey| {
+            *pubkey == wallet.pubkey() || fee_payer.as_ref().is_some_and(|payer| *pubkey == payer.pubkey())
+        };
+        for target in &targets {
+            let mut extra_accounts = target.instructions.iter().flat_map(|instruction| &instruction.extra_accounts);
+            if let Some(meta) = extra_accounts.find(|meta| meta.is_signer && !can_sign(&meta.pubkey)) {
+                return Err(eyre::eyre!(
+                    "{}: program.extra_accounts: {} is marked as signer but is not the wallet or fee payer",
+                    target.name,
+                    meta.pubkey
+                ));
+            }
+        }
+
+        let nonce = config
+            .nonce
+            .nonce_account
+            .as_deref()
+            .map(|account| -> Result<DurableNonce> {
+                let authority = match &config.nonce.nonce_authority {
+                    Some(authority) => parse_pubkey("nonce.nonce_authority", authority)?,
+                    None => wallet.pubkey(),
+                };
+                if !can_sign(&authority) {
+                    return Err(eyre::eyre!(
+                        "nonce.nonce_authority: {} is not the wallet or fee payer",
+                        authority
+                    ));
+                }
+                Ok(DurableNonce {
+                    account: parse_pubkey("nonce.nonce_account", account)?,
+                    authority,
+                })
+            })
+            .transpose()?;
+
+        let lookup_table = config
+            .settings
+            .address_lookup_table
+            .as_deref()
+            .map(|table| parse_pubkey("settings.address_lookup_table", table))
+            .transpose()?;
+
+        let jito = JitoClient::from_config(&config.jito, Duration::from_secs(config.settings.rpc_timeout_secs))?;
+        let multisig = Multisig::from_config(&config.multisig)?;
+
+        let static_accounts = config
+            .settings
+            .static_accounts
+            .iter()
+            .map(|address| parse_pubkey("settings.static_accounts", address))
+            .collect::<Result<Vec<_>>>()?;
+        let account_cache = AccountCache::new(
+            static_accounts,
+            Duration::from_secs(config.settings.static_account_ttl_secs),
+        );
+
+        let mt_root_override = config
+            .settings
+            .mt_root_override
+            .as_deref()
+            .map(|root| parse_root("--mt-root", root, config.account.root_len))
+            .transpose()?;
+        let ws_root_override = config
+            .settings
+            .ws_root_override
+            .as_deref()
+            .map(|root| parse_root("--ws-root", root, 32))
+            .transpose()?
+            .map(|root| root.try_into().expect("root of world state length"));
+
+        let submission_log = config
+            .settings
+            .submission_log
+            .as_deref()
+            .map(SubmissionLog::open)
+            .transpose()?;
+        let audit_log = config.audit.path.as_deref().map(AuditLog::open).transpose()?;
+
+        let max_seen_slot = targets
+            .iter()
+            .filter_map(|target| state::load_last_slot(&target.state_file))
+            .max()
+            .unwrap_or(0);
+        let pending = targets
+            .iter()
+            .map(|target| PendingRoots::open(&target.name, &target.state_file, config.settings.pending_queue_size))
+            .collect();
+
+        let spend = SpendTracker::new(Duration::from_secs(config.settings.runway_window_secs));
+        Ok(Self {
+            retry_policy: RetryPolicy {
+                retry_codes: config.program_errors.retry.clone(),
+                ..RetryPolicy::from(&config.retry)
+            },
+            send_retry_policy: RetryPolicy {
+                retry_codes: config.program_errors.retry.clone(),
+                ..config.retry.send_policy()
+            },
+            confirm_retry_policy: RetryPolicy {
+                retry_codes: config.program_errors.retry.clone(),
+                ..config.retry.confirm_policy()
+            },
+            rpc_timeout: Duration::from_secs(config.settings.rpc_timeout_secs),
+            config,
+            l1_client: l1_client.into(),
+            l2_client: l2_client.into(),
+            wallet,
+            fee_payer: fee_payer.map(Arc::new),
+            nonce,
+            jito,
+            multisig,
+            lookup_table,
+            account_cache,
+            mt_root_override,
+            ws_root_override,
+            leaf_chunks,
+            resolved_leaf_chunk: std::sync::Mutex::new(None),
+            leaf_chunk_owner,
+            world_state,
+            leaf_chunk_layout,
+            max_seen_slot: AtomicU64::new(max_seen_slot),
+            spend,
+            submission_log,
+            audit_log,
+            cycle_lock: tokio::sync::Mutex::new(()),
+            pending,
+            targets,
+        })
+    }
+}
+
+impl BridgeContext {
+    /// Authority of the bridge instruction: the multisig vault when one is configured, otherwise the wallet
+    pub fn authority(&self) -> Pubkey {
+        match &self.multisig {
+            Some(multisig) => multisig.vault,
+            None => self.wallet.pubkey(),
+        }
+    }
+
+    /// Signer paying the transaction fees
+    pub fn payer(&self) -> &dyn Signer {
+        match &self.fee_payer {
+            Some(fee_payer) => fee_payer.as_ref(),
+            None => self.wallet.as_ref(),
+        }
+    }
+
+    /// Every signer of a submission, fee payer first
+    pub fn signers(&self) -> Vec<WalletSigner> {
+        match &self.fee_payer {
+            Some(fee_payer) => vec![fee_payer.clone(), self.wallet.clone()],
+            None => vec![self.wallet.clone()],
+        }
+    }
+
+    /// Hash to sign with: the durable nonce's when one is configured, otherwise the latest blockhash
+    pub async fn recent_blockhash(&self) -> Result<Hash> {
+        self.rpc_call(self.blockhash_lookup()).await
+    }
+
+    /// Looks up the hash to sign with, as a call to run on the blocking pool
+    ///
+    /// A blockhash older than `settings.max_blockhash_age_slots`, as a lagging node may
+    /// serve, fails with the transient `StaleBlockhash` so the caller refetches it.
+    fn blockhash_lookup(&self) -> impl Fn() -> Result<Hash> + Send + Sync + 'static {
+        let client = self.l1_client.clone();
+        let nonce = self.nonce.clone();
+        let commitment = self.config.network.l1_commitment.into();
+        let max_blockhash_age = self.config.settings.max_blockhash_age_slots;
+        move || {
+            if let Some(nonce) = &nonce {
+                return nonce.fetch_blockhash(client.as_ref(), commitment);
+            }
+            let (blockhash, last_valid_block_height) = client.get_latest_blockhash_with_commitment(commitment)?;
+            let Some(max_age) = max_blockhash_age else {
+                return Ok(blockhash);
+            };
+            let block_height = client.get_block_height(commitment)?;
+            let remaining = last_valid_block_height.saturating_sub(block_height);
+            let age = (MAX_PROCESSING_AGE as u64).saturating_sub(remaining);
+            if age > max_age {
+                warn!(%blockhash, age, max_age, "blockhash too old, refetching");
+                return Err(BridgeError::StaleBlockhash { blockhash, age, max_age }.into());
+            }
+            Ok(blockhash)
+        }
+    }
+
+    /// Runs a single RPC call with retries, each attempt on tokio's blocking pool
+    ///
+    /// Keeping the blocking clients off the async workers lets the call run on a
+    /// current-thread runtime too. An attempt still running after `rpc_timeout` fails
+    /// with `BridgeError::RpcTimeout`, leaving its thread to the clients' HTTP timeout of
+    /// the same length. A transient client error that outlasts the retries surfaces as
+    /// `BridgeError::RpcTimeout` when the request timed out and as
+    /// `BridgeError::RpcTransient` otherwise.
+    pub async fn rpc_call<T, F>(&self, call: F) -> Result<T>
+    where
+        F: Fn() -> Result<T> + Send + Sync + 'static,
+        T: Send + 'static,
+    {
+        let call = Arc::new(call);
+        with_retry(&self.retry_policy, || {
+            let call = call.clone();
+            rpc::with_timeout(self.rpc_timeout, rpc::blocking(move || call()))
+        })
+        .await
+        .map_err(|e| {
+            if !is_transient(&e) {
+                return e;
+            }
+            match e.downcast::<ClientError>() {
+                Ok(source) if rpc::is_timeout(&source) => {
+                    BridgeError::RpcTimeout { timeout: self.rpc_timeout }.into()
+                }
+                Ok(source) => BridgeError::RpcTransient(Box::new(source)).into(),
+                Err(e) => e,
+            }
+        })
+    }
+
+    /// Fetches an L1 account, serving static accounts from the cache while their entry is fresh
+    pub async fn get_l1_account(&self, pubkey: &Pubkey, commitment: CommitmentConfig) -> Result<Option<Account>> {
+        if let Some(account) = self.account_cache.get(pubkey) {
+            debug!(%pubkey, "using cached account");
+            return Ok(Some(account));
+        }
+        let (client, address) = (self.l1_client.clone(), *pubkey);
+        let account = self
+            .rpc_call(move || Ok(client.get_account_with_commitment(&address, commitment)?.value))
+            .await?;
+        if let Some(account) = &account {
+            self.account_cache.insert(*pubkey, account);
+        }
+        Ok(account)
+    }
+}
+
+/// Roots read from L2 for a single slot
+#[derive(Debug, Clone)]
+pub struct L2Roots {
+    pub slot: u64,
+    /// Merkle tree root, `account.root_len` bytes long
+    pub mt_root: Vec<u8>,
+    pub ws_root: [u8; 32],
+}
+
+impl L2Roots {
+    /// Output of `settings.root_transform` over `settings.root_transform_fields`, `None` when roots are submitted raw
+    pub fn commitment(&self, settings: &SettingsConfig) -> Option<[u8; 32]> {
+        let slot_le = self.slot.to_le_bytes();
+        let slot_be = self.slot.to_be_bytes();
+        let parts: Vec<&[u8]> = settings
+            .root_transform_fields
+            .iter()
+            .map(|field| match field {
+                RootField::MtRoot => self.mt_root.as_slice(),
+                RootField::WsRoot => self.ws_root.as_slice(),
+                RootField::SlotLe => slot_le.as_slice(),
+                RootField::SlotBe => slot_be.as_slice(),
+            })
+            .collect();
+        settings.root_transform.digest(&parts)
+    }
+
+    /// The roots as the L1 program sees them, their slot shifted by `settings.slot_offset`
+    pub fn for_program(&self, config: &Config) -> Result<Self> {
+        Ok(Self {
+            slot: config.program_slot(self.slot)?,
+            ..self.clone()
+        })
+    }
+
+    /// What L1 stores at `program.roots_mt_root_offset`: the commitment when transformed, otherwise the merkle root
+    pub fn stored_root(&self, settings: &SettingsConfig) -> Vec<u8> {
+        match self.commitment(settings) {
+            Some(commitment) => commitment.to_vec(),
+            None => self.mt_root.clone(),
+        }
+    }
+}
+
+/// Result of a successful check-and-submit cycle
+#[derive(Debug)]
+pub enum CycleOutcome {
+    /// Roots were submitted and confirmed on L1
+    Submitted { slot: u64, signature: Signature },
+    /// Nothing needed submitting this cycle
+    Skipped { slot: u64, reason: &'static str },
+    /// The bridge instruction was proposed to the multisig, awaiting its approvers
+    Proposed {
+        slot: u64,
+        signature: Signature,
+        proposal: Pubkey,
+    },
+    /// The transaction was built but not sent
+    DryRun { slot: u64 },
+    /// Observer mode checked L1 instead of submitting
+    ///
+    /// `l1_lag_slots` is 0 when L1 holds this slot's roots, and unknown until a slot was seen on L1.
+    Observed { slot: u64, l1_lag_slots: Option<u64> },
+}
+
+/// Result of a cycle for a single L1 target
+#[derive(Debug)]
+pub struct TargetResult {
+    pub target: String,
+    pub result: Result<CycleOutcome>,
+}
+
+impl TargetResult {
+    /// Short status used in metrics labels and JSON output
+    pub fn status(&self) -> &'static str {
+        match &self.result {
+            Ok(CycleOutcome::Submitted { .. }) => "submitted",
+            Ok(CycleOutcome::Proposed { .. }) => "proposed",
+            Ok(CycleOutcome::Skipped { .. }) => "skipped",
+            Ok(CycleOutcome::DryRun { .. }) => "dry_run",
+            Ok(CycleOutcome::Observed { .. }) => "observed",
+            Err(_) => "failed",
+        }
+    }
+}
+
+/// The roots read in a cycle and what became of them on each target
+#[derive(Debug)]
+pub struct CycleReport {
+    pub roots: L2Roots,
+    pub targets: Vec<TargetResult>,
+}
+
+/// Fetches an L2 account, requiring the RPC to serve it from at least `min_context_slot`
+///
+/// Returns the account together with the slot the RPC served it from.
+pub async fn fetch_l2_account(
+    ctx: &BridgeContext,
+    pubkey: &Pubkey,
+    min_context_slot: Option<u64>,
+) -> Result<(Option<Account>, u64)> {
+    let account_config = l2_account_config(ctx, min_context_slot);
+    let (client, address) = (ctx.l2_client.clone(), *pubkey);
+    let response = ctx
+        .rpc_call(move || Ok(client.get_account_with_config(&address, account_config.clone())?))
+        .await?;
+
+    Ok((response.value, response.context.slot))
+}
+
+/// The leaf chunk accounts to read: the configured ones, or the one `account.leaf_chunk_filter` matches
+///
+/// A filtered address is reused for `cache_secs` before the program's accounts are searched again.
+async fn leaf_chunk_addresses(ctx: &BridgeContext) -> Result<Vec<Pubkey>> {
+    let Some(filter) = &ctx.config.account.leaf_chunk_filter else {
+        return Ok(ctx.leaf_chunks.clone());
+    };
+    let cached = *ctx.resolved_leaf_chunk.lock().unwrap();
+    if let Some((address, resolved_at)) = cached.filter(|(_, at)| at.elapsed() < Duration::from_secs(filter.cache_secs)) {
+        debug!(%address, age_secs = resolved_at.elapsed().as_secs(), "using resolved leaf chunk");
+        return Ok(vec![address]);
+    }
+
+    let program_id = parse_pubkey("account.leaf_chunk_filter.program_id", &filter.program_id)?;
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filter.rpc_filters()?),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            // Only the address is needed, the data is read separately at the right context slot
+            data_slice: Some(UiDataSliceConfig { offset: 0, length: 0 }),
+            commitment: Some(ctx.config.network.l2_commitment.into()),
+            min_context_slot: None,
+        },
+        with_context: None,
+    };
+    let client = ctx.l2_client.clone();
+    let matches = ctx
+        .rpc_call(move || Ok(client.get_program_accounts_with_config(&program_id, config.clone())?))
+        .await?;
+    let [(address, _)] = matches.as_slice() else {
+        return Err(BridgeError::LeafChunkUnresolved { matches: matches.len() }.into());
+    };
+
+    if cached.is_none_or(|(previous, _)| previous != *address) {
+        info!(leaf_chunk = %address, previous = cached.map(|(previous, _)| previous.to_string()), "resolved leaf chunk account");
+    }
+    *ctx.resolved_leaf_chunk.lock().unwrap() = Some((*address, Instant::now()));
+    Ok(vec![*address])
+}
+
+/// Fetches every leaf chunk and joins them into one account, returning it with the context slot
+///
+/// Fails naming the chunk that is missing or owned by an unexpected program.
+async fn fetch_leaf_chunks(ctx: &BridgeContext, min_context_slot: Option<u64>) -> Result<(Account, u64)> {
+    let leaf_chunks = leaf_chunk_addresses(ctx).await?;
+    let (accounts, slot) = match leaf_chunks.as_slice() {
+        [leaf_chunk] => {
+            let (account, slot) = fetch_l2_account(ctx, leaf_chunk, min_context_slot).await?;
+            (vec![account], slot)
+        }
+        leaf_chunks => {
+            let account_config = l2_account_config(ctx, min_context_slot);
+            let (client, addresses) = (ctx.l2_client.clone(), leaf_chunks.to_vec());
+            let response = ctx
+                .rpc_call(move || Ok(client.get_multiple_accounts_with_config(&addresses, account_config.clone())?))
+                .await?;
+            (response.value, response.context.slot)
+        }
+    };
+
+    let mut chunks = Vec::with_capacity(accounts.len());
+    for (index, (pubkey, account)) in leaf_chunks.iter().zip(accounts).enumerate() {
+        let account = match account {
+            Some(account) if account.data.iter().any(|byte| *byte != 0) => account,
+            account => {
+                let uninitialized = account.is_some();
+                error!(leaf_chunk = %pubkey, index, uninitialized, "leaf chunk account missing");
+                metrics::LEAF_CHUNK_MISSING.inc();
+                return Err(BridgeError::LeafChunkMissing {
+                    index,
+                    address: *pubkey,
+                    uninitialized,
+                }
+                .into());
+            }
+        };
+        // An account owned by another program would yield a meaningless root
+        if let Some(expected_owner) = &ctx.leaf_chunk_owner {
+            if &account.owner != expected_owner {
+                return Err(eyre::eyre!(
+                    "leaf chunk account {} is owned by {}, expected {}",
+                    pubkey,
+                    account.owner,
+                    expected_owner
+                ));
+            }
+        }
+        chunks.push(account);
+    }
+
+    let mut account = chunks[0].clone();
+    if chunks.len() > 1 {
+        let data: Vec<&[u8]> = chunks.iter().map(|chunk| chunk.data.as_slice()).collect();
+        account.data = concat_chunks(&data, &ctx.leaf_chunk_layout)?;
+    }
+    Ok((account, slot))
+}
+
+fn l2_account_config(ctx: &BridgeContext, min_context_slot: Option<u64>) -> RpcAccountInfoConfig {
+    RpcAccountInfoConfig {
+        encoding: Some(ctx.config.settings.account_encoding.into()),
+        commitment: Some(ctx.config.network.l2_commitment.into()),
+        min_context_slot,
+        ..RpcAccountInfoConfig::default()
+    }
+}
+
+/// Extracts both roots from the leaf chunk account and, when configured, the world state account
+///
+/// `context_slot` is the slot the RPC served the account at; it is replaced by the
+/// slot stored in the account when `account.slot_source` is `account`.
+async fn parse_roots(
+    ctx: &BridgeContext,
+    account: &Account,
+    context_slot: u64,
+    min_context_slot: Option<u64>,
+) -> Result<L2Roots> {
+    // Parse merkle tree root from account data
+    let leaf_chunk = LeafChunk::from_bytes(&account.data, &ctx.leaf_chunk_layout)?;
+    debug!(discriminator = %hex::encode(leaf_chunk.discriminator), "parsed leaf chunk");
+
+    // The data's own slot can lag the RPC's, and the L1 PDA must match the data
+    let slot = match leaf_chunk.slot {
+        Some(account_slot) => {
+            debug!(account_slot, context_slot, "using slot stored in leaf chunk");
+            account_slot
+        }
+        None => context_slot,
+    };
+
+    // Read world state root, falling back to zero bytes when no account is configured
+    let ws_root = match (&ctx.ws_root_override, &ctx.world_state) {
+        (Some(ws_root), _) => {
+            warn!(ws_root = %hex::encode(ws_root), "submitting world state root given by --ws-root");
+            *ws_root
+        }
+        (None, Some(reader)) => reader.read(ctx, min_context_slot).await?,
+        (None, None) => {
+            warn!("world_state_account not configured, submitting zero world state root");
+            [0u8; 32]
+        }
+    };
+
+    let mt_root = match &ctx.mt_root_override {
+        Some(mt_root) => {
+            warn!(mt_root = %hex::encode(mt_root), "submitting merkle tree root given by --mt-root");
+            mt_root.clone()
+        }
+        None => leaf_chunk.mt_root,
+    };
+
+    Ok(L2Roots { slot, mt_root, ws_root })
+}
+
+/// Reads the latest roots from L2
+pub async fn read_roots(ctx: &BridgeContext) -> Result<L2Roots> {
+    let (account, slot) = fetch_leaf_chunks(ctx, None).await?;
+
+    parse_roots(ctx, &account, slot, Some(slot)).await
+}
+
+/// Reads the roots as of exactly `slot`, or `None` when the L2 RPC cannot serve that slot
+///
+/// Standard Solana RPC nodes only serve the latest account state, so this usually
+/// succeeds only against L2 nodes that retain historical account data.
+async fn read_roots_at(ctx: &BridgeContext, slot: u64) -> Result<Option<L2Roots>> {
+    let (account, served_slot) = fetch_leaf_chunks(ctx, Some(slot)).await?;
+    if served_slot != slot {
+        return Ok(None);
+    }
+    Ok(Some(parse_roots(ctx, &account, slot, Some(slot)).await?))
+}
+
+/// Checks that both networks report the expected genesis hash, guarding against swapped endpoints
+pub async fn check_clusters(ctx: &BridgeContext) -> Result<()> {
+    let network = &ctx.config.network;
+    let (l1_client, l2_client) = (ctx.l1_client.clone(), ctx.l2_client.clone());
+    let clusters = [
+        ("l1", ctx.rpc_call(move || Ok(l1_client.get_genesis_hash()?)).await?, &network.expected_l1_genesis),
+        ("l2", ctx.rpc_call(move || Ok(l2_client.get_genesis_hash()?)).await?, &network.expected_l2_genesis),
+    ];
+    for (name, genesis, expected) in clusters {
+        info!(network = name, %genesis, "connected to cluster");
+
+        if let Some(expected) = expected {
+            let expected = parse_hash(&format!("network.expected_{}_genesis", name), expected)?;
+            if genesis != expected {
+                return Err(eyre::eyre!(
+                    "{}: connected to wrong cluster: expected {} got {}",
+                    name,
+                    expected,
+                    genesis
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Logs the effective configuration, defaults included, and the values derived from it
+///
+/// Secrets are never logged: base58 and env var wallets are redacted by their `Debug` impl.
+pub async fn log_startup_banner(ctx: &BridgeContext) {
+    info!(
+        version = env!("CARGO_PKG_VERSION"),
+        config = ?ctx.config,
+        "effective configuration"
+    );
+    info!(
+        wallet = %ctx.wallet.pubkey(),
+        fee_payer = %ctx.payer().pubkey(),
+        nonce_account = ctx.nonce.as_ref().map(|nonce| nonce.account.to_string()),
+        lookup_table = ctx.lookup_table.map(|table| table.to_string()),
+        leaf_chunks = ?ctx.leaf_chunks.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+        leaf_chunk_discriminator = ctx.leaf_chunk_layout.discriminator.map(hex::encode),
+        "derived configuration"
+    );
+
+    // The PDA is only known once the current L2 slot has been read
+    let slot = match read_roots(ctx).await {
+        Ok(roots) => Some(roots.slot),
+        Err(e) => {
+            warn!(error = format!("{:#}", e), "could not read current L2 slot for startup banner");
+            None
+        }
+    };
+    for target in &ctx.targets {
+        let pda = slot.and_then(|slot| ctx.config.program_slot(slot).ok()).map(|slot| target.slot_roots_pda(slot));
+        info!(
+            l1_target = %target.name,
+            program_id = %target.program_id,
+            slots_account = %target.slots_account,
+            state_file = %target.state_file,
+            instruction_discriminators = %target
+                .instructions
+                .iter()
+                .map(|instruction| hex::encode(instruction.discriminator))
+                .collect::<Vec<_>>()
+                .join(","),
+            slot,
+            slot_roots_account = pda.map(|(pda, _)| pda.to_string()),
+            bump = pda.map(|(_, bump)| bump),
+            "target configuration"
+        );
+    }
+}
+
+/// Reads back the slot roots account from L1 and checks it holds `expected_mt_root`
+pub async fn verify_submission(
+    ctx: &BridgeContext,
+    target: &L1Target,
+    slot: u64,
+    expected_mt_root: &[u8],
+) -> Result<()> {
+    let (slot_roots_account, _) = target.slot_roots_pda(ctx.config.program_slot(slot)?);
+    let stored_mt_root = read_stored_mt_root(ctx, target, slot)
+        .await?
+        .ok_or(BridgeError::AccountNotFound {
+            account: "slot roots",
+            address: slot_roots_account,
+        })?;
+    if stored_mt_root != expected_mt_root {
+        error!(
+            slot,
+            %slot_roots_account,
+            expected = %hex::encode(expected_mt_root),
+            stored = %hex::encode(&stored_mt_root),
+            "L1 slot roots account does not hold the submitted merkle root"
+        );
+        return Err(eyre::eyre!(
+            "L1 root mismatch for slot {}: expected {} got {}",
+            slot,
+            hex::encode(expected_mt_root),
+            hex::encode(&stored_mt_root)
+        ));
+    }
+
+    debug!(slot, %slot_roots_account, "verified submitted root on L1");
+    Ok(())
+}
+
+/// Reads the merkle root stored on L1 for `slot`, `None` while its slot roots account does not exist
+async fn read_stored_mt_root(ctx: &BridgeContext, target: &L1Target, slot: u64) -> Result<Option<Vec<u8>>> {
+    let (slot_roots_account, _) = target.slot_roots_pda(ctx.config.program_slot(slot)?);
+    let commitment = ctx.config.confirm_commitment();
+
+    let client = ctx.l1_client.clone();
+    let account = ctx
+        .rpc_call(move || Ok(client.get_account_with_commitment(&slot_roots_account, commitment)?))
+        .await?
+        .value;
+    account
+        .map(|account| extract_bytes(&account.data, target.roots_mt_root_offset, ctx.config.account.root_len))
+        .transpose()
+}
+
+/// Checks whether L1 already holds `roots` instead of submitting them, recording the slot when it does
+///
+/// The lag is measured from the last slot seen on L1, as recorded in the target's state file.
+async fn observe_roots(ctx: &BridgeContext, target: &L1Target, roots: &L2Roots) -> Result<CycleOutcome> {
+    let slot = roots.slot;
+    let expected_root = roots.for_program(&ctx.config)?.stored_root(&ctx.config.settings);
+    let l1_lag_slots = match read_stored_mt_root(ctx, target, slot).await? {
+        Some(stored_mt_root) if stored_mt_root == expected_root => {
+            info!(slot, "in sync, L1 holds the current roots");
+            record_submitted(target, slot, &roots.mt_root, &roots.ws_root)?;
+            Some(0)
+        }
+        Some(stored_mt_root) => {
+            return Err(eyre::eyre!(
+                "L1 root mismatch for slot {}: expected {} got {}",
+                slot,
+                hex::encode(&expected_root),
+                hex::encode(&stored_mt_root)
+            ));
+        }
+        None => {
+            let lag_slots = state::load_last_slot(&target.state_file).map(|last_slot| slot.saturating_sub(last_slot));
+            match lag_slots {
+                Some(lag_slots) => warn!(slot, lag_slots, "L1 lagging by {} slots", lag_slots),
+                None => warn!(slot, "L1 does not hold the current roots and no earlier slot was seen on L1"),
+            }
+            lag_slots
+        }
+    };
+
+    if let Some(lag_slots) = l1_lag_slots {
+        metrics::OBSERVER_LAG_SLOTS
+            .with_label_values(&[&target.name])
+            .set(lag_slots as i64);
+        if let Some(max_lag_slots) = ctx.config.settings.observer_max_lag_slots.filter(|max| lag_slots > *max) {
+            return Err(BridgeError::L1Lagging {
+                slot,
+                lag_slots,
+                max_lag_slots,
+            }
+            .into());
+        }
+    }
+    Ok(CycleOutcome::Observed { slot, l1_lag_slots })
+}
+
+/// Fetches the configured address lookup table, if any, for compiling `v0` messages
+async fn fetch_lookup_tables(ctx: &BridgeContext) -> Result<Vec<AddressLookupTableAccount>> {
+    let Some(key) = ctx.lookup_table else {
+        return Ok(Vec::new());
+    };
+    let commitment = ctx.config.network.l1_commitment.into();
+    let account = ctx
+        .get_l1_account(&key, commitment)
+        .await?
+        .ok_or(BridgeError::AccountNotFound {
+            account: "address lookup table",
+            address: key,
+        })?;
+    let table = AddressLookupTable::deserialize(&account.data)
+        .map_err(|e| eyre::eyre!("address lookup table {}: {}", key, e))?;
+    debug!(%key, addresses = table.addresses.len(), "resolved address lookup table");
+
+    Ok(vec![AddressLookupTableAccount {
+        key,
+        addresses: table.addresses.to_vec(),
+    }])
+}
+
+/// Signs the instructions into a transaction paid for by the fee payer, in the configured format
+///
+/// A Ledger blocks until the transaction is approved on the device, so signing runs on
+/// the blocking pool.
+async fn sign_transaction(
+    ctx: &BridgeContext,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+) -> Result<VersionedTransaction> {
+    let unsigned = unsigned_transaction(ctx, instructions, lookup_tables, recent_blockhash)?;
+    let signers = ctx.signers();
+    tokio::task::spawn_blocking(move || {
+        let signers: Vec<&dyn Signer> = signers.iter().map(|signer| signer.as_ref() as &dyn Signer).collect();
+        Ok(VersionedTransaction::try_new(unsigned.message, &signers)?)
+    })
+    .await?
+}
+
+/// Compiles the instructions into a transaction with placeholder signatures, for simulating
+///
+/// Simulation skips signature verification, so it never waits on a hardware wallet.
+fn unsigned_transaction(
+    ctx: &BridgeContext,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+) -> Result<VersionedTransaction> {
+    let payer = ctx.payer().pubkey();
+    let message = match ctx.config.settings.tx_version {
+        TxVersion::Legacy => {
+            VersionedMessage::Legacy(Message::new_with_blockhash(instructions, Some(&payer), &recent_blockhash))
+        }
+        TxVersion::V0 => {
+            VersionedMessage::V0(v0::Message::try_compile(&payer, instructions, lookup_tables, recent_blockhash)?)
+        }
+    };
+    let transaction = VersionedTransaction {
+        signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+        message,
+    };
+    let size = bincode::serialized_size(&transaction)?;
+    if size > PACKET_DATA_SIZE as u64 {
+        return Err(BridgeError::TransactionTooLarge {
+            instructions: instructions.len(),
+            size,
+            max: PACKET_DATA_SIZE,
+        }
+        .into());
+    }
+    Ok(transaction)
+}
+
+/// Main function to check L2 state and submit to L1
+///
+/// The roots are read once and offered to every target; a failing target does
+/// not prevent submission to the others.
+#[tracing::instrument(name = "cycle", skip_all)]
+pub async fn check_and_submit(ctx: &BridgeContext) -> Result<CycleReport> {
+    // Get L2 roots and corresponding slot
+    let started = Instant::now();
+    let roots = read_roots(ctx).await?;
+    let fetch = started.elapsed();
+    metrics::observe_duration("fetch", fetch);
+
+    // A lagging RPC, e.g. right after failover, can serve state we have already moved past
+    let max_seen_slot = ctx.max_seen_slot.load(Ordering::Relaxed);
+    if roots.slot.saturating_add(ctx.config.settings.slot_regression_tolerance) < max_seen_slot {
+        return Err(BridgeError::SlotRegression {
+            slot: roots.slot,
+            max_seen_slot,
+        }
+        .into());
+    }
+    ctx.max_seen_slot.fetch_max(roots.slot, Ordering::Relaxed);
+
+    debug!(
+        slot = roots.slot,
+        mt_root = %hex::encode(&roots.mt_root),
+        ws_root = %hex::encode(roots.ws_root),
+        fetch_ms = fetch.as_millis() as u64,
+        "read roots from L2"
+    );
+
+    // Roots that could still be rolled back on L2 wait for a later cycle
+    let min_depth = ctx.config.settings.min_confirmation_depth;
+    if min_depth > 0 {
+        let client = ctx.l2_client.clone();
+        let finalized_slot = ctx
+            .rpc_call(move || Ok(client.get_slot_with_commitment(CommitmentConfig::finalized())?))
+            .await?;
+        let depth = finalized_slot.saturating_sub(roots.slot);
+        if finalized_slot < roots.slot.saturating_add(min_depth) {
+            info!(slot = roots.slot, finalized_slot, depth, min_depth, "root too recent, deferring");
+            let targets = ctx
+                .targets
+                .iter()
+                .map(|target| TargetResult {
+                    target: target.name.clone(),
+                    result: Ok(CycleOutcome::Skipped {
+                        slot: roots.slot,
+                        reason: "root below confirmation depth",
+                    }),
+                })
+                .collect();
+            return Ok(CycleReport { roots, targets });
+        }
+    }
+
+    let mut targets = Vec::with_capacity(ctx.targets.len());
+    for (target, pending) in ctx.targets.iter().zip(&ctx.pending) {
+        let result = match submit_pending(ctx, target, pending).await {
+            Ok(()) => submit_to_target(ctx, target, &roots).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = &result {
+            queue_if_unreachable(ctx, target, pending, &roots, e);
+        }
+        let result = match result {
+            Err(e) if matches!(BridgeError::find(&e), Some(BridgeError::FatalProgram { .. })) => {
+                return Err(e.wrap_err(format!("L1 target {}", target.name)))
+            }
+            result => result,
+        };
+        if let Err(e) = measure_l1_lag(ctx, target, roots.slot).await {
+            warn!(l1_target = %target.name, error = %e, "failed to measure L1 lag");
+        }
+        targets.push(TargetResult {
+            target: target.name.clone(),
+            result,
+        });
+    }
+    Ok(CycleReport { roots, targets })
+}
+
+/// Submits the roots queued while the target's L1 was unreachable, oldest first
+///
+/// Stops at the first transient or fatal failure with the rest still queued;
+/// roots L1 refuses for any other reason are dropped.
+async fn submit_pending(ctx: &BridgeContext, target: &L1Target, pending: &PendingRoots) -> Result<()> {
+    while let Some(queued) = pending.front() {
+        match submit_to_target(ctx, target, &queued).await {
+            Ok(outcome) => info!(slot = queued.slot, ?outcome, remaining = pending.len() - 1, "submitted queued roots"),
+            Err(e) if is_transient(&e) || matches!(BridgeError::find(&e), Some(BridgeError::FatalProgram { .. })) => {
+                return Err(e)
+            }
+            Err(e) => warn!(slot = queued.slot, error = format!("{:#}", e), "dropping queued roots L1 refused"),
+        }
+        pending.pop_front()?;
+    }
+    Ok(())
+}
+
+/// Keeps `roots` for a later cycle when submitting failed because L1 could not be reached
+///
+/// L2 reads carry on meanwhile; observers have nothing to submit and never queue.
+fn queue_if_unreachable(ctx: &BridgeContext, target: &L1Target, pending: &PendingRoots, roots: &L2Roots, err: &eyre::Report) {
+    if ctx.config.settings.observer || !is_unreachable(err) {
+        return;
+    }
+    match pending.push(roots) {
+        Ok(true) => warn!(
+            l1_target = %target.name,
+            slot = roots.slot,
+            pending = pending.len(),
+            "L1 unreachable, queued roots until it recovers"
+        ),
+        Ok(false) => {}
+        Err(e) => warn!(l1_target = %target.name, slot = roots.slot, error = %e, "failed to persist pending roots"),
+    }
+}
+
+/// Returns true when `err` means L1 could not be reached, as opposed to a transaction that was sent
+/// but did not land or a local failure
+fn is_unreachable(err: &eyre::Report) -> bool {
+    match BridgeError::find(err) {
+        Some(BridgeError::RpcTransient(_) | BridgeError::RpcTimeout { .. }) => true,
+        Some(_) => false,
+        None => err
+            .chain()
+            .find_map(|e| e.downcast_ref::<ClientError>())
+            .is_some_and(rpc::is_connection_error),
+    }
+}
+
+/// Reads the highest slot recorded in the target's slots account and reports how far it trails `l2_slot`
+///
+/// Does nothing unless the target's `program.last_slot_offset` is configured.
+pub async fn measure_l1_lag(ctx: &BridgeContext, target: &L1Target, l2_slot: u64) -> Result<Option<u64>> {
+    let Some(offset) = target.last_slot_offset else {
+        return Ok(None);
+    };
+    let commitment = ctx.config.confirm_commitment();
+    let (client, slots_account) = (ctx.l1_client.clone(), target.slots_account);
+    let account = ctx
+        .rpc_call(move || Ok(client.get_account_with_commitment(&slots_account, commitment)?.value))
+        .await?
+        .ok_or(BridgeError::AccountNotFound {
+            account: "slots",
+            address: target.slots_account,
+        })?;
+    let l1_slot = extract_slot(&account.data, offset)?;
+
+    let lag = l2_slot.saturating_sub(l1_slot);
+    metrics::L1_LAG_SLOTS.with_label_values(&[&target.name]).set(lag as i64);
+    health::HEALTH.set_l1_lag(&target.name, lag);
+    info!(l1_target = %target.name, l2_slot, l1_slot, lag_slots = lag, "measured L1 lag");
+    Ok(Some(lag))
+}
+
+/// Compares the local clock with the time of the latest L1 block, warning past `settings.max_clock_skew_secs`
+///
+/// Returns the local time minus the block time, in seconds.
+pub async fn check_clock_skew(ctx: &BridgeContext) -> Result<i64> {
+    let commitment = ctx.config.network.l1_commitment.into();
+    let client = ctx.l1_client.clone();
+    let slot = ctx.rpc_call(move || Ok(client.get_slot_with_commitment(commitment)?)).await?;
+    let client = ctx.l1_client.clone();
+    let block_time = ctx.rpc_call(move || Ok(client.get_block_time(slot)?)).await?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+
+    let skew = now - block_time;
+    metrics::CLOCK_SKEW_SECONDS.set(skew);
+    let max_skew = ctx.config.settings.max_clock_skew_secs;
+    if skew.unsigned_abs() > max_skew {
+        warn!(slot, block_time, skew_secs = skew, max_skew_secs = max_skew, "local clock skewed from L1 block time");
+    } else {
+        debug!(slot, skew_secs = skew, "checked clock skew");
+    }
+    Ok(skew)
+}
+
+/// Offers the roots to a single target, skipping slots and roots it already holds
+#[tracing::instrument(name = "target", skip_all, fields(l1_target = %target.name))]
+async fn submit_to_target(ctx: &BridgeContext, target: &L1Target, roots: &L2Roots) -> Result<CycleOutcome> {
+    let config = &ctx.config;
+    let account_slot = roots.slot;
+
+    // Replicas split the slots between them instead of racing for the same PDA
+    if let Some(replicas) = config.settings.leader_slot_modulo.filter(|_| !config.settings.observer) {
+        if account_slot % replicas != config.settings.replica_index {
+            debug!(slot = account_slot, replicas, replica_index = config.settings.replica_index, "slot assigned to another replica");
+            return Ok(CycleOutcome::Skipped {
+                slot: account_slot,
+                reason: "slot assigned to another replica",
+            });
+        }
+    }
+
+    // Skip slots that were already submitted, e.g. before a restart
+    let mut last_state = state::load_state(&target.state_file);
+    if let Some(last_state) = &last_state {
+        if account_slot <= last_state.last_slot {
+            info!(slot = account_slot, last_slot = last_state.last_slot, "slot already submitted, skipping");
+            return Ok(CycleOutcome::Skipped {
+                slot: account_slot,
+                reason: "slot already submitted",
+            });
+        }
+    }
+
+    info!(
+        slot = account_slot,
+        mt_root = %hex::encode(&roots.mt_root),
+        ws_root = %hex::encode(roots.ws_root),
+        "read roots from L2"
+    );
+
+    // Fill in slots missed while the service was down before submitting the latest one
+    if config.settings.max_backfill_slots > 0 && !config.settings.observer {
+        if let Some(last_slot) = last_state.as_ref().map(|state| state.last_slot) {
+            backfill_missed_slots(ctx, target, last_slot, account_slot).await?;
+            last_state = state::load_state(&target.state_file);
+        }
+    }
+
+    // Resubmitting identical roots only burns fees
+    let Some(last_state) = last_state else {
+        return submit_roots(ctx, target, roots).await;
+    };
+    if last_state.roots_match(&roots.mt_root, &roots.ws_root) {
+        info!(slot = account_slot, "root unchanged, skipping");
+        return Ok(CycleOutcome::Skipped {
+            slot: account_slot,
+            reason: "root unchanged",
+        });
+    }
+
+    // Slot-based rate limit on top of the check interval; the root has changed at this point
+    let slot_gap = account_slot - last_state.last_slot;
+    if slot_gap < config.settings.min_slot_gap && !config.settings.force_on_root_change {
+        info!(
+            slot = account_slot,
+            last_slot = last_state.last_slot,
+            min_slot_gap = config.settings.min_slot_gap,
+            "minimum slot gap not reached, skipping"
+        );
+        return Ok(CycleOutcome::Skipped {
+            slot: account_slot,
+            reason: "minimum slot gap not reached",
+        });
+    }
+    
+    submit_roots(ctx, target, roots).await
+}
+
+/// Submits roots for the slots strictly between `last_slot` and `current_slot`, oldest first
+///
+/// At most `settings.max_backfill_slots` slots are attempted; slots whose historical
+/// state cannot be read from L2 are logged and skipped.
+async fn backfill_missed_slots(
+    ctx: &BridgeContext,
+    target: &L1Target,
+    last_slot: u64,
+    current_slot: u64,
+) -> Result<()> {
+    let max_backfill = ctx.config.settings.max_backfill_slots;
+    let first_missing = last_slot + 1;
+    if first_missing >= current_slot {
+        return Ok(());
+    }
+
+    let start = first_missing.max(current_slot.saturating_sub(max_backfill));
+    if start > first_missing {
+        warn!(
+            from = first_missing,
+            to = start - 1,
+            "missed slots exceed max_backfill_slots, not backfilling them"
+        );
+    }
+
+    for slot in start..current_slot {
+        match read_roots_at(ctx, slot).await? {
+            Some(roots) => {
+                info!(slot, "backfilling missed slot");
+                submit_roots(ctx, target, &roots).await?;
+            }
+            None => warn!(slot, "historical L2 state unavailable, skipping slot"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Outcome of one slot of an on-demand backfill
+#[derive(Debug)]
+pub enum BackfillSlot {
+    /// The L2 RPC could not serve the slot's historical state
+    Unavailable,
+    /// Reading the slot's roots from L2 failed
+    ReadFailed(eyre::Report),
+    /// The roots were offered to every target
+    Offered(Vec<TargetResult>),
+}
+
+/// Submits the roots of `slot` to every target, ignoring what the state files record
+///
+/// Used to re-post known ranges, e.g. after an L1 program redeploy wiped its accounts.
+#[tracing::instrument(name = "backfill", skip(ctx))]
+pub async fn backfill_slot(ctx: &BridgeContext, slot: u64) -> BackfillSlot {
+    let roots = match read_roots_at(ctx, slot).await {
+        Ok(Some(roots)) => roots,
+        Ok(None) => return BackfillSlot::Unavailable,
+        Err(e) => return BackfillSlot::ReadFailed(e),
+    };
+
+    let mut targets = Vec::with_capacity(ctx.targets.len());
+    for target in &ctx.targets {
+        targets.push(TargetResult {
+            target: target.name.clone(),
+            result: submit_roots(ctx, target, &roots).await,
+        });
+    }
+    BackfillSlot::Offered(targets)
+}
+
+/// Builds, signs and sends the L1 transaction recording `roots`, persisting the slot on success
+async fn submit_roots(ctx: &BridgeContext, target: &L1Target, roots: &L2Roots) -> Result<CycleOutcome> {
+    let config = &ctx.config;
+    if config.settings.observer {
+        return observe_roots(ctx, target, roots).await;
+    }
+    // Unlike the state file this covers every slot, so backfills in any order stay idempotent
+    let program_id = target.program_id.to_string();
+    if let Some(entry) = ctx.submission_log.as_ref().and_then(|log| log.find(&program_id, roots.slot)) {
+        if entry.roots_match(&roots.mt_root, &roots.ws_root) {
+            info!(slot = roots.slot, signature = %entry.signature, "roots already in submission log, skipping");
+            return Ok(CycleOutcome::Skipped {
+                slot: roots.slot,
+                reason: "already in submission log",
+            });
+        }
+        warn!(slot = roots.slot, signature = %entry.signature, "submission log holds different roots for this slot");
+    }
+    let account_slot = roots.slot;
+    let mt_root = &roots.mt_root;
+    let ws_root = roots.ws_root;
+    let program_roots = roots.for_program(config)?;
+    let stored_root = program_roots.stored_root(&config.settings);
+    
+    let slots_account = target.slots_account;
+    let build_started = Instant::now();
+    
+    // Calculate PDA for slot roots account
+    let (slot_roots_account, _) = target.slot_roots_pda(program_roots.slot);
+    
+    // Resolve priority fee for the accounts this transaction writes
+    let (client, fees) = (ctx.l1_client.clone(), config.fees.clone());
+    let compute_unit_price = ctx
+        .rpc_call(move || fees::resolve_compute_unit_price(&fees, client.as_ref(), &[slots_account, slot_roots_account]))
+        .await?;
+    info!(compute_unit_price, "resolved compute unit price");
+
+    // Fail early with a clear error rather than after sending
+    let signatures = ctx.signers().len() as u64;
+    let fee_estimate = balance::estimate_fee(signatures, compute_unit_price, config.fees.compute_unit_limit)
+        + ctx.jito.as_ref().map_or(0, JitoClient::tip_lamports);
+    if let Some(max_fee) = config.settings.max_fee_lamports.filter(|&max_fee| fee_estimate > max_fee) {
+        warn!(slot = account_slot, compute_unit_price, "fee {} exceeds cap {}, skipping", fee_estimate, max_fee);
+        return Ok(CycleOutcome::Skipped {
+            slot: account_slot,
+            reason: "fee exceeds cap",
+        });
+    }
+    balance::check_fee_payer_balance(ctx, &ctx.payer().pubkey(), fee_estimate).await?;
+    
+    let bridge_instructions = bridge_instructions(ctx, target, &program_roots, slot_roots_account);
+    let instruction_data: Vec<String> = bridge_instructions.iter().map(|instruction| hex::encode(&instruction.data)).collect();
+    let (authorized, proposal) = authorize(ctx, bridge_instructions).await?;
+    let instructions = submission_instructions(ctx, authorized, compute_unit_price);
+    
+    let lookup_tables = fetch_lookup_tables(ctx).await?;
+    let build = build_started.elapsed();
+    metrics::observe_duration("build", build);
+
+    if config.settings.dry_run {
+        let recent_blockhash = ctx.recent_blockhash().await?;
+        // Left unsigned, so a dry run never waits on a hardware wallet
+        let transaction = unsigned_transaction(ctx, &instructions, &lookup_tables, recent_blockhash)?;
+        info!(
+            slot = account_slot,
+            %slot_roots_account,
+            instruction_data = %instruction_data.join(","),
+            fee_payer = %ctx.payer().pubkey(),
+            message_hash = %transaction.message.hash(),
+            "dry run, not submitting transaction"
+        );
+        return Ok(CycleOutcome::DryRun { slot: account_slot });
+    }
+    
+    // Each attempt signs with a fresh blockhash so a timed-out send can be retried
+    let result = async {
+        if config.settings.simulate_first {
+            simulate(ctx, &instructions, &lookup_tables, account_slot).await?;
+        }
+        with_retry(&ctx.confirm_retry_policy, || async {
+            // An expired blockhash needs a rebuilt transaction rather than a plain retry
+            let mut blockhash_retries = config.retry.blockhash_retries;
+            loop {
+                let recent_blockhash = rpc::blocking(ctx.blockhash_lookup()).await?;
+                match send_submission(ctx, &instructions, &lookup_tables, recent_blockhash, account_slot).await {
+                    Err(e) if blockhash_retries > 0 && confirm::is_blockhash_not_found(&e) => {
+                        blockhash_retries -= 1;
+                        warn!(slot = account_slot, remaining = blockhash_retries, "blockhash not found, re-signing transaction");
+                    }
+                    result => return result,
+                }
+            }
+        })
+        .await
+    }
+    .await;
+    
+    // Another proposer may have created this slot's account first, which is benign
+    let signature = match result {
+        Ok(signature) => signature,
+        Err(e) if config.program_errors.is_already_initialized(&e) => {
+            info!(slot = account_slot, %slot_roots_account, error = %e, "slot roots already recorded on L1");
+            if config.settings.verify_submission {
+                verify_submission(ctx, target, account_slot, &stored_root).await?;
+            }
+            record_submitted(target, account_slot, mt_root, &ws_root)?;
+            return Ok(CycleOutcome::Skipped {
+                slot: account_slot,
+                reason: "already recorded on L1",
+            });
+        }
+        Err(e) => {
+            let action = config.program_errors.action(&e);
+            if action == Some(ErrorAction::Ignore) {
+                info!(slot = account_slot, error = %e, "ignoring program error, recording slot as submitted");
+                record_submitted(target, account_slot, mt_root, &ws_root)?;
+                return Ok(CycleOutcome::Skipped {
+                    slot: account_slot,
+                    reason: "program error ignored",
+                });
+            }
+            let e = with_program_logs(ctx, &instructions, &lookup_tables, account_slot, e).await;
+            if action == Some(ErrorAction::Fatal) {
+                let code = program_errors::custom_error_code(&e).expect("fatal action has a code");
+                return Err(e.wrap_err(BridgeError::FatalProgram { code }));
+            }
+            return Err(e);
+        }
+    };
+    info!(slot = account_slot, %signature, build_ms = build.as_millis() as u64, "transaction confirmed");
+
+    metrics::PROPOSALS_SUBMITTED.inc();
+    record_fee(ctx, account_slot, &signature, balance::priority_fee(compute_unit_price, config.fees.compute_unit_limit)).await;
+
+    // Nothing reaches the slot roots account until the approvers execute the proposal,
+    // so a proposal is neither logged nor audited
+    if let Some(proposal) = proposal {
+        info!(slot = account_slot, %proposal, %signature, "created multisig proposal, awaiting approval");
+        record_submitted(target, account_slot, mt_root, &ws_root)?;
+        return Ok(CycleOutcome::Proposed {
+            slot: account_slot,
+            signature,
+            proposal,
+        });
+    }
+
+    if config.settings.verify_submission {
+        verify_submission(ctx, target, account_slot, &stored_root).await?;
+    }
+
+    // A slot that did not finalize is neither logged nor recorded, so the next cycle submits it again
+    if await_finalized(ctx, account_slot, &signature).await? {
+        // Logged only once verified: a logged slot is never submitted again, and audited roots must have landed
+        log_submission(ctx, &program_id, roots, &signature)?;
+        record_submitted(target, account_slot, mt_root, &ws_root)?;
+    }
+    
+    Ok(CycleOutcome::Submitted {
+        slot: account_slot,
+        signature,
+    })
+}
+
+/// Waits up to `settings.post_submit_finalize_wait_secs` for a confirmed submission to be finalized
+///
+/// Returns whether the slot may be persisted: always when no wait is configured, and
+/// otherwise only once the transaction finalized.
+async fn await_finalized(ctx: &BridgeContext, slot: u64, signature: &Signature) -> Result<bool> {
+    let Some(wait_secs) = ctx.config.settings.post_submit_finalize_wait_secs else {
+        return Ok(true);
+    };
+    let options = ConfirmOptions {
+        commitment: CommitmentConfig::finalized(),
+        timeout: Duration::from_secs(wait_secs),
+        // The transaction already landed, so its blockhash expiring says nothing
+        blockhash: None,
+        ..ctx.config.confirm_options(None)
+    };
+    match confirm::poll_confirmation(&ctx.l1_client, signature, &options).await? {
+        ConfirmationOutcome::Finalized => Ok(true),
+        outcome => {
+            warn!(slot, %signature, ?outcome, wait_secs, "submission not finalized, not advancing the last submitted slot");
+            Ok(false)
+        }
+    }
+}
+
+/// Signs and sends a submission, waiting for it to reach the confirm commitment
+///
+/// With `[jito]` configured the transaction, with a tip appended, goes out as a block
+/// engine bundle; a rejected bundle falls back to sending it without the tip over RPC.
+async fn send_submission(
+    ctx: &BridgeContext,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+    slot: u64,
+) -> Result<Signature> {
+    let l1_client = &ctx.l1_client;
+    // A durable nonce transaction never expires, so it can only time out
+    let options = ctx.config.confirm_options(ctx.nonce.is_none().then_some(recent_blockhash));
+
+    if let Some(jito) = &ctx.jito {
+        let mut bundled = instructions.to_vec();
+        bundled.push(jito.tip_instruction(&ctx.payer().pubkey()));
+        let transaction = sign_transaction(ctx, &bundled, lookup_tables, recent_blockhash).await?;
+        let started = Instant::now();
+        match jito.send_bundle(&transaction).await {
+            Ok(bundle_id) => {
+                metrics::observe_duration("send", started.elapsed());
+                info!(slot, %bundle_id, tip_lamports = jito.tip_lamports(), "sent bundle to block engine");
+                return confirm::await_confirmation(l1_client, transaction.signatures[0], &options, started).await;
+            }
+            Err(e) => warn!(slot, error = %e, "bundle rejected, falling back to RPC submission"),
+        }
+    }
+
+    let transaction = sign_transaction(ctx, instructions, lookup_tables, recent_blockhash).await?;
+    let send_config = ctx.config.send.rpc_config(ctx.config.network.l1_commitment);
+    confirm::send_and_confirm(l1_client, &transaction, send_config, &ctx.send_retry_policy, &options).await
+}
+
+/// Encodes the data of `instruction` recording `roots`: the discriminator followed by its data template
+pub fn instruction_data(settings: &SettingsConfig, instruction: &InstructionLayout, roots: &L2Roots) -> Vec<u8> {
+    let mut instruction_data = instruction.discriminator.to_vec();
+    for part in &instruction.data {
+        match part {
+            DataPart::Literal(bytes) => instruction_data.extend_from_slice(bytes),
+            DataPart::Slot => instruction_data.extend_from_slice(&roots.slot.to_le_bytes()),
+            DataPart::SlotBe => instruction_data.extend_from_slice(&roots.slot.to_be_bytes()),
+            DataPart::MtRoot => instruction_data.extend_from_slice(&roots.mt_root),
+            DataPart::WsRoot => instruction_data.extend_from_slice(&roots.ws_root),
+            DataPart::Roots => match roots.commitment(settings) {
+                Some(commitment) => instruction_data.extend_from_slice(&commitment),
+                None => {
+                    instruction_data.extend_from_slice(&roots.mt_root);
+                    instruction_data.extend_from_slice(&roots.ws_root);
+                }
+            },
+        }
+    }
+    instruction_data
+}
+
+/// Builds the instructions recording `roots` in `slot_roots_account`, in the configured order
+fn bridge_instructions(ctx: &BridgeContext, target: &L1Target, roots: &L2Roots, slot_roots_account: Pubkey) -> Vec<Instruction> {
+    target
+        .instructions
+        .iter()
+        .map(|instruction| {
+            let instruction_data = instruction_data(&ctx.config.settings, instruction, roots);
+            let mut accounts: Vec<AccountMeta> = instruction
+                .accounts
+                .iter()
+                .map(|account| {
+                    let pubkey = match account.role {
+                        AccountRole::Slots => target.slots_account,
+                        AccountRole::SystemProgram => solana_program::system_program::id(),
+                        AccountRole::SlotRootsPda => slot_roots_account,
+                        AccountRole::Authority => ctx.authority(),
+                    };
+                    account.to_account_meta(pubkey)
+                })
+                .collect();
+            accounts.extend(instruction.extra_accounts.iter().cloned());
+            Instruction::new_with_bytes(target.program_id, &instruction_data, accounts)
+        })
+        .collect()
+}
+
+/// Wraps `instructions` in a proposal to the multisig when one is configured, returning the proposal address
+///
+/// Without a multisig the wallet signs `instructions` itself.
+async fn authorize(ctx: &BridgeContext, instructions: Vec<Instruction>) -> Result<(Vec<Instruction>, Option<Pubkey>)> {
+    let Some(multisig) = &ctx.multisig else {
+        return Ok((instructions, None));
+    };
+    let account = ctx
+        .get_l1_account(&multisig.address, ctx.config.network.l1_commitment.into())
+        .await?
+        .ok_or(BridgeError::AccountNotFound {
+            account: "multisig",
+            address: multisig.address,
+        })?;
+    let index = multisig.transaction_index(&account.data)? + 1;
+    let instructions = multisig.propose_instructions(&instructions, index, &ctx.wallet.pubkey(), &ctx.payer().pubkey());
+    Ok((instructions, Some(multisig.proposal_pda(index))))
+}
+
+/// Prepends the compute budget and, when signing with a durable nonce, the nonce advance
+fn submission_instructions(ctx: &BridgeContext, authorized: Vec<Instruction>, compute_unit_price: u64) -> Vec<Instruction> {
+    let mut instructions = fees::compute_budget_instructions(compute_unit_price, ctx.config.fees.compute_unit_limit);
+    instructions.extend(authorized);
+    // Advancing the nonce has to be the transaction's first instruction
+    if let Some(nonce) = &ctx.nonce {
+        instructions.insert(0, nonce.advance_instruction());
+    }
+    instructions
+}
+
+/// Simulates submitting `roots` to `target` without sending anything
+pub async fn simulate_submission(ctx: &BridgeContext, target: &L1Target, roots: &L2Roots) -> Result<()> {
+    let program_roots = roots.for_program(&ctx.config)?;
+    let (slot_roots_account, _) = target.slot_roots_pda(program_roots.slot);
+    let (client, fees, slots_account) = (ctx.l1_client.clone(), ctx.config.fees.clone(), target.slots_account);
+    let compute_unit_price = ctx
+        .rpc_call(move || fees::resolve_compute_unit_price(&fees, client.as_ref(), &[slots_account, slot_roots_account]))
+        .await?;
+    let instructions = bridge_instructions(ctx, target, &program_roots, slot_roots_account);
+    let (authorized, _) = authorize(ctx, instructions).await?;
+    let instructions = submission_instructions(ctx, authorized, compute_unit_price);
+    let lookup_tables = fetch_lookup_tables(ctx).await?;
+    simulate(ctx, &instructions, &lookup_tables, roots.slot).await
+}
+
+/// Simulates the transaction, failing with the simulation's error before anything is sent
+async fn simulate(
+    ctx: &BridgeContext,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    slot: u64,
+) -> Result<()> {
+    let recent_blockhash = ctx.recent_blockhash().await?;
+    let transaction = unsigned_transaction(ctx, instructions, lookup_tables, recent_blockhash)?;
+    let simulation = simulate_transaction(ctx, transaction).await?;
+
+    let logs = simulation.logs.unwrap_or_default();
+    let Some(err) = simulation.err else {
+        debug!(slot, units_consumed = ?simulation.units_consumed, "simulation succeeded");
+        return Ok(());
+    };
+    warn!(slot, error = %err, "simulation failed, not sending transaction");
+    for line in &logs {
+        warn!(slot, "{}", line);
+    }
+    Err(confirm::attach_program_logs(
+        eyre::Report::new(err).wrap_err("transaction simulation failed"),
+        logs,
+    ))
+}
+
+/// Simulates `transaction` against the L1 client, without verifying its signatures
+async fn simulate_transaction(ctx: &BridgeContext, transaction: VersionedTransaction) -> Result<RpcSimulateTransactionResult> {
+    let client = ctx.l1_client.clone();
+    ctx.rpc_call(move || Ok(client.simulate_transaction(&transaction)?.value)).await
+}
+
+/// Attaches the program logs explaining a failed submission to its error
+///
+/// They come from the failed preflight check when the node ran one, otherwise from
+/// simulating the transaction again. Errors that are not transaction failures are
+/// returned unchanged.
+async fn with_program_logs(
+    ctx: &BridgeContext,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    slot: u64,
+    err: eyre::Report,
+) -> eyre::Report {
+    if err.downcast_ref::<confirm::ProgramLogs>().is_some() || program_errors::transaction_error(&err).is_none() {
+        return err;
+    }
+    let logs = match confirm::preflight_logs(&err) {
+        Some(logs) => logs,
+        None => {
+            let simulation = async {
+                let recent_blockhash = ctx.recent_blockhash().await?;
+                let transaction = unsigned_transaction(ctx, instructions, lookup_tables, recent_blockhash)?;
+                simulate_transaction(ctx, transaction).await
+            };
+            match simulation.await {
+                Ok(simulation) => simulation.logs.unwrap_or_default(),
+                Err(e) => {
+                    debug!(slot, error = %e, "could not simulate failed transaction for its logs");
+                    return err;
+                }
+            }
+        }
+    };
+    let err = confirm::attach_program_logs(err, logs);
+    if let Some(program_logs) = err.downcast_ref::<confirm::ProgramLogs>() {
+        warn!(slot, logs = ?program_logs.logs, "transaction failed");
+    }
+    err
+}
+
+/// Reads the fee charged for a confirmed submission and adds it to the spend counters
+///
+/// The priority part is derived from the compute budget we requested. Failing to read
+/// the fee is only logged, as the submission itself succeeded.
+async fn record_fee(ctx: &BridgeContext, slot: u64, signature: &Signature, priority_fee: u64) {
+    // Transaction details are not served at processed commitment
+    let commitment = Some(ctx.config.confirm_commitment())
+        .filter(CommitmentConfig::is_at_least_confirmed)
+        .unwrap_or_else(CommitmentConfig::confirmed);
+    let (client, signature) = (ctx.l1_client.clone(), *signature);
+    let fee = ctx
+        .rpc_call(move || Ok(client.get_transaction_fee(&signature, commitment)?))
+        .await;
+    match fee {
+        Ok(Some(fee)) => {
+            let priority_fee = priority_fee.min(fee);
+            metrics::LAMPORTS_SPENT.inc_by(fee);
+            ctx.spend.record_fee(fee);
+            metrics::PRIORITY_LAMPORTS_SPENT.inc_by(priority_fee);
+            info!(
+                slot,
+                %signature,
+                fee,
+                base_fee = fee - priority_fee,
+                priority_fee,
+                lamports_spent_total = metrics::LAMPORTS_SPENT.get(),
+                "transaction fee"
+            );
+        }
+        Ok(None) => warn!(slot, %signature, "transaction fee unavailable"),
+        Err(e) => warn!(slot, %signature, error = %e, "failed to read transaction fee"),
+    }
+}
+
+/// Appends a submission of `roots` to the submission log and the audit file, when they are kept
+fn log_submission(ctx: &BridgeContext, program_id: &str, roots: &L2Roots, signature: &Signature) -> Result<()> {
+    if let Some(log) = &ctx.submission_log {
+        log.append(LogEntry::new(program_id, roots.slot, &roots.mt_root, &roots.ws_root, &signature.to_string()))?;
+    }
+    if let Some(audit) = &ctx.audit_log {
+        audit.append(roots.slot, &roots.mt_root, &roots.ws_root, &signature.to_string())?;
+    }
+    Ok(())
+}
+
+/// Persists `slot` as the target's last submitted slot, unless a later slot is already recorded
+fn record_submitted(target: &L1Target, slot: u64, mt_root: &[u8], ws_root: &[u8; 32]) -> Result<()> {
+    // On-demand backfills submit old slots and must not move the state backwards
+    if state::load_last_slot(&target.state_file).is_some_and(|last_slot| last_slot >= slot) {
+        return Ok(());
+    }
+    state::save_last_slot(&target.state_file, slot, mt_root, ws_root)?;
+    if target.is_primary() {
+        metrics::LAST_SUBMITTED_SLOT.set(slot as i64);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jito::JitoConfig;
+    use crate::mock::{self, MockClient};
+    use base64::Engine;
+    use solana_sdk::commitment_config::CommitmentLevel;
+    use solana_sdk::nonce;
+    use solana_sdk::instruction::InstructionError;
+    use solana_sdk::transaction::TransactionError;
+
+    const SLOT: u64 = 4242;
+    const MT_ROOT: [u8; 32] = [0xab; 32];
+    const WS_ROOT: [u8; 32] = [0xcd; 32];
+    /// sha256("global:add_roots")[..8]
+    const ADD_ROOTS_DISCRIMINATOR: [u8; 8] = [249, 209, 47, 60, 18, 3, 81, 219];
+
+    struct Fixture {
+        ctx: BridgeContext,
+        mock: MockClient,
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            for target in &self.ctx.targets {
+                let _ = std::fs::remove_file(&target.state_file);
+            }
+        }
+    }
+
+    /// Builds a context over a mock holding the leaf chunk and world state accounts
+    ///
+    /// `extra_config` is appended inside the `[settings]` section and may open further sections.
+    async fn fixture(name: &str, extra_config: &str) -> Fixture {
+        fixture_with_account(name, "", extra_config).await
+    }
+
+    /// Like `fixture`, with `account_config` appended inside the `[account]` section
+    async fn fixture_with_account(name: &str, account_config: &str, extra_config: &str) -> Fixture {
+        let leaf_chunk = Pubkey::new_unique();
+        let world_state = Pubkey::new_unique();
+        let state_file = std::env::temp_dir().join(format!("sol-proposer-{}-{}.json", name, std::process::id()));
+
+        let config: Config = toml::from_str(&format!(
+            r#"
+            [network]
+            l1_rpc_url = "http://127.0.0.1:1"
+            l2_rpc_url = "http://127.0.0.1:2"
+            l1_program_id = "{program_id}"
+
+            [account]
+            leaf_chunk_address = "{leaf_chunk}"
+            slots_account = "{slots_account}"
+            world_state_account = "{world_state}"
+            {account_config}
+
+            [wallet]
+            base58 = "{wallet}"
+
+            [settings]
+            check_interval_secs = 1
+            state_file = "{state_file}"
+            {extra_config}
+            "#,
+            program_id = Pubkey::new_unique(),
+            slots_account = Pubkey::new_unique(),
+            wallet = Keypair::new().to_base58_string(),
+            state_file = state_file.display(),
+        ))
+        .unwrap();
+        config.validate().unwrap();
+
+        let mock = MockClient::new(SLOT);
+        mock.set_account(leaf_chunk, [[0u8; 8].as_slice(), &MT_ROOT].concat());
+        mock.set_account(world_state, [[0u8; 8].as_slice(), &WS_ROOT].concat());
+
+        let ctx = BridgeContext::with_clients(config, Box::new(mock.clone()), Box::new(mock.clone()))
+            .await
+            .unwrap();
+        for target in &ctx.targets {
+            let _ = std::fs::remove_file(&target.state_file);
+        }
+        Fixture { ctx, mock }
+    }
+
+    /// Runs a cycle and returns the primary target's outcome
+    async fn primary_outcome(ctx: &BridgeContext) -> Result<CycleOutcome> {
+        check_and_submit(ctx).await.unwrap().targets.remove(0).result
+    }
+
+    /// Finds the instruction calling `target` and resolves its account metas
+    fn target_instruction(message: &VersionedMessage, target: &L1Target) -> Option<(Vec<u8>, Vec<AccountMeta>)> {
+        let account_keys = message.static_account_keys();
+        let instruction = message
+            .instructions()
+            .iter()
+            .find(|ix| account_keys[ix.program_id_index as usize] == target.program_id)?;
+        let accounts = instruction
+            .accounts
+            .iter()
+            .map(|&index| {
+                let index = index as usize;
+                AccountMeta {
+                    pubkey: account_keys[index],
+                    is_signer: message.is_signer(index),
+                    is_writable: message.is_maybe_writable(index),
+                }
+            })
+            .collect();
+        Some((instruction.data.clone(), accounts))
+    }
+
+    #[tokio::test]
+    async fn submits_expected_instruction() {
+        let Fixture { ctx, mock } = &fixture("submit", "").await;
+
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot: SLOT, .. }));
+
+        let sent = mock.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        let target = &ctx.targets[0];
+        let (data, accounts) = target_instruction(&sent[0].message, target).expect("bridge instruction");
+
+        let expected_data = [ADD_ROOTS_DISCRIMINATOR.as_slice(), &SLOT.to_le_bytes(), &MT_ROOT, &WS_ROOT].concat();
+        assert_eq!(data, expected_data);
+
+        let (slot_roots_account, _) = target.slot_roots_pda(SLOT);
+        let expected_accounts = [
+            AccountMeta::new(target.slots_account, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new(slot_roots_account, false),
+            AccountMeta::new(ctx.wallet.pubkey(), true),
+        ];
+        assert_eq!(accounts, expected_accounts);
+    }
+
+    #[tokio::test]
+    async fn submits_configured_root_length() {
+        let Fixture { ctx, mock } = &fixture_with_account("root-len", "root_len = 64", "").await;
+        let mt_root = [MT_ROOT, [0xef; 32]].concat();
+        mock.set_account(ctx.leaf_chunks[0], [[0u8; 8].as_slice(), &mt_root].concat());
+
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot: SLOT, .. }));
+
+        let (data, _) = target_instruction(&mock.sent_transactions()[0].message, &ctx.targets[0]).unwrap();
+        let expected_data = [ADD_ROOTS_DISCRIMINATOR.as_slice(), &SLOT.to_le_bytes(), &mt_root, &WS_ROOT].concat();
+        assert_eq!(data, expected_data);
+
+        // A leaf chunk shorter than the configured root fails to parse
+        mock.set_account(ctx.leaf_chunks[0], [[0u8; 8].as_slice(), &MT_ROOT].concat());
+        assert!(read_roots(ctx).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn submits_transformed_roots() {
+        let extra = "root_transform = \"keccak256\"\nroot_transform_fields = [\"slot_be\", \"mt_root\"]";
+        let Fixture { ctx, mock } = &fixture("root-transform", extra).await;
+        primary_outcome(ctx).await.unwrap();
+
+        let (data, _) = target_instruction(&mock.sent_transactions()[0].message, &ctx.targets[0]).unwrap();
+        let commitment = solana_sdk::keccak::hashv(&[&SLOT.to_be_bytes(), &MT_ROOT]).to_bytes();
+        let expected_data = [ADD_ROOTS_DISCRIMINATOR.as_slice(), &SLOT.to_le_bytes(), &commitment].concat();
+        assert_eq!(data, expected_data);
+
+        // By default the hash covers mt_root || ws_root || slot
+        let Fixture { ctx, .. } = &fixture("root-transform-default", "root_transform = \"sha256\"").await;
+        let roots = read_roots(ctx).await.unwrap();
+        let expected = solana_sdk::hash::hashv(&[&MT_ROOT, &WS_ROOT, &SLOT.to_le_bytes()]).to_bytes();
+        assert_eq!(roots.stored_root(&ctx.config.settings), expected);
+    }
+
+    #[tokio::test]
+    async fn submits_configured_instruction_sequence() {
+        let instructions = r#"
+            [[program.instructions]]
+            instruction = "prepare"
+            data = "01 {slot_be} {mt_root}"
+            [[program.instructions.accounts]]
+            role = "authority"
+            ordinal = 0
+            is_signer = true
+            [[program.instructions.accounts]]
+            role = "slot_roots_pda"
+            ordinal = 1
+            is_writable = true
+            [[program.instructions.accounts]]
+            role = "system_program"
+            ordinal = 2
+            [[program.instructions]]
+            instruction = "commit"
+            data = "{slot}{ws_root}"
+        "#;
+        let Fixture { ctx, mock } = &fixture("instruction-sequence", instructions).await;
+        primary_outcome(ctx).await.unwrap();
+
+        let message = &mock.sent_transactions()[0].message;
+        let account_keys = message.static_account_keys();
+        let sent: Vec<&[u8]> = message
+            .instructions()
+            .iter()
+            .filter(|ix| account_keys[ix.program_id_index as usize] == ctx.targets[0].program_id)
+            .map(|ix| ix.data.as_slice())
+            .collect();
+        let prepare = [crate::program::anchor_sighash("prepare").as_slice(), &[1], &SLOT.to_be_bytes(), &MT_ROOT].concat();
+        let commit = [crate::program::anchor_sighash("commit").as_slice(), &SLOT.to_le_bytes(), &WS_ROOT].concat();
+        assert_eq!(sent, [prepare.as_slice(), commit.as_slice()]);
+
+        // Every entry's layout is held to the same role rules as program.accounts
+        let mut program = ctx.config.program.clone();
+        program.instructions[0].accounts[2].role = AccountRole::SlotRootsPda;
+        let err = program.instruction_layouts().unwrap_err().to_string();
+        assert!(err.contains("program.instructions[0].accounts: role SlotRootsPda must appear exactly once"), "{}", err);
+
+        let oversized = format!("[[program.instructions]]\ndata = \"{}\"", "00".repeat(PACKET_DATA_SIZE));
+        let Fixture { ctx, .. } = &fixture("instruction-oversized", &oversized).await;
+        let err = primary_outcome(ctx).await.unwrap_err();
+        assert!(matches!(BridgeError::find(&err), Some(BridgeError::TransactionTooLarge { .. })), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn rejects_unexpected_account_discriminator() {
+        let account = r#"expected_account_discriminator = "0102030405060708""#;
+        let Fixture { ctx, mock } = &fixture_with_account("discriminator", account, "").await;
+
+        let err = read_roots(ctx).await.unwrap_err();
+        assert!(format!("{:#}", err)
+            .contains("unexpected account discriminator: got 0000000000000000 expected 0102030405060708"));
+
+        mock.set_account(ctx.leaf_chunks[0], [[1u8, 2, 3, 4, 5, 6, 7, 8].as_slice(), &MT_ROOT].concat());
+        assert_eq!(read_roots(ctx).await.unwrap().mt_root, MT_ROOT);
+    }
+
+    #[tokio::test]
+    async fn defers_roots_below_confirmation_depth() {
+        let Fixture { ctx, mock } = &fixture("confirmation-depth", "min_confirmation_depth = 32").await;
+
+        mock.set_finalized_slot(SLOT + 31);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Skipped { slot: SLOT, reason: "root below confirmation depth" }));
+        assert!(mock.sent_transactions().is_empty());
+
+        mock.set_finalized_slot(SLOT + 32);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot: SLOT, .. }));
+    }
+
+    #[tokio::test]
+    async fn skips_when_fee_exceeds_cap() {
+        let extra = "max_fee_lamports = 100000\n[fees]\ncompute_unit_price = 1000000";
+        let Fixture { ctx, mock } = &fixture("fee-cap", extra).await;
+
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Skipped { slot: SLOT, reason: "fee exceeds cap" }));
+        assert!(mock.sent_transactions().is_empty());
+        assert_eq!(state::load_last_slot(&ctx.targets[0].state_file), None);
+    }
+
+    #[tokio::test]
+    async fn times_each_submission_stage() {
+        let Fixture { ctx, .. } = &fixture("durations", "").await;
+        let stages = ["fetch", "build", "send", "confirm"];
+        let count = |stage: &str| metrics::SUBMISSION_DURATION.with_label_values(&[stage]).get_sample_count();
+        let before = stages.map(count);
+
+        primary_outcome(ctx).await.unwrap();
+
+        // Other tests submit concurrently, so only check every stage was observed at least once more
+        for (stage, before) in stages.into_iter().zip(before) {
+            assert!(count(stage) > before, "{} not timed", stage);
+        }
+    }
+
+    #[tokio::test]
+    async fn separate_fee_payer_pays_and_signs() {
+        let mut fixture = fixture("fee-payer", "").await;
+        let fee_payer = Keypair::new();
+        let fee_payer_pubkey = fee_payer.pubkey();
+        fixture.ctx.fee_payer = Some(Arc::new(fee_payer));
+        let Fixture { ctx, mock } = &fixture;
+
+        primary_outcome(ctx).await.unwrap();
+
+        let sent = mock.sent_transactions();
+        let message = &sent[0].message;
+        assert_eq!(message.static_account_keys()[0], fee_payer_pubkey);
+        assert_eq!(message.header().num_required_signatures, 2);
+        assert!(sent[0].verify_with_results().into_iter().all(|verified| verified));
+
+        let (_, accounts) = target_instruction(message, &ctx.targets[0]).expect("bridge instruction");
+        assert_eq!(accounts.last(), Some(&AccountMeta::new(ctx.wallet.pubkey(), true)));
+    }
+
+    #[tokio::test]
+    async fn submits_slot_stored_in_account() {
+        let mut fixture = fixture("account-slot", "").await;
+        fixture.ctx.leaf_chunk_layout.slot_offset = Some(40);
+        let Fixture { ctx, mock } = &fixture;
+        let account_slot = SLOT - 5;
+        mock.set_account(ctx.leaf_chunks[0], [[0u8; 8].as_slice(), &MT_ROOT, &account_slot.to_le_bytes()].concat());
+
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot, .. } if slot == account_slot));
+
+        let target = &ctx.targets[0];
+        let (data, accounts) = target_instruction(&mock.sent_transactions()[0].message, target).unwrap();
+        assert_eq!(data[8..16], account_slot.to_le_bytes());
+        assert_eq!(accounts[2].pubkey, target.slot_roots_pda(account_slot).0);
+    }
+
+    #[tokio::test]
+    async fn resolves_leaf_chunk_by_filter() {
+        let mut fixture = fixture("leaf-chunk-filter", "").await;
+        let program_id = Pubkey::new_unique();
+        fixture.ctx.leaf_chunks.clear();
+        fixture.ctx.config.account.leaf_chunk_filter = Some(crate::config::LeafChunkFilter {
+            program_id: program_id.to_string(),
+            data_size: None,
+            memcmp: vec![crate::config::MemcmpFilter {
+                offset: 40,
+                bytes: "abab".to_string(),
+            }],
+            cache_secs: 30,
+        });
+        let Fixture { ctx, mock } = &fixture;
+        let tagged = |root: u8, tag: u8| Account {
+            lamports: 1_000_000,
+            data: [[0u8; 8].as_slice(), &[root; 32], &[tag; 2]].concat(),
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let (current, untagged, rotated) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        mock.insert_account(current, tagged(0x44, 0xab));
+        mock.insert_account(untagged, tagged(0x55, 0xcd));
+
+        assert_eq!(read_roots(ctx).await.unwrap().mt_root, [0x44; 32]);
+        assert_eq!(ctx.resolved_leaf_chunk.lock().unwrap().unwrap().0, current);
+
+        // The resolved address is reused until it is looked up again
+        mock.insert_account(rotated, tagged(0x66, 0xab));
+        assert_eq!(read_roots(ctx).await.unwrap().mt_root, [0x44; 32]);
+        *ctx.resolved_leaf_chunk.lock().unwrap() = None;
+        let err = read_roots(ctx).await.unwrap_err();
+        assert!(matches!(BridgeError::find(&err), Some(BridgeError::LeafChunkUnresolved { matches: 2 })), "{}", err);
+
+        mock.remove_account(&current);
+        assert_eq!(read_roots(ctx).await.unwrap().mt_root, [0x66; 32]);
+        mock.remove_account(&rotated);
+        *ctx.resolved_leaf_chunk.lock().unwrap() = None;
+        let err = read_roots(ctx).await.unwrap_err();
+        assert!(matches!(BridgeError::find(&err), Some(BridgeError::LeafChunkUnresolved { matches: 0 })), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn reads_accounts_in_configured_encoding() {
+        let Fixture { ctx, mock } = &fixture("zstd", "").await;
+        assert_eq!(read_roots(ctx).await.unwrap().mt_root, MT_ROOT);
+        let Fixture { ctx, mock: plain } = &fixture("base64", r#"account_encoding = "base64""#).await;
+        assert_eq!(read_roots(ctx).await.unwrap().mt_root, MT_ROOT);
+
+        assert!(mock.account_encodings().iter().all(|encoding| *encoding == Some(UiAccountEncoding::Base64Zstd)));
+        assert!(plain.account_encodings().iter().all(|encoding| *encoding == Some(UiAccountEncoding::Base64)));
+        assert!(!plain.account_encodings().is_empty());
+    }
+
+    #[tokio::test]
+    async fn queues_roots_while_l1_unreachable() {
+        let Fixture { ctx, mock } = &fixture("pending", "[retry]\nbase_delay_ms = 0").await;
+        let target = &ctx.targets[0];
+        let set_root = |byte: u8| mock.set_account(ctx.leaf_chunks[0], [[0u8; 8].as_slice(), &[byte; 32]].concat());
+
+        mock.set_l1_unreachable(true);
+        assert!(primary_outcome(ctx).await.is_err());
+        mock.set_slot(SLOT + 1);
+        set_root(0x22);
+        assert!(primary_outcome(ctx).await.is_err());
+        assert_eq!(ctx.pending[0].len(), 2);
+        assert_eq!(PendingRoots::open(&target.name, &target.state_file, 16).len(), 2);
+
+        mock.set_l1_unreachable(false);
+        mock.set_slot(SLOT + 2);
+        set_root(0x33);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot, .. } if slot == SLOT + 2));
+        let slots: Vec<Vec<u8>> = mock
+            .sent_transactions()
+            .iter()
+            .map(|tx| target_instruction(&tx.message, target).unwrap().0[8..16].to_vec())
+            .collect();
+        assert_eq!(slots, [SLOT, SLOT + 1, SLOT + 2].map(|slot| slot.to_le_bytes().to_vec()));
+        assert!(ctx.pending[0].is_empty());
+        assert_eq!(PendingRoots::open(&target.name, &target.state_file, 16).len(), 0);
+    }
+
+    #[tokio::test]
+    async fn does_not_queue_unconfirmed_submission() {
+        let extra = "confirm_commitment = \"finalized\"\nconfirm_timeout_secs = 0\n[retry]\nbase_delay_ms = 0";
+        let Fixture { ctx, mock } = &fixture("pending-timeout", extra).await;
+        mock.hold_unfinalized(true);
+        let err = primary_outcome(ctx).await.unwrap_err();
+        assert!(matches!(BridgeError::find(&err), Some(BridgeError::ConfirmationTimeout { .. })), "{}", err);
+        assert!(ctx.pending[0].is_empty());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn runs_rpc_calls_on_current_thread_runtime() {
+        let mut fixture = fixture("current-thread", "[retry]\nbase_delay_ms = 0").await;
+        // A real blocking client, pointed at the fixture's unreachable L1 endpoint
+        let network = &fixture.ctx.config.network;
+        let client = FailoverRpcClient::new(
+            &network.l1_rpc_url,
+            CommitmentConfig::confirmed(),
+            fixture.ctx.rpc_timeout,
+            &fixture.ctx.config.rate_limit,
+            &network.endpoint_auth,
+            &network.endpoint_aliases,
+            Duration::from_secs(network.rpc_stats_window_secs),
+        )
+        .unwrap();
+        fixture.ctx.l1_client = Arc::new(client);
+
+        let client = fixture.ctx.l1_client.clone();
+        let err = fixture.ctx.rpc_call(move || Ok(client.get_genesis_hash()?)).await.unwrap_err();
+        assert!(matches!(BridgeError::find(&err), Some(BridgeError::RpcTransient(_))), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn times_out_hung_rpc_call() {
+        let mut fixture = fixture("rpc-timeout", "[retry]\nmax_attempts = 2\nbase_delay_ms = 0").await;
+        fixture.ctx.rpc_timeout = Duration::from_millis(20);
+        let err = fixture
+            .ctx
+            .rpc_call(|| {
+                std::thread::sleep(Duration::from_millis(200));
+                Ok(())
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(BridgeError::find(&err), Some(BridgeError::RpcTimeout { .. })), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn applies_slot_offset_for_program() {
+        let Fixture { ctx, mock } = &fixture("slot-offset", "slot_offset = -100").await;
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot: SLOT, .. }));
+
+        let target = &ctx.targets[0];
+        let (data, accounts) = target_instruction(&mock.sent_transactions()[0].message, target).unwrap();
+        assert_eq!(data[8..16], (SLOT - 100).to_le_bytes());
+        assert_eq!(accounts[2].pubkey, target.slot_roots_pda(SLOT - 100).0);
+        assert_eq!(state::load_last_slot(&target.state_file), Some(SLOT));
+
+        let underflow = format!("slot_offset = -{}", SLOT + 1);
+        let Fixture { ctx, .. } = &fixture("slot-offset-underflow", &underflow).await;
+        let err = primary_outcome(ctx).await.unwrap_err();
+        assert!(err.to_string().contains("out of range"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn signs_with_durable_nonce() {
+        let mut fixture = fixture("nonce", "").await;
+        let nonce = DurableNonce {
+            account: Pubkey::new_unique(),
+            authority: fixture.ctx.wallet.pubkey(),
+        };
+        let nonce_hash = Hash::new_unique();
+        let data = nonce::state::Data::new(nonce.authority, nonce::state::DurableNonce::from_blockhash(&nonce_hash), 5_000);
+        let versions = nonce::state::Versions::new(nonce::State::Initialized(data.clone()));
+        let account = Account::new_data(1_000_000, &versions, &solana_program::system_program::id()).unwrap();
+        fixture.mock.insert_account(nonce.account, account);
+        fixture.ctx.nonce = Some(nonce.clone());
+        let Fixture { ctx, mock } = &fixture;
+
+        primary_outcome(ctx).await.unwrap();
+
+        let sent = mock.sent_transactions();
+        let message = &sent[0].message;
+        assert_eq!(*message.recent_blockhash(), data.blockhash());
+        let first = &message.instructions()[0];
+        assert_eq!(
+            message.static_account_keys()[first.program_id_index as usize],
+            solana_program::system_program::id()
+        );
+        assert_eq!(first.data, nonce.advance_instruction().data);
+    }
+
+    #[tokio::test]
+    async fn compresses_v0_transaction_with_lookup_table() {
+        let mut fixture = fixture("v0", r#"tx_version = "v0""#).await;
+        let target = fixture.ctx.targets[0].clone();
+        let (slot_roots_account, _) = target.slot_roots_pda(SLOT);
+        let table = AddressLookupTable {
+            meta: Default::default(),
+            addresses: vec![target.slots_account, slot_roots_account].into(),
+        };
+        let table_key = Pubkey::new_unique();
+        fixture.mock.set_account(table_key, table.serialize_for_tests().unwrap());
+        fixture.ctx.lookup_table = Some(table_key);
+        let Fixture { ctx, mock } = &fixture;
+
+        primary_outcome(ctx).await.unwrap();
+
+        let sent = mock.sent_transactions();
+        let VersionedMessage::V0(message) = &sent[0].message else {
+            panic!("expected a v0 message");
+        };
+        assert_eq!(message.address_table_lookups.len(), 1);
+        assert_eq!(message.address_table_lookups[0].account_key, table_key);
+        assert_eq!(message.address_table_lookups[0].writable_indexes, vec![0, 1]);
+        assert!(!message.account_keys.contains(&slot_roots_account));
+        assert!(message.account_keys.contains(&ctx.wallet.pubkey()));
+    }
+
+    #[tokio::test]
+    async fn joins_root_across_leaf_chunks() {
+        let mut fixture = fixture("chunks", "").await;
+        let chunks = [Pubkey::new_unique(), Pubkey::new_unique()];
+        fixture.ctx.leaf_chunks = chunks.to_vec();
+        let Fixture { ctx, mock } = &fixture;
+        mock.set_account(chunks[0], [[0u8; 8].as_slice(), &MT_ROOT[..20]].concat());
+
+        let Err(err) = check_and_submit(ctx).await else {
+            panic!("read succeeded with a missing chunk");
+        };
+        assert!(err.to_string().contains(&format!("leaf chunk account 1 ({}) not found", chunks[1])), "{}", err);
+
+        mock.set_account(chunks[1], [[0u8; 8].as_slice(), &MT_ROOT[20..]].concat());
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot: SLOT, .. }));
+        let (data, _) = target_instruction(&mock.sent_transactions()[0].message, &ctx.targets[0]).unwrap();
+        assert_eq!(data[16..48], MT_ROOT);
+    }
+
+    #[tokio::test]
+    async fn attaches_program_logs_to_failed_submission() {
+        let Fixture { ctx, mock } = &fixture("program-logs", "").await;
+        let failure = TransactionError::InstructionError(0, InstructionError::Custom(6001));
+        mock.fail_sends(vec![failure.clone()]);
+
+        let err = primary_outcome(ctx).await.unwrap_err();
+        assert!(err.to_string().contains("program logs: Program log: simulated"), "{}", err);
+        assert_eq!(program_errors::transaction_error(&err), Some(failure));
+    }
+
+    #[tokio::test]
+    async fn orders_accounts_by_configured_layout() {
+        let layout = r#"
+            [[program.accounts]]
+            role = "authority"
+            ordinal = 0
+            is_signer = true
+            is_writable = true
+            [[program.accounts]]
+            role = "slot_roots_pda"
+            ordinal = 1
+            is_writable = true
+            [[program.accounts]]
+            role = "system_program"
+            ordinal = 2
+        "#;
+        let Fixture { ctx, mock } = &fixture("account-layout", layout).await;
+        primary_outcome(ctx).await.unwrap();
+
+        let (_, accounts) = target_instruction(&mock.sent_transactions()[0].message, &ctx.targets[0]).unwrap();
+        let (slot_roots_account, _) = ctx.targets[0].slot_roots_pda(SLOT);
+        assert_eq!(
+            accounts,
+            [
+                AccountMeta::new(ctx.wallet.pubkey(), true),
+                AccountMeta::new(slot_roots_account, false),
+                AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            ]
+        );
+
+        let mut program = ctx.config.program.clone();
+        program.accounts[2].role = AccountRole::Authority;
+        let err = program.account_layout().unwrap_err().to_string();
+        assert!(err.contains("role Authority must appear exactly once"), "{}", err);
+        program.accounts[2].role = AccountRole::SystemProgram;
+        program.accounts[2].ordinal = 3;
+        assert!(program.account_layout().is_err());
+    }
+
+    #[tokio::test]
+    async fn sends_with_configured_rpc_settings() {
+        let Fixture { ctx, mock } = &fixture("send-config", "").await;
+        primary_outcome(ctx).await.unwrap();
+        let config = mock.send_configs()[0];
+        assert!(!config.skip_preflight);
+        assert_eq!(config.preflight_commitment, Some(CommitmentLevel::Confirmed));
+        assert_eq!(config.max_retries, None);
+
+        let extra = "[send]\nskip_preflight = true\npreflight_commitment = \"processed\"\nmax_retries = 2";
+        let Fixture { ctx, mock } = &fixture("send-config-set", extra).await;
+        primary_outcome(ctx).await.unwrap();
+        let config = mock.send_configs()[0];
+        assert!(config.skip_preflight);
+        assert_eq!(config.preflight_commitment, Some(CommitmentLevel::Processed));
+        assert_eq!(config.max_retries, Some(2));
+    }
+
+    #[tokio::test]
+    async fn confirms_already_processed_transaction() {
+        let Fixture { ctx, mock } = &fixture("already-processed", "").await;
+        mock.fail_sends(vec![TransactionError::AlreadyProcessed]);
+
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot: SLOT, .. }));
+        // Rejected as a duplicate rather than sent again
+        assert_eq!(mock.send_configs().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn proposes_to_multisig() {
+        let address = Pubkey::new_unique();
+        let log_path = std::env::temp_dir().join(format!("sol-proposer-log-multisig-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&log_path);
+        let audit_path = log_path.with_extension("audit");
+        let _ = std::fs::remove_file(&audit_path);
+        let extra = format!(
+            "submission_log = {:?}\n[audit]\npath = {:?}\n[multisig]\naddress = \"{}\"",
+            log_path.display().to_string(),
+            audit_path.display().to_string(),
+            address
+        );
+        let Fixture { ctx, mock } = &fixture("multisig", &extra).await;
+        // Four transactions created so far, the index following the create key, config authority, threshold and time lock
+        let mut data = vec![0; 86];
+        data[78..].copy_from_slice(&4u64.to_le_bytes());
+        mock.set_account(address, data);
+
+        let multisig = ctx.multisig.as_ref().unwrap();
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Proposed { slot: SLOT, proposal, .. } if proposal == multisig.proposal_pda(5)));
+
+        // The bridge instruction goes into the vault transaction rather than the sent one
+        let message = &mock.sent_transactions()[0].message;
+        assert!(target_instruction(message, &ctx.targets[0]).is_none());
+        let programs: Vec<&Pubkey> = message
+            .instructions()
+            .iter()
+            .map(|instruction| &message.static_account_keys()[instruction.program_id_index as usize])
+            .collect();
+        assert_eq!(programs.iter().filter(|&&program| *program == multisig.program_id).count(), 2);
+        assert_eq!(ctx.authority(), multisig.vault);
+
+        // The roots have not landed until the proposal is executed
+        assert!(ctx.submission_log.as_ref().unwrap().find(&ctx.targets[0].program_id.to_string(), SLOT).is_none());
+        assert!(std::fs::read_to_string(&audit_path).unwrap_or_default().is_empty());
+        let _ = std::fs::remove_file(&log_path);
+        let _ = std::fs::remove_file(&audit_path);
+    }
+
+    #[tokio::test]
+    async fn reports_missing_leaf_chunk() {
+        let Fixture { ctx, mock } = &fixture("leaf-chunk-missing", "").await;
+
+        mock.set_account(ctx.leaf_chunks[0], vec![0; 40]);
+        let err = check_and_submit(ctx).await.unwrap_err();
+        assert!(matches!(
+            BridgeError::find(&err),
+            Some(BridgeError::LeafChunkMissing { uninitialized: true, .. })
+        ));
+
+        mock.remove_account(&ctx.leaf_chunks[0]);
+        let err = check_and_submit(ctx).await.unwrap_err();
+        let Some(BridgeError::LeafChunkMissing { address, uninitialized, .. }) = BridgeError::find(&err) else {
+            panic!("{}", err);
+        };
+        assert_eq!((*address, *uninitialized), (ctx.leaf_chunks[0], false));
+    }
+
+    #[tokio::test]
+    async fn refetches_stale_blockhash() {
+        let extra = "max_blockhash_age_slots = 60\n[retry]\nbase_delay_ms = 0";
+        let Fixture { ctx, mock } = &fixture("stale-blockhash", extra).await;
+
+        mock.serve_stale_blockhashes(1);
+        let err = rpc::blocking(ctx.blockhash_lookup()).await.unwrap_err();
+        assert!(matches!(
+            BridgeError::find(&err),
+            Some(BridgeError::StaleBlockhash { age: mock::STALE_BLOCKHASH_AGE, .. })
+        ));
+
+        mock.serve_stale_blockhashes(1);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot: SLOT, .. }));
+        assert_eq!(mock.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn measures_clock_skew() {
+        let Fixture { ctx, mock } = &fixture("clock-skew", "").await;
+        mock.set_block_time_offset(120);
+        let skew = check_clock_skew(ctx).await.unwrap();
+        assert!((-121..=-119).contains(&skew), "{}", skew);
+    }
+
+    #[tokio::test]
+    async fn applies_program_error_actions() {
+        let extra = "[retry]\nbase_delay_ms = 0\n[program_errors]\nignore = [6000]\nretry = [6001]\nfatal = [6002]";
+        let custom = |code| TransactionError::InstructionError(0, InstructionError::Custom(code));
+
+        let Fixture { ctx, mock } = &fixture("error-retry", extra).await;
+        mock.fail_sends(vec![custom(6001)]);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot: SLOT, .. }));
+
+        let Fixture { ctx, mock } = &fixture("error-ignore", extra).await;
+        mock.fail_sends(vec![custom(6000)]);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Skipped { slot: SLOT, reason: "program error ignored" }));
+        assert_eq!(state::load_last_slot(&ctx.targets[0].state_file), Some(SLOT));
+
+        let Fixture { ctx, mock } = &fixture("error-fatal", extra).await;
+        mock.fail_sends(vec![custom(6002)]);
+        let err = check_and_submit(ctx).await.unwrap_err();
+        assert!(matches!(BridgeError::find(&err), Some(BridgeError::FatalProgram { code: 6002 })), "{}", err);
+        assert_eq!(state::load_last_slot(&ctx.targets[0].state_file), None);
+    }
+
+    #[tokio::test]
+    async fn serves_static_accounts_from_cache() {
+        let (fixed, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let Fixture { ctx, mock } = &fixture("account-cache", &format!("static_accounts = [\"{}\"]", fixed)).await;
+        let commitment = CommitmentConfig::confirmed();
+        mock.set_account(fixed, vec![1]);
+        mock.set_account(other, vec![1]);
+        ctx.get_l1_account(&fixed, commitment).await.unwrap();
+        ctx.get_l1_account(&other, commitment).await.unwrap();
+
+        mock.set_account(fixed, vec![2]);
+        mock.set_account(other, vec![2]);
+        assert_eq!(ctx.get_l1_account(&fixed, commitment).await.unwrap().unwrap().data, [1]);
+        assert_eq!(ctx.get_l1_account(&other, commitment).await.unwrap().unwrap().data, [2]);
+    }
+
+    #[tokio::test]
+    async fn submits_overridden_roots() {
+        let mut fixture = fixture("root-override", "").await;
+        fixture.ctx.config.settings.mt_root_override = Some(hex::encode([1u8; 16]));
+        assert!(fixture.ctx.config.validate().is_err());
+
+        fixture.ctx.mt_root_override = Some(vec![1; 32]);
+        fixture.ctx.ws_root_override = Some([2; 32]);
+        let Fixture { ctx, mock } = &fixture;
+        primary_outcome(ctx).await.unwrap();
+
+        let (data, _) = target_instruction(&mock.sent_transactions()[0].message, &ctx.targets[0]).unwrap();
+        assert_eq!(data[16..], [[1u8; 32], [2; 32]].concat());
+    }
+
+    #[tokio::test]
+    async fn measures_l1_lag_from_slots_account() {
+        let Fixture { ctx, mock } = &fixture("l1-lag", "[program]\nlast_slot_offset = 8").await;
+        let target = &ctx.targets[0];
+        assert!(measure_l1_lag(ctx, target, SLOT).await.is_err());
+
+        mock.set_account(target.slots_account, [[0u8; 8], (SLOT - 10).to_le_bytes()].concat());
+        assert_eq!(measure_l1_lag(ctx, target, SLOT).await.unwrap(), Some(10));
+        assert_eq!(health::HEALTH.report(60).l1_lag_slots.get("primary"), Some(&10));
+    }
+
+    #[tokio::test]
+    async fn submits_only_slots_of_this_replica() {
+        let Fixture { ctx, mock } = &fixture("replica", "leader_slot_modulo = 4\nreplica_index = 3").await;
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Skipped { reason: "slot assigned to another replica", .. }));
+        assert!(mock.sent_transactions().is_empty());
+
+        mock.set_slot(SLOT + 1);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot, .. } if slot == SLOT + 1));
+    }
+
+    #[tokio::test]
+    async fn sends_tipped_bundle_and_falls_back_to_rpc() {
+        use axum::routing::post;
+        use axum::{Json, Router};
+        use serde_json::{json, Value};
+        use std::sync::{Arc, Mutex};
+
+        let bundles: Arc<Mutex<Vec<Value>>> = Arc::default();
+        let received = bundles.clone();
+        let app = Router::new().route(
+            "/api/v1/bundles",
+            post(move |Json(request): Json<Value>| async move {
+                received.lock().unwrap().push(request);
+                Json(json!({ "jsonrpc": "2.0", "id": 1, "result": "bundle-id" }))
+            }),
+        );
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+        let tip_account = Pubkey::new_unique();
+        let jito = format!(
+            "[jito]\nblock_engine_url = \"http://{}\"\ntip_account = \"{}\"\ntip_lamports = 1000",
+            addr, tip_account
+        );
+        let mut fixture = fixture("jito", &jito).await;
+        let outcome = primary_outcome(&fixture.ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot: SLOT, .. }));
+        assert!(fixture.mock.sent_transactions().is_empty());
+
+        let request = bundles.lock().unwrap().remove(0);
+        assert_eq!(request["method"], "sendBundle");
+        let encoded = request["params"][0][0].as_str().unwrap();
+        let bytes = base64::engine::general_purpose::STANDARD.decode(encoded).unwrap();
+        let transaction: VersionedTransaction = bincode::deserialize(&bytes).unwrap();
+        assert!(transaction.message.static_account_keys().contains(&tip_account));
+
+        // A block engine that cannot be reached falls back to the RPC, without the tip
+        fixture.ctx.jito = JitoClient::from_config(
+            &JitoConfig {
+                block_engine_url: Some("http://127.0.0.1:1".to_string()),
+                tip_account: Some(tip_account.to_string()),
+                tip_lamports: 1000,
+            },
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        fixture.mock.set_slot(SLOT + 1);
+        fixture.mock.set_account(fixture.ctx.leaf_chunks[0], [[0u8; 8].as_slice(), &[0x11; 32]].concat());
+        let outcome = primary_outcome(&fixture.ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { .. }));
+        let sent = fixture.mock.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        assert!(!sent[0].message.static_account_keys().contains(&tip_account));
+    }
+
+    #[tokio::test]
+    async fn skips_submitted_slot_and_unchanged_root() {
+        let Fixture { ctx, mock } = &fixture("skip", "").await;
+        primary_outcome(ctx).await.unwrap();
+
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Skipped { reason: "slot already submitted", .. }));
+
+        mock.set_slot(SLOT + 1);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Skipped { reason: "root unchanged", .. }));
+        assert_eq!(mock.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn persists_slot_only_once_finalized() {
+        let Fixture { ctx, mock } = &fixture("finalize-wait", "post_submit_finalize_wait_secs = 0").await;
+        mock.hold_unfinalized(true);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { .. }));
+        assert_eq!(state::load_last_slot(&ctx.targets[0].state_file), None);
+
+        mock.hold_unfinalized(false);
+        primary_outcome(ctx).await.unwrap();
+        assert_eq!(state::load_last_slot(&ctx.targets[0].state_file), Some(SLOT));
+    }
+
+    #[tokio::test]
+    async fn resubmits_unfinalized_slot_despite_submission_log() {
+        let log_path = std::env::temp_dir().join(format!("sol-proposer-log-finalize-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&log_path);
+        let extra = format!("submission_log = {:?}\npost_submit_finalize_wait_secs = 0", log_path.display().to_string());
+        let Fixture { ctx, mock } = &fixture("log-finalize", &extra).await;
+        mock.hold_unfinalized(true);
+        assert!(matches!(primary_outcome(ctx).await.unwrap(), CycleOutcome::Submitted { .. }));
+
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { .. }), "{:?}", outcome);
+        assert_eq!(mock.sent_transactions().len(), 2);
+        assert!(SubmissionLog::entries(log_path.to_str().unwrap()).unwrap_or_default().is_empty());
+        let _ = std::fs::remove_file(&log_path);
+    }
+
+    #[tokio::test]
+    async fn waits_for_min_slot_gap() {
+        let Fixture { ctx, mock } = &fixture("slot-gap", "min_slot_gap = 10").await;
+        primary_outcome(ctx).await.unwrap();
+
+        mock.set_account(ctx.leaf_chunks[0], [[0u8; 8].as_slice(), &[0x11; 32]].concat());
+        mock.set_slot(SLOT + 5);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Skipped { reason: "minimum slot gap not reached", .. }));
+
+        mock.set_slot(SLOT + 10);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot, .. } if slot == SLOT + 10));
+    }
+
+    #[tokio::test]
+    async fn resigns_on_blockhash_not_found() {
+        let Fixture { ctx, mock } = &fixture("blockhash", "").await;
+        mock.fail_sends(vec![TransactionError::BlockhashNotFound; 2]);
+
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { .. }));
+        assert_eq!(mock.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn refuses_slot_regression() {
+        let Fixture { ctx, mock } = &fixture("regression", "slot_regression_tolerance = 2").await;
+        primary_outcome(ctx).await.unwrap();
+
+        mock.set_slot(SLOT - 2);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Skipped { reason: "slot already submitted", .. }));
+
+        mock.set_slot(SLOT - 3);
+        let err = check_and_submit(ctx).await.unwrap_err();
+        assert!(matches!(BridgeError::find(&err), Some(BridgeError::SlotRegression { .. })), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn rejects_leaf_chunk_with_unexpected_owner() {
+        let mut fixture = fixture("owner", "").await;
+        fixture.ctx.leaf_chunk_owner = Some(Pubkey::new_unique());
+
+        let err = check_and_submit(&fixture.ctx).await.unwrap_err();
+        assert!(err.to_string().contains("is owned by"), "{}", err);
+        assert!(fixture.mock.sent_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn submits_to_every_target() {
+        let extra_config = format!(
+            "[[l1_targets]]\nname = \"next\"\nl1_program_id = \"{}\"\nslots_account = \"{}\"\nprogram = {{ discriminator = [1, 2, 3, 4, 5, 6, 7, 8] }}",
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let Fixture { ctx, mock } = &fixture("targets", &extra_config).await;
+
+        let results = check_and_submit(ctx).await.unwrap().targets;
+        let names: Vec<&str> = results.iter().map(|result| result.target.as_str()).collect();
+        assert_eq!(names, ["primary", "next"]);
+        assert!(results.iter().all(|result| matches!(result.result, Ok(CycleOutcome::Submitted { .. }))));
+
+        let sent = mock.sent_transactions();
+        assert_eq!(sent.len(), 2);
+        let (data, _) = target_instruction(&sent[1].message, &ctx.targets[1]).expect("second target instruction");
+        assert_eq!(data[..8], [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_ne!(ctx.targets[0].state_file, ctx.targets[1].state_file);
+    }
+
+    #[tokio::test]
+    async fn backfill_keeps_later_state() {
+        let Fixture { ctx, mock } = &fixture("backfill", "").await;
+        mock.set_slot(SLOT + 10);
+        primary_outcome(ctx).await.unwrap();
+
+        mock.set_slot(SLOT);
+        let BackfillSlot::Offered(targets) = backfill_slot(ctx, SLOT).await else {
+            panic!("slot should be readable");
+        };
+        assert!(matches!(targets[0].result, Ok(CycleOutcome::Submitted { slot: SLOT, .. })));
+        assert_eq!(state::load_last_slot(&ctx.targets[0].state_file), Some(SLOT + 10));
+
+        assert!(matches!(backfill_slot(ctx, SLOT - 1).await, BackfillSlot::Unavailable));
+    }
+
+    #[tokio::test]
+    async fn dry_run_sends_nothing() {
+        let Fixture { ctx, mock } = &fixture("dry-run", "dry_run = true").await;
+
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::DryRun { slot: SLOT }));
+        assert!(mock.sent_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn observer_reports_lag_without_submitting() {
+        let Fixture { ctx, mock } = &fixture("observer", "observer = true\nobserver_max_lag_slots = 5").await;
+        let target = &ctx.targets[0];
+
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Observed { slot: SLOT, l1_lag_slots: None }));
+
+        // Another proposer posts the roots
+        let (slot_roots_account, _) = target.slot_roots_pda(SLOT);
+        mock.set_account(slot_roots_account, [[0u8; 16].as_slice(), &MT_ROOT].concat());
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Observed { slot: SLOT, l1_lag_slots: Some(0) }));
+
+        // L2 moves on but nobody submits
+        mock.set_slot(SLOT + 10);
+        mock.set_account(ctx.leaf_chunks[0], [[0u8; 8].as_slice(), &[0x11; 32]].concat());
+        let err = primary_outcome(ctx).await.unwrap_err();
+        assert!(matches!(BridgeError::find(&err), Some(BridgeError::L1Lagging { lag_slots: 10, .. })), "{}", err);
+        assert!(mock.sent_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn skips_slot_in_submission_log() {
+        let log_path = std::env::temp_dir().join(format!("sol-proposer-log-skip-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&log_path);
+        let Fixture { ctx, mock } = &fixture("log-skip", &format!("submission_log = {:?}", log_path.display().to_string())).await;
+
+        assert!(matches!(primary_outcome(ctx).await.unwrap(), CycleOutcome::Submitted { .. }));
+
+        // Even with the state file gone the logged slot is not submitted again
+        std::fs::remove_file(&ctx.targets[0].state_file).unwrap();
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Skipped { reason: "already in submission log", .. }));
+        assert_eq!(mock.sent_transactions().len(), 1);
+
+        let entries = SubmissionLog::entries(log_path.to_str().unwrap()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].slot, SLOT);
+        assert_eq!(entries[0].signature, mock.sent_transactions()[0].signatures[0].to_string());
+        std::fs::remove_file(&log_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn logs_submission_only_once_verified() {
+        let log_path = std::env::temp_dir().join(format!("sol-proposer-log-verify-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&log_path);
+        let audit_path = log_path.with_extension("audit");
+        let _ = std::fs::remove_file(&audit_path);
+        let extra = format!(
+            "submission_log = {:?}\nverify_submission = true\n[audit]\npath = {:?}",
+            log_path.display().to_string(),
+            audit_path.display().to_string()
+        );
+        let Fixture { ctx, .. } = &fixture("log-verify", &extra).await;
+        assert!(ctx.audit_log.is_some());
+
+        // The mock never creates the slot roots account, so the read-back fails
+        let err = primary_outcome(ctx).await.unwrap_err();
+        assert!(matches!(BridgeError::find(&err), Some(BridgeError::AccountNotFound { .. })), "{}", err);
+        assert!(ctx.submission_log.as_ref().unwrap().find(&ctx.targets[0].program_id.to_string(), SLOT).is_none());
+        assert!(std::fs::read_to_string(&audit_path).unwrap_or_default().is_empty());
+        let _ = std::fs::remove_file(&log_path);
+        let _ = std::fs::remove_file(&audit_path);
+    }
+
+    #[tokio::test]
+    async fn failed_simulation_sends_nothing() {
+        let Fixture { ctx, mock } = &fixture("simulate", "simulate_first = true").await;
+        mock.fail_simulations(vec![TransactionError::InstructionError(2, InstructionError::Custom(6000))]);
+
+        assert!(primary_outcome(ctx).await.is_err());
+        assert!(mock.sent_transactions().is_empty());
+
+        // An already recorded slot is recognised from the simulation alone
+        mock.fail_simulations(vec![TransactionError::InstructionError(2, InstructionError::Custom(0))]);
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Skipped { reason: "already recorded on L1", .. }));
+        assert!(mock.sent_transactions().is_empty());
+    }
+
+    /// Counts the messages the wallet signs, as a hardware wallet would prompt for them
+    struct CountingSigner {
+        inner: WalletSigner,
+        signed: AtomicU64,
+    }
+
+    impl Signer for CountingSigner {
+        fn try_pubkey(&self) -> Result<Pubkey, solana_sdk::signer::SignerError> {
+            self.inner.try_pubkey()
+        }
+
+        fn try_sign_message(&self, message: &[u8]) -> Result<Signature, solana_sdk::signer::SignerError> {
+            self.signed.fetch_add(1, Ordering::Relaxed);
+            self.inner.try_sign_message(message)
+        }
+
+        fn is_interactive(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn simulates_without_signing() {
+        let mut fixture = fixture("simulate-unsigned", "simulate_first = true").await;
+        let wallet = Arc::new(CountingSigner {
+            inner: fixture.ctx.wallet.clone(),
+            signed: AtomicU64::new(0),
+        });
+        fixture.ctx.wallet = wallet.clone();
+        let Fixture { ctx, mock } = &fixture;
+
+        mock.fail_simulations(vec![TransactionError::InstructionError(2, InstructionError::Custom(6000))]);
+        assert!(primary_outcome(ctx).await.is_err());
+        assert_eq!(wallet.signed.load(Ordering::Relaxed), 0);
+
+        let outcome = primary_outcome(ctx).await.unwrap();
+        assert!(matches!(outcome, CycleOutcome::Submitted { slot: SLOT, .. }));
+        assert_eq!(wallet.signed.load(Ordering::Relaxed), 1);
+        let sent = &mock.sent_transactions()[0];
+        assert!(sent.verify_with_results().iter().all(|&valid| valid));
+    }
+}
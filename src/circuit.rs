@@ -0,0 +1,59 @@
+//! Circuit breaker that backs off after repeated cycle failures.
+
+/// State change caused by recording a cycle outcome
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Opened,
+    Closed,
+}
+
+/// Opens after `threshold` consecutive failures and closes again after one success
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u64,
+    consecutive_failures: u64,
+    open: bool,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed breaker; a threshold of zero disables it
+    pub fn new(threshold: u64) -> Self {
+        Self {
+            threshold,
+            consecutive_failures: 0,
+            open: false,
+        }
+    }
+
+    /// Changes the threshold, keeping the current failure count
+    pub fn set_threshold(&mut self, threshold: u64) {
+        self.threshold = threshold;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn consecutive_failures(&self) -> u64 {
+        self.consecutive_failures
+    }
+
+    /// Records a cycle outcome, returning the transition it caused, if any
+    pub fn record(&mut self, success: bool) -> Option<Transition> {
+        if success {
+            self.consecutive_failures = 0;
+            if self.open {
+                self.open = false;
+                return Some(Transition::Closed);
+            }
+            return None;
+        }
+
+        self.consecutive_failures += 1;
+        if !self.open && self.threshold > 0 && self.consecutive_failures >= self.threshold {
+            self.open = true;
+            return Some(Transition::Opened);
+        }
+        None
+    }
+}
@@ -0,0 +1,115 @@
+//! Command-line arguments.
+
+use l2_state_bridge::Config;
+use clap::{Parser, Subcommand};
+
+/// Bridges the L2 merkle tree root to the L1 program
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Path of the configuration file
+    #[arg(long, default_value = "config")]
+    pub config: String,
+
+    /// Override `settings.check_interval_secs`
+    #[arg(long, value_name = "SECS")]
+    pub interval: Option<u64>,
+
+    /// Build and log transactions without sending them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Check that another proposer keeps L1 in sync instead of submitting
+    #[arg(long)]
+    pub observer: bool,
+
+    /// Submit on L2 account changes over websocket instead of polling
+    #[arg(long)]
+    pub subscribe: bool,
+
+    /// Run a single cycle and exit: 0 when submitted or nothing to do, 1 on error
+    #[arg(long)]
+    pub once: bool,
+
+    /// Submit this hex merkle tree root instead of reading it from L2, for testing only
+    #[arg(long, value_name = "HEX")]
+    pub mt_root: Option<String>,
+
+    /// Submit this hex world state root instead of reading it from L2, for testing only
+    #[arg(long, value_name = "HEX")]
+    pub ws_root: Option<String>,
+
+    /// Print the slot roots PDA and bump for SLOT, then exit
+    #[arg(long, value_name = "SLOT")]
+    pub print_pda: Option<u64>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// One-off commands run instead of the service loop
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Submit the roots of every slot in an inclusive range, then exit
+    ///
+    /// Historical roots are read from L2 where the RPC can serve them. With
+    /// `--dry-run` the transactions are built and logged but not sent.
+    Backfill {
+        /// First slot to submit
+        #[arg(long)]
+        from: u64,
+        /// Last slot to submit
+        #[arg(long)]
+        to: u64,
+    },
+    /// Print the submissions recorded in `settings.submission_log`, then exit
+    Submissions {
+        /// Only show this slot
+        #[arg(long)]
+        slot: Option<u64>,
+        /// Only show submissions to this L1 program
+        #[arg(long)]
+        program_id: Option<String>,
+    },
+    /// Print the instruction data and slot roots PDA each target would be sent for these roots, then exit
+    ///
+    /// Runs offline, for comparing the encoding against the L1 program's IDL.
+    Encode {
+        #[arg(long)]
+        slot: u64,
+        /// Hex merkle tree root, `account.root_len` bytes long
+        #[arg(long, value_name = "HEX")]
+        mt_root: String,
+        /// Hex world state root
+        #[arg(long, value_name = "HEX")]
+        ws_root: String,
+    },
+    /// Check the config, endpoints, wallet, leaf chunk and L1 program, then exit
+    ///
+    /// Prints a pass/fail checklist and exits with 1 if any check failed.
+    Doctor,
+}
+
+impl Cli {
+    /// Applies command-line overrides on top of the loaded configuration
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(interval) = self.interval {
+            config.settings.check_interval_secs = interval;
+        }
+        if self.dry_run {
+            config.settings.dry_run = true;
+        }
+        if self.observer {
+            config.settings.observer = true;
+        }
+        if self.subscribe {
+            config.settings.subscribe = true;
+        }
+        if let Some(root) = &self.mt_root {
+            config.settings.mt_root_override = Some(root.clone());
+        }
+        if let Some(root) = &self.ws_root {
+            config.settings.ws_root_override = Some(root.clone());
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Traits abstracting the RPC calls made against L1 and L2.
+
+use crate::rpc::FailoverRpcClient;
+use solana_client::client_error::Result as ClientResult;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig, RpcSimulateTransactionConfig,
+    RpcTransactionConfig,
+};
+use solana_client::rpc_response::{RpcPrioritizationFee, RpcResult, RpcSimulateTransactionResult};
+use solana_sdk::account::Account;
+use solana_sdk::clock::UnixTimestamp;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::TransactionStatus;
+
+/// Calls available on every network
+pub trait RpcNode: Send + Sync {
+    fn get_genesis_hash(&self) -> ClientResult<Hash>;
+
+    /// Succeeds when the node reports itself healthy
+    fn get_health(&self) -> ClientResult<()>;
+
+    /// Latest slot that has reached `commitment`
+    fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64>;
+}
+
+/// Reads account state from L2
+pub trait L2Reader: RpcNode {
+    fn get_account_with_config(&self, pubkey: &Pubkey, config: RpcAccountInfoConfig) -> RpcResult<Option<Account>>;
+
+    fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: RpcAccountInfoConfig,
+    ) -> RpcResult<Vec<Option<Account>>>;
+
+    /// Accounts owned by `program_id` that pass every filter in `config`
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>>;
+}
+
+/// Builds, sends and confirms L1 transactions
+pub trait L1Submitter: RpcNode {
+    fn get_account_with_commitment(&self, pubkey: &Pubkey, commitment: CommitmentConfig) -> RpcResult<Option<Account>>;
+
+    fn get_balance(&self, pubkey: &Pubkey) -> ClientResult<u64>;
+
+    /// Latest blockhash and the last block height it is valid at
+    fn get_latest_blockhash_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<(Hash, u64)>;
+
+    fn get_block_height(&self, commitment: CommitmentConfig) -> ClientResult<u64>;
+
+    /// Unix time the block at `slot` was produced, as estimated by the cluster
+    fn get_block_time(&self, slot: u64) -> ClientResult<UnixTimestamp>;
+
+    fn get_recent_prioritization_fees(&self, accounts: &[Pubkey]) -> ClientResult<Vec<RpcPrioritizationFee>>;
+
+    /// Simulates without verifying signatures, so an unsigned transaction can be simulated
+    fn simulate_transaction(&self, transaction: &VersionedTransaction) -> RpcResult<RpcSimulateTransactionResult>;
+
+    fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature>;
+
+    /// Status of a sent transaction, `None` while no node has seen it land
+    fn get_signature_status(&self, signature: &Signature) -> ClientResult<Option<TransactionStatus>>;
+
+    fn is_blockhash_valid(&self, blockhash: &Hash, commitment: CommitmentConfig) -> ClientResult<bool>;
+
+    /// Fee charged for a confirmed transaction, `None` when its status meta is unavailable
+    fn get_transaction_fee(&self, signature: &Signature, commitment: CommitmentConfig) -> ClientResult<Option<u64>>;
+}
+
+impl RpcNode for FailoverRpcClient {
+    fn get_genesis_hash(&self) -> ClientResult<Hash> {
+        self.call(|c| c.get_genesis_hash())
+    }
+
+    fn get_health(&self) -> ClientResult<()> {
+        self.call(|c| c.get_health())
+    }
+
+    fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        self.call(|c| c.get_slot_with_commitment(commitment))
+    }
+}
+
+impl L2Reader for FailoverRpcClient {
+    fn get_account_with_config(&self, pubkey: &Pubkey, config: RpcAccountInfoConfig) -> RpcResult<Option<Account>> {
+        self.call(|c| c.get_account_with_config(pubkey, config.clone()))
+    }
+
+    fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: RpcAccountInfoConfig,
+    ) -> RpcResult<Vec<Option<Account>>> {
+        self.call(|c| c.get_multiple_accounts_with_config(pubkeys, config.clone()))
+    }
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        self.call(|c| c.get_program_accounts_with_config(program_id, config.clone()))
+    }
+}
+
+impl L1Submitter for FailoverRpcClient {
+    fn get_account_with_commitment(&self, pubkey: &Pubkey, commitment: CommitmentConfig) -> RpcResult<Option<Account>> {
+        self.call(|c| c.get_account_with_commitment(pubkey, commitment))
+    }
+
+    fn get_balance(&self, pubkey: &Pubkey) -> ClientResult<u64> {
+        self.call(|c| c.get_balance(pubkey))
+    }
+
+    fn get_latest_blockhash_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<(Hash, u64)> {
+        self.call(|c| c.get_latest_blockhash_with_commitment(commitment))
+    }
+
+    fn get_block_height(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        self.call(|c| c.get_block_height_with_commitment(commitment))
+    }
+
+    fn get_block_time(&self, slot: u64) -> ClientResult<UnixTimestamp> {
+        self.call(|c| c.get_block_time(slot))
+    }
+
+    fn get_recent_prioritization_fees(&self, accounts: &[Pubkey]) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        self.call(|c| c.get_recent_prioritization_fees(accounts))
+    }
+
+    fn simulate_transaction(&self, transaction: &VersionedTransaction) -> RpcResult<RpcSimulateTransactionResult> {
+        self.call(|c| {
+            let config = RpcSimulateTransactionConfig {
+                sig_verify: false,
+                commitment: Some(c.commitment()),
+                ..RpcSimulateTransactionConfig::default()
+            };
+            c.simulate_transaction_with_config(transaction, config)
+        })
+    }
+
+    fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        self.call(|c| c.send_transaction_with_config(transaction, config))
+    }
+
+    fn get_signature_status(&self, signature: &Signature) -> ClientResult<Option<TransactionStatus>> {
+        let statuses = self.call(|c| c.get_signature_statuses(std::slice::from_ref(signature)))?;
+        Ok(statuses.value.into_iter().next().flatten())
+    }
+
+    fn is_blockhash_valid(&self, blockhash: &Hash, commitment: CommitmentConfig) -> ClientResult<bool> {
+        self.call(|c| c.is_blockhash_valid(blockhash, commitment))
+    }
+
+    fn get_transaction_fee(&self, signature: &Signature, commitment: CommitmentConfig) -> ClientResult<Option<u64>> {
+        let config = RpcTransactionConfig {
+            encoding: None,
+            commitment: Some(commitment),
+            max_supported_transaction_version: Some(0),
+        };
+        let transaction = self.call(|c| c.get_transaction_with_config(signature, config))?;
+        Ok(transaction.transaction.meta.map(|meta| meta.fee))
+    }
+}
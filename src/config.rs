@@ -0,0 +1,895 @@
+//! Configuration loading and validation.
+
+use crate::alerts::AlertsConfig;
+use crate::audit::AuditConfig;
+use crate::confirm::{ConfirmOptions, PollBackoff, PollSchedule, SendConfig};
+use crate::fees::FeesConfig;
+use crate::leaf_chunk::DISCRIMINATOR_LEN;
+use crate::jito::JitoConfig;
+use crate::multisig::MultisigConfig;
+use crate::nonce::NonceConfig;
+use crate::program::ProgramConfig;
+use crate::program_errors::ProgramErrorsConfig;
+use crate::rate_limit::RateLimitConfig;
+use crate::retry::RetryConfig;
+use crate::rpc::EndpointAuth;
+use crate::target::{L1Target, TargetConfig};
+use crate::trigger::AdminToken;
+use crate::wallet::{WalletConfig, WalletSource};
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Deserializer};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Configuration structure for the bridge service
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub network: NetworkConfig,
+    pub account: AccountConfig,
+    pub wallet: WalletConfig,
+    pub settings: SettingsConfig,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default)]
+    pub fees: FeesConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub program: ProgramConfig,
+    #[serde(default)]
+    pub program_errors: ProgramErrorsConfig,
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+    #[serde(default)]
+    pub nonce: NonceConfig,
+    #[serde(default)]
+    pub jito: JitoConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub send: SendConfig,
+    #[serde(default)]
+    pub multisig: MultisigConfig,
+    /// Further L1 programs receiving the same roots as the primary target
+    #[serde(default)]
+    pub l1_targets: Vec<TargetConfig>,
+}
+
+/// Network-related configuration
+#[derive(Debug, Deserialize)]
+pub struct NetworkConfig {
+    /// L1 RPC endpoint, or a list of endpoints tried in order
+    #[serde(alias = "l1_rpc_urls", deserialize_with = "string_or_vec")]
+    pub l1_rpc_url: Vec<String>,
+    /// L2 RPC endpoint, or a list of endpoints tried in order
+    #[serde(alias = "l2_rpc_urls", deserialize_with = "string_or_vec")]
+    pub l2_rpc_url: Vec<String>,
+    pub l1_program_id: String,
+    /// L2 websocket endpoint; derived from the first L2 RPC URL when unset
+    pub l2_ws_url: Option<String>,
+    /// Commitment of every L1 read and, by default, of the confirmation wait
+    #[serde(default)]
+    pub l1_commitment: Commitment,
+    /// Commitment of every L2 read; `finalized` avoids proposing roots that are later rolled back
+    #[serde(default)]
+    pub l2_commitment: Commitment,
+    /// Genesis hash the L1 endpoints must report, checked at startup
+    pub expected_l1_genesis: Option<String>,
+    /// Genesis hash the L2 endpoints must report, checked at startup
+    pub expected_l2_genesis: Option<String>,
+    /// Headers, query parameters and TLS client certificates of authenticated endpoints, keyed by RPC URL
+    #[serde(default)]
+    pub endpoint_auth: BTreeMap<String, EndpointAuth>,
+    /// Names labeling endpoints in metrics and logs instead of their URL, keyed by RPC URL
+    #[serde(default)]
+    pub endpoint_aliases: BTreeMap<String, String>,
+    /// Seconds over which each endpoint's error rate is computed before it is logged and reset
+    #[serde(default = "default_rpc_stats_window_secs")]
+    pub rpc_stats_window_secs: u64,
+}
+
+/// Default window of the per-endpoint error rate
+fn default_rpc_stats_window_secs() -> u64 {
+    300
+}
+
+/// Deserializes either a single string or a list of strings
+fn string_or_vec<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(url) => vec![url],
+        OneOrMany::Many(urls) => urls,
+    })
+}
+
+/// Account addresses configuration
+#[derive(Debug, Deserialize)]
+pub struct AccountConfig {
+    /// Leaf chunk account, or a list of chunks whose payloads are concatenated in order
+    #[serde(default, alias = "leaf_chunk_addresses", deserialize_with = "string_or_vec")]
+    pub leaf_chunk_address: Vec<String>,
+    /// Finds the leaf chunk among a program's accounts instead, for addresses that rotate
+    pub leaf_chunk_filter: Option<LeafChunkFilter>,
+    pub slots_account: String,
+    /// Byte offset of the merkle tree root within the leaf chunk data
+    #[serde(default = "default_root_offset")]
+    pub mt_root_offset: usize,
+    /// Length in bytes of the merkle tree root, such as 64 for a pair of hashes
+    #[serde(default = "default_root_len")]
+    pub root_len: usize,
+    /// Hex-encoded 8-byte Anchor discriminator the leaf chunk must start with
+    #[serde(alias = "expected_account_discriminator")]
+    pub leaf_chunk_discriminator: Option<String>,
+    /// L2 program that must own the leaf chunk account
+    pub expected_leaf_chunk_owner: Option<String>,
+    /// Where the slot submitted with the roots comes from
+    #[serde(default)]
+    pub slot_source: SlotSource,
+    /// Byte offset of the little-endian `u64` slot within the leaf chunk data, for `slot_source = "account"`
+    pub slot_data_offset: Option<usize>,
+    /// L2 account holding the world state root
+    pub world_state_account: Option<String>,
+    /// Byte offset of the world state root within the account data, or after
+    /// the vector at `world_state_vec_offset` when that is set
+    #[serde(default = "default_root_offset")]
+    pub world_state_offset: usize,
+    /// Byte offset of a Borsh vector (`u32` length prefix) stored ahead of the world state root
+    pub world_state_vec_offset: Option<usize>,
+    /// Encoded size of one element of that vector
+    #[serde(default = "default_vec_item_len")]
+    pub world_state_vec_item_len: usize,
+}
+
+/// Filters picking the one current leaf chunk out of an L2 program's accounts,
+/// as read from `[account.leaf_chunk_filter]`
+#[derive(Debug, Clone, Deserialize)]
+pub struct LeafChunkFilter {
+    /// L2 program owning the leaf chunk account
+    pub program_id: String,
+    /// Exact length of the account data
+    pub data_size: Option<u64>,
+    /// Bytes the account data must hold at an offset, such as a tag field
+    #[serde(default)]
+    pub memcmp: Vec<MemcmpFilter>,
+    /// Seconds a resolved address is reused before it is looked up again
+    #[serde(default = "default_leaf_chunk_cache_secs")]
+    pub cache_secs: u64,
+}
+
+/// Hex-encoded `bytes` expected at `offset` in the account data
+#[derive(Debug, Clone, Deserialize)]
+pub struct MemcmpFilter {
+    pub offset: usize,
+    pub bytes: String,
+}
+
+impl LeafChunkFilter {
+    /// The `getProgramAccounts` filters, checking at least one narrows the search
+    pub fn rpc_filters(&self) -> Result<Vec<RpcFilterType>> {
+        let mut filters: Vec<RpcFilterType> = self.data_size.map(RpcFilterType::DataSize).into_iter().collect();
+        for (index, memcmp) in self.memcmp.iter().enumerate() {
+            let bytes = hex::decode(&memcmp.bytes)
+                .map_err(|e| eyre::eyre!("account.leaf_chunk_filter.memcmp[{}].bytes: invalid hex: {}", index, e))?;
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(memcmp.offset, bytes)));
+        }
+        if filters.is_empty() {
+            return Err(eyre::eyre!("account.leaf_chunk_filter: data_size or at least one memcmp is required"));
+        }
+        Ok(filters)
+    }
+}
+
+/// Default time a filtered leaf chunk address is reused
+fn default_leaf_chunk_cache_secs() -> u64 {
+    30
+}
+
+/// Default vector element size, one 32-byte hash or pubkey
+fn default_vec_item_len() -> usize {
+    32
+}
+
+/// Default merkle tree root length, one 32-byte hash
+fn default_root_len() -> usize {
+    32
+}
+
+/// Default byte offset of a root, right after the 8-byte account discriminator
+fn default_root_offset() -> usize {
+    8
+}
+
+/// General settings configuration
+#[derive(Debug, Deserialize)]
+pub struct SettingsConfig {
+    pub check_interval_secs: u64,
+    /// Path of the JSON file recording the last submitted slot
+    #[serde(default = "default_state_file")]
+    pub state_file: String,
+    /// Append-only JSON lines file recording every confirmed submission; disabled when unset
+    pub submission_log: Option<String>,
+    /// Seconds before a single RPC call is abandoned as timed out
+    #[serde(default = "default_rpc_timeout_secs")]
+    pub rpc_timeout_secs: u64,
+    /// Seconds to wait for a sent transaction to reach `confirm_commitment`
+    #[serde(default = "default_confirm_timeout_secs")]
+    pub confirm_timeout_secs: u64,
+    /// Milliseconds between the first signature status polls of a sent transaction
+    #[serde(default = "default_confirm_poll_interval_ms")]
+    pub confirm_poll_interval_ms: u64,
+    /// Whether the poll interval stays fixed or doubles after every poll
+    #[serde(default)]
+    pub confirm_poll_backoff: PollBackoff,
+    /// Longest poll interval reached by exponential backoff, in milliseconds
+    #[serde(default = "default_confirm_max_poll_interval_ms")]
+    pub confirm_max_poll_interval_ms: u64,
+    /// Commitment a sent transaction must reach before the slot is persisted; defaults to `network.l1_commitment`
+    pub confirm_commitment: Option<Commitment>,
+    /// Seconds to wait after confirmation for the transaction to be finalized before the slot is persisted;
+    /// no wait when unset
+    pub post_submit_finalize_wait_secs: Option<u64>,
+    /// Read back the slot roots account after submitting and check it holds our root
+    #[serde(default)]
+    pub verify_submission: bool,
+    /// Hash submitted in place of the raw roots, for programs that expect a derived commitment
+    #[serde(default)]
+    pub root_transform: RootTransform,
+    /// Fields concatenated, in order, into the input of `root_transform`
+    #[serde(default = "default_root_transform_fields")]
+    pub root_transform_fields: Vec<RootField>,
+    /// Added to the L2 slot wherever the L1 program sees it, in the PDA seeds and the instruction data,
+    /// for programs counting slots from a different genesis
+    #[serde(default)]
+    pub slot_offset: i64,
+    /// Encoding L2 account data is requested in; decoded by the RPC client before any field is read
+    #[serde(default)]
+    pub account_encoding: AccountEncoding,
+    /// Transaction format to submit in
+    #[serde(default)]
+    pub tx_version: TxVersion,
+    /// Address lookup table compressing the account list of `v0` transactions
+    pub address_lookup_table: Option<String>,
+    /// L1 accounts whose data does not change, such as the lookup table, cached between cycles
+    #[serde(default)]
+    pub static_accounts: Vec<String>,
+    /// Seconds a cached static account is used before it is fetched again
+    #[serde(default = "default_static_account_ttl_secs")]
+    pub static_account_ttl_secs: u64,
+    /// Simulate each transaction and skip sending it when the simulation fails
+    #[serde(default)]
+    pub simulate_first: bool,
+    /// Trigger cycles from L2 account change notifications instead of polling
+    #[serde(default)]
+    pub subscribe: bool,
+    /// Minimum seconds between subscription-triggered cycles
+    #[serde(default = "default_subscribe_debounce_secs")]
+    pub subscribe_debounce_secs: u64,
+    /// Balance below which the fee payer refuses to submit
+    #[serde(default)]
+    pub min_balance_lamports: u64,
+    /// Balance below which a low balance warning is logged
+    #[serde(default = "default_low_balance_lamports")]
+    pub low_balance_lamports: u64,
+    /// Seconds of paid fees the spend rate behind the fee payer's runway estimate is averaged over
+    #[serde(default = "default_runway_window_secs")]
+    pub runway_window_secs: u64,
+    /// Projected runway, in hours, below which a warning is logged and an alert sent; never when unset
+    pub low_runway_hours: Option<f64>,
+    /// Skip submitting while the estimated fee would exceed this many lamports; uncapped when unset
+    pub max_fee_lamports: Option<u64>,
+    /// Build and log the transaction without sending it
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Seconds after which the service exits cleanly between cycles, for a supervisor to restart it; 0 runs forever
+    #[serde(default)]
+    pub max_runtime_secs: u64,
+    /// Seconds to wait at startup for both RPC endpoints to report healthy; 0 skips the wait
+    #[serde(default = "default_warmup_timeout_secs")]
+    pub warmup_timeout_secs: u64,
+    /// Maximum random delay in seconds before the first cycle, desynchronizing replicas
+    #[serde(default)]
+    pub startup_jitter_secs: u64,
+    /// Maximum random delay in seconds added before every cycle
+    #[serde(default)]
+    pub cycle_jitter_secs: u64,
+    /// Number of replicas sharing the slots; this one only submits slots where
+    /// `slot % leader_slot_modulo == replica_index`. Every slot is submitted when unset
+    pub leader_slot_modulo: Option<u64>,
+    /// Index of this replica, below `leader_slot_modulo`
+    #[serde(default)]
+    pub replica_index: u64,
+    /// Only check whether another proposer already posted the roots to L1, never submitting
+    #[serde(default)]
+    pub observer: bool,
+    /// In observer mode, fail the cycle once L1 trails L2 by more than this many slots; only logged when unset
+    pub observer_max_lag_slots: Option<u64>,
+    /// Hex merkle tree root submitted in place of the one read from L2, set by `--mt-root` for testing
+    #[serde(skip)]
+    pub mt_root_override: Option<String>,
+    /// Hex world state root submitted in place of the one read from L2, set by `--ws-root` for testing
+    #[serde(skip)]
+    pub ws_root_override: Option<String>,
+    /// Port for the Prometheus metrics endpoint; disabled when unset
+    pub metrics_port: Option<u16>,
+    /// Bearer token `POST /trigger` on the metrics port requires; unauthenticated when unset
+    pub admin_token: Option<AdminToken>,
+    /// Maximum number of missed slots to backfill after downtime; 0 disables backfilling
+    #[serde(default)]
+    pub max_backfill_slots: u64,
+    /// Roots read while L1 is unreachable that are kept for when it recovers, oldest dropped first; 0 disables queueing
+    #[serde(default = "default_pending_queue_size")]
+    pub pending_queue_size: usize,
+    /// Slots the L2 slot may move backwards before a cycle is refused as a regression
+    #[serde(default)]
+    pub slot_regression_tolerance: u64,
+    /// Slots the L2 slot of a root must trail the L2 finalized slot before it is submitted; 0 disables the check
+    #[serde(default)]
+    pub min_confirmation_depth: u64,
+    /// Minimum slots since the last submission before submitting again; 0 disables the limit
+    #[serde(default)]
+    pub min_slot_gap: u64,
+    /// Submit a changed root even when `min_slot_gap` has not been reached
+    #[serde(default)]
+    pub force_on_root_change: bool,
+    /// Consecutive failed cycles before the circuit breaker opens; 0 disables it
+    #[serde(default = "default_max_consecutive_failures")]
+    pub max_consecutive_failures: u64,
+    /// Poll interval while the circuit breaker is open
+    #[serde(default = "default_circuit_open_interval_secs")]
+    pub circuit_open_interval_secs: u64,
+    /// Blocks a fetched blockhash may have aged by before it is refetched; not checked when unset
+    pub max_blockhash_age_slots: Option<u64>,
+    /// Seconds the local clock may differ from the latest L1 block time before a warning
+    #[serde(default = "default_max_clock_skew_secs")]
+    pub max_clock_skew_secs: u64,
+    /// Seconds between clock skew checks; 0 disables them
+    #[serde(default = "default_clock_skew_check_interval_secs")]
+    pub clock_skew_check_interval_secs: u64,
+    /// Poll interval while a leaf chunk account is closed or uninitialized
+    #[serde(default = "default_leaf_chunk_missing_interval_secs")]
+    pub leaf_chunk_missing_interval_secs: u64,
+    /// Number of check intervals without a successful cycle before `/health` reports 503
+    #[serde(default = "default_health_stale_cycles")]
+    pub health_stale_cycles: u64,
+    /// Seconds to wait for an in-flight cycle when shutting down
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
+    /// Default log filter, overridden by `RUST_LOG`
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// Log output format
+    #[serde(default)]
+    pub log_format: LogFormat,
+    /// With `json`, a result record per cycle and target goes to stdout and logs go to stderr
+    #[serde(default)]
+    pub output_format: OutputFormat,
+}
+
+/// Default low balance watermark, 0.1 SOL
+fn default_low_balance_lamports() -> u64 {
+    100_000_000
+}
+
+/// Default spend rate window, a day
+fn default_runway_window_secs() -> u64 {
+    86_400
+}
+
+/// Default debounce between subscription-triggered cycles
+fn default_subscribe_debounce_secs() -> u64 {
+    5
+}
+
+/// Default lifetime of a cached static account
+fn default_static_account_ttl_secs() -> u64 {
+    300
+}
+
+/// Default per-call RPC timeout
+fn default_rpc_timeout_secs() -> u64 {
+    30
+}
+
+/// Default confirmation timeout
+fn default_confirm_timeout_secs() -> u64 {
+    60
+}
+
+/// Default interval between signature status polls
+fn default_confirm_poll_interval_ms() -> u64 {
+    500
+}
+
+/// Default cap on the signature status poll interval
+fn default_confirm_max_poll_interval_ms() -> u64 {
+    5_000
+}
+
+/// Format of submitted transactions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TxVersion {
+    #[default]
+    Legacy,
+    /// Versioned transaction with a v0 message, which can reference address lookup tables
+    V0,
+}
+
+/// Encoding of account data in L2 RPC responses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum AccountEncoding {
+    #[serde(rename = "base64")]
+    Base64,
+    /// Zstd-compressed before base64 encoding, cutting the bandwidth of large leaf chunks
+    #[default]
+    #[serde(rename = "base64+zstd")]
+    Base64Zstd,
+}
+
+impl From<AccountEncoding> for UiAccountEncoding {
+    fn from(encoding: AccountEncoding) -> Self {
+        match encoding {
+            AccountEncoding::Base64 => UiAccountEncoding::Base64,
+            AccountEncoding::Base64Zstd => UiAccountEncoding::Base64Zstd,
+        }
+    }
+}
+
+/// Source of the L2 slot the roots are submitted for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlotSource {
+    /// The slot the L2 RPC served the leaf chunk account at
+    #[default]
+    RpcContext,
+    /// A slot stored in the leaf chunk account data
+    Account,
+}
+
+/// Hash applied to the roots before they are put in the instruction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RootTransform {
+    /// Submit the raw merkle and world state roots
+    #[default]
+    None,
+    Sha256,
+    Keccak256,
+}
+
+impl RootTransform {
+    /// Hashes the concatenation of `parts`, `None` when roots are submitted raw
+    pub fn digest(self, parts: &[&[u8]]) -> Option<[u8; 32]> {
+        match self {
+            RootTransform::None => None,
+            RootTransform::Sha256 => Some(solana_sdk::hash::hashv(parts).to_bytes()),
+            RootTransform::Keccak256 => Some(solana_sdk::keccak::hashv(parts).to_bytes()),
+        }
+    }
+}
+
+/// A field fed into the root transform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RootField {
+    MtRoot,
+    WsRoot,
+    /// The slot as a little-endian `u64`
+    SlotLe,
+    /// The slot as a big-endian `u64`
+    SlotBe,
+}
+
+/// Commitment level accepted in config files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Processed,
+    #[default]
+    Confirmed,
+    Finalized,
+}
+
+impl From<Commitment> for CommitmentConfig {
+    fn from(commitment: Commitment) -> Self {
+        match commitment {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+/// Default failure count that opens the circuit breaker
+fn default_max_consecutive_failures() -> u64 {
+    5
+}
+
+/// Default poll interval while the circuit breaker is open
+fn default_circuit_open_interval_secs() -> u64 {
+    3600
+}
+
+/// Default transform input: `mt_root || ws_root || slot`
+fn default_root_transform_fields() -> Vec<RootField> {
+    vec![RootField::MtRoot, RootField::WsRoot, RootField::SlotLe]
+}
+
+/// Default number of roots queued while L1 is unreachable
+fn default_pending_queue_size() -> usize {
+    16
+}
+
+/// Default wait for healthy endpoints at startup
+fn default_warmup_timeout_secs() -> u64 {
+    60
+}
+
+/// Default clock skew tolerated before a warning
+fn default_max_clock_skew_secs() -> u64 {
+    30
+}
+
+/// Default interval between clock skew checks
+fn default_clock_skew_check_interval_secs() -> u64 {
+    600
+}
+
+/// Default poll interval while a leaf chunk account is missing
+fn default_leaf_chunk_missing_interval_secs() -> u64 {
+    300
+}
+
+/// Default number of missed cycles tolerated by the health check
+fn default_health_stale_cycles() -> u64 {
+    3
+}
+
+/// Default grace period for the in-flight cycle on shutdown
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+/// Default log filter
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+/// Output format of the tracing logs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Format of the per-cycle result records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Results are only logged
+    #[default]
+    Text,
+    /// One JSON line per cycle and target on stdout
+    Json,
+}
+
+/// Default location of the state file
+fn default_state_file() -> String {
+    "bridge_state.json".to_string()
+}
+
+impl Config {
+    /// The slot the L1 program records the roots of L2 `slot` under
+    pub fn program_slot(&self, slot: u64) -> Result<u64> {
+        let offset = self.settings.slot_offset;
+        slot.checked_add_signed(offset)
+            .ok_or_else(|| eyre::eyre!("slot {} is out of range with settings.slot_offset {}", slot, offset))
+    }
+
+    /// Commitment a sent transaction must reach before it counts as submitted
+    pub fn confirm_commitment(&self) -> CommitmentConfig {
+        self.settings.confirm_commitment.unwrap_or(self.network.l1_commitment).into()
+    }
+
+    /// How to wait for a transaction signed with `blockhash`, which is unset for durable nonce transactions
+    pub fn confirm_options(&self, blockhash: Option<Hash>) -> ConfirmOptions {
+        let settings = &self.settings;
+        ConfirmOptions {
+            commitment: self.confirm_commitment(),
+            timeout: Duration::from_secs(settings.confirm_timeout_secs),
+            poll: PollSchedule {
+                interval: Duration::from_millis(settings.confirm_poll_interval_ms),
+                max_interval: Duration::from_millis(settings.confirm_max_poll_interval_ms),
+                backoff: settings.confirm_poll_backoff,
+            },
+            blockhash,
+        }
+    }
+
+    /// Checks every field that would otherwise only fail once the service is running
+    pub fn validate(&self) -> Result<()> {
+        parse_pubkey("network.l1_program_id", &self.network.l1_program_id)?;
+        match &self.account.leaf_chunk_filter {
+            Some(filter) => {
+                if !self.account.leaf_chunk_address.is_empty() {
+                    return Err(eyre::eyre!("account.leaf_chunk_filter: cannot be combined with account.leaf_chunk_address"));
+                }
+                parse_pubkey("account.leaf_chunk_filter.program_id", &filter.program_id)?;
+                filter.rpc_filters()?;
+            }
+            None => {
+                parse_leaf_chunks(&self.account.leaf_chunk_address)?;
+            }
+        }
+        parse_pubkey("account.slots_account", &self.account.slots_account)?;
+        if let Some(value) = &self.account.leaf_chunk_discriminator {
+            parse_discriminator("account.leaf_chunk_discriminator", value)?;
+        }
+        if let Some(address) = &self.account.expected_leaf_chunk_owner {
+            parse_pubkey("account.expected_leaf_chunk_owner", address)?;
+        }
+        if let Some(address) = &self.account.world_state_account {
+            parse_pubkey("account.world_state_account", address)?;
+        }
+        if self.account.slot_source == SlotSource::Account && self.account.slot_data_offset.is_none() {
+            return Err(eyre::eyre!("account.slot_data_offset: required when slot_source is \"account\""));
+        }
+        if self.account.root_len == 0 {
+            return Err(eyre::eyre!("account.root_len: must be greater than 0"));
+        }
+        if self.account.world_state_vec_offset.is_some() && self.account.world_state_vec_item_len == 0 {
+            return Err(eyre::eyre!("account.world_state_vec_item_len: must be greater than 0"));
+        }
+        if let Some(root) = &self.settings.mt_root_override {
+            parse_root("--mt-root", root, self.account.root_len)?;
+        }
+        if let Some(root) = &self.settings.ws_root_override {
+            parse_root("--ws-root", root, 32)?;
+        }
+        if let Some(replicas) = self.settings.leader_slot_modulo {
+            if replicas == 0 {
+                return Err(eyre::eyre!("settings.leader_slot_modulo: must be greater than 0"));
+            }
+            if self.settings.replica_index >= replicas {
+                return Err(eyre::eyre!(
+                    "settings.replica_index: must be below leader_slot_modulo ({}), got {}",
+                    replicas,
+                    self.settings.replica_index
+                ));
+            }
+        }
+        if self.settings.root_transform != RootTransform::None && self.settings.root_transform_fields.is_empty() {
+            return Err(eyre::eyre!("settings.root_transform_fields: at least one field is required"));
+        }
+        self.jito.validate()?;
+        self.multisig.validate()?;
+        if let Some(expected) = &self.wallet.expected_pubkey {
+            parse_pubkey("wallet.expected_pubkey", expected)?;
+        }
+        self.program_errors.validate()?;
+        if let Some(url) = &self.jito.block_engine_url {
+            validate_urls("jito.block_engine_url", std::slice::from_ref(url))?;
+        }
+        for address in &self.settings.static_accounts {
+            parse_pubkey("settings.static_accounts", address)?;
+        }
+        if let Some(table) = &self.settings.address_lookup_table {
+            parse_pubkey("settings.address_lookup_table", table)?;
+            if self.settings.tx_version != TxVersion::V0 {
+                return Err(eyre::eyre!("settings.address_lookup_table: requires tx_version = \"v0\""));
+            }
+        }
+        if let Some(account) = &self.nonce.nonce_account {
+            parse_pubkey("nonce.nonce_account", account)?;
+        }
+        if let Some(authority) = &self.nonce.nonce_authority {
+            parse_pubkey("nonce.nonce_authority", authority)?;
+        }
+        if let Some(webhook_url) = &self.alerts.webhook_url {
+            validate_urls("alerts.webhook_url", std::slice::from_ref(webhook_url))?;
+        }
+        let requests_per_second = self.rate_limit.requests_per_second;
+        if !requests_per_second.is_finite() || requests_per_second < 0.0 {
+            return Err(eyre::eyre!("rate_limit.requests_per_second: must be 0 or a positive number"));
+        }
+
+        L1Target::all(self)?;
+
+        for (key, value) in [
+            ("network.expected_l1_genesis", &self.network.expected_l1_genesis),
+            ("network.expected_l2_genesis", &self.network.expected_l2_genesis),
+        ] {
+            if let Some(value) = value {
+                parse_hash(key, value)?;
+            }
+        }
+
+        validate_urls("network.l1_rpc_url", &self.network.l1_rpc_url)?;
+        validate_urls("network.l2_rpc_url", &self.network.l2_rpc_url)?;
+        for (url, auth) in &self.network.endpoint_auth {
+            let key = format!("network.endpoint_auth.{:?}", url);
+            if !self.network.l1_rpc_url.contains(url) && !self.network.l2_rpc_url.contains(url) {
+                return Err(eyre::eyre!("{}: not one of the configured RPC URLs", key));
+            }
+            auth.header_map().wrap_err_with(|| key.clone())?;
+            auth.url_with_query(url).wrap_err_with(|| key.clone())?;
+            auth.identity().wrap_err(key)?;
+        }
+        if let Some(ws_url) = &self.network.l2_ws_url {
+            let parsed = reqwest::Url::parse(ws_url)
+                .map_err(|e| eyre::eyre!("network.l2_ws_url: invalid URL {:?}: {}", ws_url, e))?;
+            if !matches!(parsed.scheme(), "ws" | "wss") {
+                return Err(eyre::eyre!("network.l2_ws_url: unsupported URL scheme in {:?}", ws_url));
+            }
+        }
+
+        match &self.wallet.source {
+            WalletSource::Path { wallet_path } => {
+                let expanded = shellexpand::tilde(wallet_path);
+                File::open(Path::new(expanded.as_ref()))
+                    .map_err(|e| eyre::eyre!("wallet.wallet_path: cannot read {}: {}", wallet_path, e))?;
+            }
+            WalletSource::EnvVar { env_var } => {
+                if std::env::var_os(env_var).is_none() {
+                    return Err(eyre::eyre!("wallet.env_var: ${} is not set", env_var));
+                }
+            }
+            WalletSource::Base58 { .. } => {}
+            WalletSource::Ledger { ledger } => {
+                crate::ledger::parse_derivation_path(ledger)?;
+            }
+        }
+        if let Some(fee_payer_path) = &self.wallet.fee_payer_path {
+            let expanded = shellexpand::tilde(fee_payer_path);
+            File::open(Path::new(expanded.as_ref()))
+                .map_err(|e| eyre::eyre!("wallet.fee_payer_path: cannot read {}: {}", fee_payer_path, e))?;
+        }
+
+        if self.settings.check_interval_secs == 0 {
+            return Err(eyre::eyre!("settings.check_interval_secs must be greater than 0"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a pubkey, naming the offending config key on failure
+pub fn parse_pubkey(key: &str, value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).map_err(|e| eyre::eyre!("{}: invalid pubkey {:?}: {}", key, value, e))
+}
+
+/// Parses a base58 hash, naming the offending config key on failure
+pub fn parse_hash(key: &str, value: &str) -> Result<Hash> {
+    Hash::from_str(value).map_err(|e| eyre::eyre!("{}: invalid hash {:?}: {}", key, value, e))
+}
+
+/// Parses a hex-encoded 8-byte discriminator, naming the offending config key on failure
+pub fn parse_discriminator(key: &str, value: &str) -> Result<[u8; DISCRIMINATOR_LEN]> {
+    let bytes = hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| eyre::eyre!("{}: invalid hex {:?}: {}", key, value, e))?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| eyre::eyre!("{}: expected {} bytes, got {}", key, DISCRIMINATOR_LEN, bytes.len()))
+}
+
+/// Parses a hex-encoded root of `len` bytes, naming the offending option on failure
+pub fn parse_root(key: &str, value: &str, len: usize) -> Result<Vec<u8>> {
+    let bytes = hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| eyre::eyre!("{}: invalid hex {:?}: {}", key, value, e))?;
+    if bytes.len() != len {
+        return Err(eyre::eyre!("{}: expected {} bytes, got {}", key, len, bytes.len()));
+    }
+    Ok(bytes)
+}
+
+/// Parses the leaf chunk addresses, of which there must be at least one
+pub fn parse_leaf_chunks(addresses: &[String]) -> Result<Vec<Pubkey>> {
+    if addresses.is_empty() {
+        return Err(eyre::eyre!("account.leaf_chunk_address: at least one address is required"));
+    }
+    addresses
+        .iter()
+        .enumerate()
+        .map(|(index, address)| parse_pubkey(&format!("account.leaf_chunk_address[{}]", index), address))
+        .collect()
+}
+
+/// Checks that a list of RPC URLs is non-empty and every entry is an http(s) URL
+fn validate_urls(key: &str, urls: &[String]) -> Result<()> {
+    if urls.is_empty() {
+        return Err(eyre::eyre!("{}: at least one URL is required", key));
+    }
+    for url in urls {
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|e| eyre::eyre!("{}: invalid URL {:?}: {}", key, url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(eyre::eyre!("{}: unsupported URL scheme in {:?}", key, url));
+        }
+    }
+    Ok(())
+}
+
+/// Prefix of the environment variables overriding config values, as in `SOLPROPOSER__NETWORK__L1_RPC_URL`
+pub const ENV_PREFIX: &str = "SOLPROPOSER";
+
+/// Separates the prefix, sections and keys in those variable names
+const ENV_SEPARATOR: &str = "__";
+
+/// Loads configuration from the given file, with or without its extension, overridden by `SOLPROPOSER__*` variables
+///
+/// The file may be missing when variables supply the configuration instead.
+pub fn load_config(path: &str) -> Result<Config> {
+    // Without any variable set the file must exist, so a mistyped --config is not reported as missing fields
+    let env_prefix = format!("{}{}", ENV_PREFIX, ENV_SEPARATOR);
+    let from_env = std::env::vars_os().any(|(key, _)| key.to_string_lossy().starts_with(&env_prefix));
+    let settings = config::Config::builder()
+        .add_source(config::File::with_name(path).required(!from_env))
+        .add_source(
+            config::Environment::with_prefix(ENV_PREFIX)
+                .separator(ENV_SEPARATOR)
+                .try_parsing(true),
+        )
+        .build()?;
+    
+    Ok(settings.try_deserialize()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_file_values_from_environment() {
+        let path = std::env::temp_dir().join(format!("sol-proposer-env-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+            [network]
+            l1_rpc_url = "http://127.0.0.1:1"
+            l2_rpc_url = "http://127.0.0.1:2"
+            l1_program_id = "11111111111111111111111111111111"
+
+            [account]
+            leaf_chunk_address = "11111111111111111111111111111111"
+            slots_account = "11111111111111111111111111111111"
+
+            [wallet]
+            wallet_path = "~/.config/solana/id.json"
+
+            [settings]
+            check_interval_secs = 60
+            "#,
+        )
+        .unwrap();
+
+        std::env::set_var("SOLPROPOSER__NETWORK__L1_RPC_URL", "http://127.0.0.1:3");
+        std::env::set_var("SOLPROPOSER__SETTINGS__CHECK_INTERVAL_SECS", "5");
+        let config = load_config(path.to_str().unwrap()).unwrap();
+        std::env::remove_var("SOLPROPOSER__NETWORK__L1_RPC_URL");
+        std::env::remove_var("SOLPROPOSER__SETTINGS__CHECK_INTERVAL_SECS");
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.network.l1_rpc_url, ["http://127.0.0.1:3"]);
+        assert_eq!(config.network.l2_rpc_url, ["http://127.0.0.1:2"]);
+        assert_eq!(config.settings.check_interval_secs, 5);
+    }
+}
@@ -0,0 +1,319 @@
+//! Sending transactions and polling for a bounded confirmation.
+
+use crate::client::L1Submitter;
+use crate::config::Commitment;
+use crate::error::BridgeError;
+use crate::metrics;
+use crate::program_errors::transaction_error;
+use crate::retry::{is_transient, with_retry, RetryPolicy};
+use crate::rpc;
+use eyre::Result;
+use serde::Deserialize;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::{TransactionError, VersionedTransaction};
+use solana_transaction_status::TransactionConfirmationStatus;
+use std::fmt;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// RPC send settings as read from the optional `[send]` config section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SendConfig {
+    /// Send without the node simulating the transaction first
+    pub skip_preflight: bool,
+    /// Commitment the preflight simulation runs at; defaults to `network.l1_commitment`
+    pub preflight_commitment: Option<Commitment>,
+    /// Times the RPC node rebroadcasts the transaction; the node's own default when unset
+    pub max_retries: Option<usize>,
+}
+
+impl SendConfig {
+    /// The `sendTransaction` config, simulating at `default_commitment` unless `preflight_commitment` is set
+    pub fn rpc_config(&self, default_commitment: Commitment) -> RpcSendTransactionConfig {
+        let preflight_commitment: CommitmentConfig = self.preflight_commitment.unwrap_or(default_commitment).into();
+        RpcSendTransactionConfig {
+            skip_preflight: self.skip_preflight,
+            preflight_commitment: Some(preflight_commitment.commitment),
+            max_retries: self.max_retries,
+            ..RpcSendTransactionConfig::default()
+        }
+    }
+}
+
+/// How the interval between signature status polls changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PollBackoff {
+    /// Poll at the same interval throughout
+    #[default]
+    Linear,
+    /// Double the interval after every poll, up to the maximum
+    Exponential,
+}
+
+/// Intervals between signature status polls
+#[derive(Debug, Clone)]
+pub struct PollSchedule {
+    pub interval: Duration,
+    pub max_interval: Duration,
+    pub backoff: PollBackoff,
+}
+
+impl PollSchedule {
+    /// Interval to wait after the one just waited
+    pub fn next(&self, interval: Duration) -> Duration {
+        match self.backoff {
+            PollBackoff::Linear => interval,
+            PollBackoff::Exponential => (interval * 2).min(self.max_interval.max(self.interval)),
+        }
+    }
+}
+
+/// How waiting for a sent transaction ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmationOutcome {
+    /// Reached the target commitment, short of finalized
+    Confirmed,
+    Finalized,
+    /// Never landed and its blockhash expired, so it never will
+    Dropped,
+    /// Neither landed at the target commitment nor expired in time
+    TimedOut,
+}
+
+/// A failed transaction's error followed by the program log lines explaining it
+#[derive(Debug)]
+pub struct ProgramLogs {
+    pub error: String,
+    pub logs: Vec<String>,
+}
+
+impl fmt::Display for ProgramLogs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}; program logs: {}", self.error, self.logs.join(" | "))
+    }
+}
+
+impl std::error::Error for ProgramLogs {}
+
+/// Wraps `err` with the `Program log:` lines among `logs`, leaving it unchanged when there are none
+pub fn attach_program_logs(err: eyre::Report, logs: Vec<String>) -> eyre::Report {
+    let logs: Vec<String> = logs.into_iter().filter(|line| line.starts_with("Program log:")).collect();
+    if logs.is_empty() {
+        return err;
+    }
+    let error = err.to_string();
+    err.wrap_err(ProgramLogs { error, logs })
+}
+
+/// Logs of the simulation a send was rejected by, when preflight checks failed
+pub fn preflight_logs(err: &eyre::Report) -> Option<Vec<String>> {
+    match err.downcast_ref::<ClientError>()?.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => result.logs.clone(),
+        _ => None,
+    }
+}
+
+/// Returns true if the transaction was rejected because its blockhash expired
+///
+/// Such a transaction can never land; it has to be re-signed with a fresh blockhash.
+pub fn is_blockhash_not_found(err: &eyre::Report) -> bool {
+    matches!(transaction_error(err), Some(TransactionError::BlockhashNotFound))
+}
+
+/// Returns true if the node had already processed this exact transaction, as when a resend races the original
+pub fn is_already_processed(err: &eyre::Report) -> bool {
+    matches!(transaction_error(err), Some(TransactionError::AlreadyProcessed))
+        || format!("{:#}", err).contains("This transaction has already been processed")
+}
+
+/// How to wait for a sent transaction
+#[derive(Debug, Clone)]
+pub struct ConfirmOptions {
+    pub commitment: CommitmentConfig,
+    /// Total time to wait before giving up
+    pub timeout: Duration,
+    pub poll: PollSchedule,
+    /// Blockhash the transaction was signed with, whose expiry means it was dropped;
+    /// unset for durable nonce transactions, which do not expire
+    pub blockhash: Option<Hash>,
+}
+
+/// Sends a transaction, retrying the send under `send_policy`, and polls its status until it reaches the target commitment
+///
+/// A send rejected as already processed is confirmed like a successful one. The send
+/// and the confirmation wait are timed separately.
+pub async fn send_and_confirm(
+    client: &Arc<dyn L1Submitter>,
+    transaction: &VersionedTransaction,
+    send_config: RpcSendTransactionConfig,
+    send_policy: &RetryPolicy,
+    options: &ConfirmOptions,
+) -> Result<Signature> {
+    let started = Instant::now();
+    let sent = with_retry(send_policy, || {
+        let (client, transaction) = (client.clone(), transaction.clone());
+        rpc::blocking(move || Ok(client.send_transaction_with_config(&transaction, send_config)?))
+    })
+    .await;
+    // The earlier send landed, so confirm that one instead of failing
+    let signature = match sent {
+        Ok(signature) => signature,
+        Err(e) if is_already_processed(&e) => {
+            let signature = transaction.signatures[0];
+            info!(%signature, "transaction already processed, confirming the earlier send");
+            signature
+        }
+        Err(e) => return Err(e),
+    };
+    metrics::observe_duration("send", started.elapsed());
+    await_confirmation(client, signature, options, started).await
+}
+
+/// Waits for a transaction sent at `started`, failing unless it was confirmed or finalized
+pub async fn await_confirmation(
+    client: &Arc<dyn L1Submitter>,
+    signature: Signature,
+    options: &ConfirmOptions,
+    started: Instant,
+) -> Result<Signature> {
+    let sent = Instant::now();
+    let outcome = poll_confirmation(client, &signature, options).await?;
+    match outcome {
+        ConfirmationOutcome::Confirmed | ConfirmationOutcome::Finalized => {
+            let confirm = sent.elapsed();
+            metrics::observe_duration("confirm", confirm);
+            debug!(
+                %signature,
+                ?outcome,
+                send_ms = (sent - started).as_millis() as u64,
+                confirm_ms = confirm.as_millis() as u64,
+                "transaction reached commitment"
+            );
+            Ok(signature)
+        }
+        ConfirmationOutcome::Dropped => Err(BridgeError::TransactionDropped { signature }.into()),
+        ConfirmationOutcome::TimedOut => Err(BridgeError::ConfirmationTimeout {
+            signature,
+            timeout: options.timeout,
+        }
+        .into()),
+    }
+}
+
+/// Polls the status of a sent transaction until it reaches the target commitment, is dropped or times out
+///
+/// Fails when the transaction landed with an error.
+pub async fn poll_confirmation(
+    client: &Arc<dyn L1Submitter>,
+    signature: &Signature,
+    options: &ConfirmOptions,
+) -> Result<ConfirmationOutcome> {
+    let deadline = Instant::now() + options.timeout;
+    let mut interval = options.poll.interval;
+    loop {
+        let (lookup, status_signature) = (client.clone(), *signature);
+        match rpc::blocking(move || Ok(lookup.get_signature_status(&status_signature)?)).await {
+            Ok(Some(status)) if status.satisfies_commitment(options.commitment) => {
+                if let Some(err) = status.err {
+                    return Err(err.into());
+                }
+                return Ok(match status.confirmation_status() {
+                    TransactionConfirmationStatus::Finalized => ConfirmationOutcome::Finalized,
+                    _ => ConfirmationOutcome::Confirmed,
+                });
+            }
+            // Landed, but not yet at the target commitment
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                // Checked after the status, so a transaction that just landed is never reported dropped
+                if let Some(blockhash) = options.blockhash {
+                    let (lookup, commitment) = (client.clone(), options.commitment);
+                    match rpc::blocking(move || Ok(lookup.is_blockhash_valid(&blockhash, commitment)?)).await {
+                        Ok(false) => return Ok(ConfirmationOutcome::Dropped),
+                        Ok(true) => {}
+                        Err(e) => keep_polling_if_transient(e, signature, "blockhash")?,
+                    }
+                }
+            }
+            Err(e) => keep_polling_if_transient(e, signature, "signature status")?,
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(ConfirmationOutcome::TimedOut);
+        }
+        tokio::time::sleep(interval.min(deadline - now)).await;
+        interval = options.poll.next(interval);
+    }
+}
+
+/// Fails with `err` unless it is transient, in which case the lookup counts as no answer yet
+///
+/// The transaction is already sent, so a flaky lookup must not end the wait early.
+fn keep_polling_if_transient(err: eyre::Report, signature: &Signature, lookup: &str) -> Result<()> {
+    if !is_transient(&err) {
+        return Err(err);
+    }
+    debug!(%signature, lookup, error = %err, "lookup failed while polling, trying again");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockClient;
+
+    fn options(blockhash: Option<Hash>) -> ConfirmOptions {
+        ConfirmOptions {
+            commitment: CommitmentConfig::confirmed(),
+            timeout: Duration::from_millis(20),
+            poll: PollSchedule {
+                interval: Duration::from_millis(5),
+                max_interval: Duration::from_millis(8),
+                backoff: PollBackoff::Exponential,
+            },
+            blockhash,
+        }
+    }
+
+    #[tokio::test]
+    async fn distinguishes_confirmation_outcomes() {
+        let mock = MockClient::new(1);
+        let client: Arc<dyn L1Submitter> = Arc::new(mock.clone());
+        let signature = Signature::new_unique();
+        let outcome = poll_confirmation(&client, &signature, &options(Some(Hash::new_unique()))).await;
+        assert_eq!(outcome.unwrap(), ConfirmationOutcome::Finalized);
+
+        mock.drop_transactions();
+        let outcome = poll_confirmation(&client, &signature, &options(Some(Hash::new_unique()))).await;
+        assert_eq!(outcome.unwrap(), ConfirmationOutcome::Dropped);
+        let outcome = poll_confirmation(&client, &signature, &options(None)).await;
+        assert_eq!(outcome.unwrap(), ConfirmationOutcome::TimedOut);
+
+        // A failed lookup is polled again rather than ending the wait
+        let mock = MockClient::new(1);
+        mock.fail_status_lookups(1);
+        let client: Arc<dyn L1Submitter> = Arc::new(mock);
+        let outcome = poll_confirmation(&client, &signature, &options(None)).await;
+        assert_eq!(outcome.unwrap(), ConfirmationOutcome::Finalized);
+
+        let schedule = options(None).poll;
+        assert_eq!(schedule.next(Duration::from_millis(5)), Duration::from_millis(8));
+        let linear = PollSchedule {
+            backoff: PollBackoff::Linear,
+            ..schedule
+        };
+        assert_eq!(linear.next(Duration::from_millis(5)), Duration::from_millis(5));
+    }
+}
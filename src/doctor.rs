@@ -0,0 +1,231 @@
+//! Pre-flight checks of the configuration, endpoints, wallet and L1 program.
+
+use crate::bridge::{self, BridgeContext};
+use crate::config::Config;
+use crate::wallet::{load_fee_payer, load_wallet};
+use eyre::Result;
+use solana_sdk::native_token::lamports_to_sol;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+
+/// Outcome of one check: a short detail on success, the error on failure
+pub struct Check {
+    pub name: String,
+    pub result: Result<String>,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, result: Result<String>) -> Self {
+        Self {
+            name: name.into(),
+            result,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// Runs every check against `config`, stopping early when no context can be built
+pub async fn run(config: Config) -> Vec<Check> {
+    let mut checks = vec![Check::new("config is valid", config.validate().map(|()| "ok".to_string()))];
+    if !checks[0].passed() {
+        return checks;
+    }
+
+    let wallet = async {
+        let wallet = load_wallet(&config.wallet).await?;
+        let detail = match load_fee_payer(&config.wallet)? {
+            Some(fee_payer) => format!("authority {}, fee payer {}", wallet.pubkey(), fee_payer.pubkey()),
+            None => format!("authority {}", wallet.pubkey()),
+        };
+        Ok(detail)
+    }
+    .await;
+    checks.push(Check::new("wallet loads", wallet));
+    if !checks[1].passed() {
+        return checks;
+    }
+
+    match BridgeContext::new(config).await {
+        Ok(ctx) => checks.extend(check_context(&ctx).await),
+        Err(e) => checks.push(Check::new("clients build", Err(e))),
+    }
+    checks
+}
+
+/// Checks the endpoints, fee payer balance, leaf chunk and every L1 target of a built context
+pub async fn check_context(ctx: &BridgeContext) -> Vec<Check> {
+    let (l1_client, l2_client) = (ctx.l1_client.clone(), ctx.l2_client.clone());
+    let mut checks = vec![
+        Check::new(
+            "L1 RPC is healthy",
+            ctx.rpc_call(move || Ok(l1_client.get_health()?)).await.map(|()| "ok".to_string()),
+        ),
+        Check::new(
+            "L2 RPC is healthy",
+            ctx.rpc_call(move || Ok(l2_client.get_health()?)).await.map(|()| "ok".to_string()),
+        ),
+        Check::new(
+            "clusters match expected genesis",
+            bridge::check_clusters(ctx).await.map(|()| "ok".to_string()),
+        ),
+        Check::new("fee payer balance", check_balance(ctx).await),
+    ];
+
+    let roots = bridge::read_roots(ctx).await;
+    checks.push(Check::new(
+        "leaf chunk account parses",
+        roots
+            .as_ref()
+            .map(|roots| format!("slot {}, mt_root {}", roots.slot, hex::encode(&roots.mt_root)))
+            .map_err(|e| eyre::eyre!("{:#}", e)),
+    ));
+
+    for target in &ctx.targets {
+        checks.push(Check::new(
+            format!("{}: L1 program is deployed", target.name),
+            check_program(ctx, &target.program_id).await,
+        ));
+
+        let simulation = match &roots {
+            Ok(roots) => match bridge::simulate_submission(ctx, target, roots).await {
+                Ok(()) => Ok(format!("slot {}", roots.slot)),
+                Err(e) if ctx.config.program_errors.is_already_initialized(&e) => {
+                    Ok(format!("slot {} already recorded on L1", roots.slot))
+                }
+                Err(e) => Err(e),
+            },
+            Err(_) => Err(eyre::eyre!("skipped, the leaf chunk account could not be read")),
+        };
+        checks.push(Check::new(format!("{}: dry-run simulation succeeds", target.name), simulation));
+    }
+
+    checks
+}
+
+/// Checks the fee payer holds at least `settings.min_balance_lamports`
+async fn check_balance(ctx: &BridgeContext) -> Result<String> {
+    let fee_payer = ctx.payer().pubkey();
+    let client = ctx.l1_client.clone();
+    let balance = ctx.rpc_call(move || Ok(client.get_balance(&fee_payer)?)).await?;
+    let min_balance = ctx.config.settings.min_balance_lamports;
+    if balance < min_balance {
+        return Err(eyre::eyre!(
+            "{} holds {} SOL, below settings.min_balance_lamports ({} SOL)",
+            fee_payer,
+            lamports_to_sol(balance),
+            lamports_to_sol(min_balance)
+        ));
+    }
+    Ok(format!("{} holds {} SOL", fee_payer, lamports_to_sol(balance)))
+}
+
+/// Checks `program_id` exists on L1 and is executable
+async fn check_program(ctx: &BridgeContext, program_id: &Pubkey) -> Result<String> {
+    let commitment = ctx.config.network.l1_commitment.into();
+    let account = ctx
+        .get_l1_account(program_id, commitment)
+        .await?
+        .ok_or_else(|| eyre::eyre!("program {} not found on L1", program_id))?;
+    if !account.executable {
+        return Err(eyre::eyre!("account {} is not an executable program", program_id));
+    }
+    Ok(program_id.to_string())
+}
+
+/// Prints one `[PASS]` or `[FAIL]` line per check, returning whether all passed
+pub fn print_checklist(checks: &[Check]) -> bool {
+    for check in checks {
+        match &check.result {
+            Ok(detail) => println!("[PASS] {}: {}", check.name, detail),
+            Err(e) => println!("[FAIL] {}: {:#}", check.name, e),
+        }
+    }
+    let failed = checks.iter().filter(|check| !check.passed()).count();
+    println!("{} of {} checks passed", checks.len() - failed, checks.len());
+    failed == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockClient;
+    use solana_sdk::account::Account;
+    use solana_sdk::signature::Keypair;
+
+    /// Builds a context over a mock holding the leaf chunk and, when `deployed`, the L1 program
+    async fn context(leaf_chunk_data: Option<Vec<u8>>, deployed: bool) -> BridgeContext {
+        let leaf_chunk = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let config: Config = toml::from_str(&format!(
+            r#"
+            [network]
+            l1_rpc_url = "http://127.0.0.1:1"
+            l2_rpc_url = "http://127.0.0.1:2"
+            l1_program_id = "{program_id}"
+
+            [account]
+            leaf_chunk_address = "{leaf_chunk}"
+            slots_account = "{slots_account}"
+
+            [wallet]
+            base58 = "{wallet}"
+
+            [settings]
+            check_interval_secs = 1
+            "#,
+            slots_account = Pubkey::new_unique(),
+            wallet = Keypair::new().to_base58_string(),
+        ))
+        .unwrap();
+
+        let mock = MockClient::new(7);
+        if let Some(data) = leaf_chunk_data {
+            mock.set_account(leaf_chunk, data);
+        }
+        if deployed {
+            mock.insert_account(
+                program_id,
+                Account {
+                    lamports: 1_000_000,
+                    data: Vec::new(),
+                    owner: solana_sdk::bpf_loader_upgradeable::id(),
+                    executable: true,
+                    rent_epoch: 0,
+                },
+            );
+        }
+        BridgeContext::with_clients(config, Box::new(mock.clone()), Box::new(mock))
+            .await
+            .unwrap()
+    }
+
+    fn failed(checks: &[Check]) -> Vec<&str> {
+        checks.iter().filter(|check| !check.passed()).map(|check| check.name.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn passes_on_healthy_setup() {
+        let ctx = context(Some([[0u8; 8].as_slice(), &[1; 32]].concat()), true).await;
+        let checks = check_context(&ctx).await;
+        assert!(failed(&checks).is_empty());
+        assert!(print_checklist(&checks));
+    }
+
+    #[tokio::test]
+    async fn reports_missing_accounts() {
+        let ctx = context(None, false).await;
+        let checks = check_context(&ctx).await;
+        assert_eq!(
+            failed(&checks),
+            [
+                "leaf chunk account parses",
+                "primary: L1 program is deployed",
+                "primary: dry-run simulation succeeds",
+            ]
+        );
+        assert!(!print_checklist(&checks));
+    }
+}
@@ -0,0 +1,113 @@
+//! Errors a bridge cycle fails with, so callers can tell them apart without parsing messages.
+
+use crate::leaf_chunk::DISCRIMINATOR_LEN;
+use solana_client::client_error::ClientError;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::time::Duration;
+
+/// The ways reading the roots from L2 and submitting them to L1 can fail
+#[derive(Debug, thiserror::Error)]
+pub enum BridgeError {
+    /// A configured account does not exist; `account` names its role, such as `slots`
+    #[error("{account} account {address} not found")]
+    AccountNotFound { account: &'static str, address: Pubkey },
+
+    /// Account data ends before a field the layout places in it
+    #[error("account data too short: {len} < {needed}")]
+    AccountTooShort { len: usize, needed: usize },
+
+    /// The account's discriminator did not match the expected account type
+    #[error("unexpected account discriminator: got {} expected {}", hex::encode(.actual), hex::encode(.expected))]
+    DiscriminatorMismatch {
+        expected: [u8; DISCRIMINATOR_LEN],
+        actual: [u8; DISCRIMINATOR_LEN],
+    },
+
+    /// A leaf chunk account was closed or holds only zeroes, which takes an L2-side fix rather than a retry
+    #[error("leaf chunk account {index} ({address}) {}", missing_state(.uninitialized))]
+    LeafChunkMissing {
+        /// Position of the chunk in `account.leaf_chunk_address`
+        index: usize,
+        address: Pubkey,
+        /// The account exists but was reallocated or zeroed rather than closed
+        uninitialized: bool,
+    },
+
+    /// `account.leaf_chunk_filter` matched no account, or more than one
+    #[error("account.leaf_chunk_filter matched {matches} accounts, expected exactly one")]
+    LeafChunkUnresolved { matches: usize },
+
+    /// The L2 RPC served an older slot than one already read
+    #[error("slot regression: got {slot} after {max_seen_slot}")]
+    SlotRegression { slot: u64, max_seen_slot: u64 },
+
+    /// In observer mode, L1 trails L2 by more than `settings.observer_max_lag_slots`
+    #[error("L1 lagging by {lag_slots} slots at slot {slot}, more than the allowed {max_lag_slots}")]
+    L1Lagging { slot: u64, lag_slots: u64, max_lag_slots: u64 },
+
+    /// An RPC call kept failing with errors that may clear up, after its retries ran out
+    #[error(transparent)]
+    RpcTransient(Box<ClientError>),
+
+    /// An RPC call did not complete within `settings.rpc_timeout_secs`
+    #[error("RPC call timed out after {}s", .timeout.as_secs())]
+    RpcTimeout { timeout: Duration },
+
+    /// The RPC served a blockhash older than `settings.max_blockhash_age_slots`
+    #[error("blockhash {blockhash} is {age} blocks old, over the {max_age} allowed")]
+    StaleBlockhash {
+        blockhash: Hash,
+        /// Blocks produced since the blockhash
+        age: u64,
+        max_age: u64,
+    },
+
+    /// The signed transaction does not fit in a single packet
+    #[error("transaction of {instructions} instructions is {size} bytes, over the {max} byte limit")]
+    TransactionTooLarge { instructions: usize, size: u64, max: usize },
+
+    /// The transaction was sent but did not reach the target commitment in time
+    #[error("transaction {signature} not confirmed within {}s", .timeout.as_secs())]
+    ConfirmationTimeout { signature: Signature, timeout: Duration },
+
+    /// The transaction's blockhash expired before it landed
+    #[error("transaction {signature} dropped, its blockhash expired before it landed")]
+    TransactionDropped { signature: Signature },
+
+    /// The fee payer cannot safely cover another submission
+    #[error("insufficient fee payer balance: have {have} lamports, need about {need_estimate}")]
+    InsufficientBalance { have: u64, need_estimate: u64 },
+
+    /// The L1 program returned a custom error code configured in `program_errors.fatal`
+    #[error("L1 program returned fatal error code {code}, aborting the cycle")]
+    FatalProgram { code: u32 },
+}
+
+impl BridgeError {
+    /// Finds the bridge error behind `err`, through any context wrapped around it
+    pub fn find(err: &eyre::Report) -> Option<&Self> {
+        err.downcast_ref().or_else(|| err.chain().find_map(|e| e.downcast_ref()))
+    }
+
+    /// Returns true for errors that may succeed if the cycle is repeated
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::RpcTransient(_)
+                | Self::RpcTimeout { .. }
+                | Self::StaleBlockhash { .. }
+                | Self::ConfirmationTimeout { .. }
+                | Self::TransactionDropped { .. }
+        )
+    }
+}
+
+fn missing_state(uninitialized: &bool) -> &'static str {
+    if *uninitialized {
+        "uninitialized"
+    } else {
+        "not found"
+    }
+}
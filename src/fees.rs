@@ -0,0 +1,77 @@
+//! Priority fee and compute budget configuration.
+
+use crate::client::L1Submitter;
+use eyre::Result;
+use serde::Deserialize;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+
+/// How the compute unit price is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeMode {
+    /// Always use `compute_unit_price`
+    Fixed,
+    /// Use a percentile of recent prioritization fees, never below `compute_unit_price`
+    Auto,
+}
+
+/// Fee configuration as read from the `[fees]` config section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FeesConfig {
+    pub mode: FeeMode,
+    /// Price in micro-lamports per compute unit
+    pub compute_unit_price: u64,
+    pub compute_unit_limit: u32,
+    /// Percentile of recent fees used in auto mode, 0-100
+    pub auto_percentile: u8,
+}
+
+impl Default for FeesConfig {
+    fn default() -> Self {
+        Self {
+            mode: FeeMode::Fixed,
+            compute_unit_price: 0,
+            compute_unit_limit: 200_000,
+            auto_percentile: 75,
+        }
+    }
+}
+
+/// Resolves the compute unit price for a transaction touching `accounts`
+pub fn resolve_compute_unit_price(
+    config: &FeesConfig,
+    client: &dyn L1Submitter,
+    accounts: &[Pubkey],
+) -> Result<u64> {
+    match config.mode {
+        FeeMode::Fixed => Ok(config.compute_unit_price),
+        FeeMode::Auto => {
+            let recent = client.get_recent_prioritization_fees(accounts)?;
+            let mut fees: Vec<u64> = recent.iter().map(|f| f.prioritization_fee).collect();
+            let price = percentile(&mut fees, config.auto_percentile);
+            Ok(price.max(config.compute_unit_price))
+        }
+    }
+}
+
+/// Builds the compute budget instructions to prepend to a transaction
+pub fn compute_budget_instructions(compute_unit_price: u64, compute_unit_limit: u32) -> Vec<Instruction> {
+    vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit),
+        ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price),
+    ]
+}
+
+/// Returns the nearest-rank percentile of `values`, or zero when empty
+fn percentile(values: &mut [u64], pct: u8) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort_unstable();
+    let pct = pct.min(100) as usize;
+    let rank = (pct * values.len()).div_ceil(100).max(1);
+    values[rank - 1]
+}
@@ -0,0 +1,121 @@
+//! Liveness tracking for the `/health` endpoint.
+
+use crate::metrics;
+use axum::http::StatusCode;
+use axum::Json;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Process-wide record of cycle outcomes
+pub static HEALTH: Lazy<HealthState> = Lazy::new(HealthState::new);
+
+/// Timestamps and counters describing recent cycles
+pub struct HealthState {
+    started_unix: u64,
+    last_success_unix: AtomicU64,
+    consecutive_failures: AtomicU64,
+    circuit_open: AtomicBool,
+    ready: AtomicBool,
+    /// Age of the last success past which `/health` reports unhealthy
+    max_age_secs: AtomicU64,
+    l1_lag_slots: Mutex<BTreeMap<String, u64>>,
+}
+
+/// JSON body returned by `/health`
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    pub healthy: bool,
+    /// False while the service is still waiting for its RPC endpoints to become healthy
+    pub ready: bool,
+    pub last_success_unix: Option<u64>,
+    pub last_submitted_slot: u64,
+    pub consecutive_failures: u64,
+    pub circuit_open: bool,
+    /// Latest L2 slot minus the highest slot recorded on L1, per target where it is measured
+    pub l1_lag_slots: BTreeMap<String, u64>,
+}
+
+impl HealthState {
+    fn new() -> Self {
+        Self {
+            started_unix: unix_now(),
+            last_success_unix: AtomicU64::new(0),
+            consecutive_failures: AtomicU64::new(0),
+            circuit_open: AtomicBool::new(false),
+            ready: AtomicBool::new(true),
+            max_age_secs: AtomicU64::new(u64::MAX),
+            l1_lag_slots: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Records a cycle that finished without error
+    pub fn record_success(&self) {
+        self.last_success_unix.store(unix_now(), Ordering::Relaxed);
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+    }
+
+    /// Records a failed cycle
+    pub fn record_failure(&self) {
+        self.consecutive_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records the circuit breaker state
+    pub fn set_circuit_open(&self, open: bool) {
+        self.circuit_open.store(open, Ordering::Relaxed);
+    }
+
+    /// Records whether startup warmup has finished
+    pub fn set_ready(&self, ready: bool) {
+        self.ready.store(ready, Ordering::Relaxed);
+    }
+
+    /// Sets how long after the last success `/health` turns unhealthy, kept up to date across reloads
+    pub fn set_max_age(&self, max_age_secs: u64) {
+        self.max_age_secs.store(max_age_secs, Ordering::Relaxed);
+    }
+
+    /// Records how many slots a target's L1 state trails L2 by
+    pub fn set_l1_lag(&self, target: &str, lag_slots: u64) {
+        self.l1_lag_slots.lock().unwrap().insert(target.to_string(), lag_slots);
+    }
+
+    /// Builds a report, healthy when ready and the last success (or startup) is within `max_age_secs`
+    pub fn report(&self, max_age_secs: u64) -> HealthReport {
+        let last_success = self.last_success_unix.load(Ordering::Relaxed);
+        let reference = last_success.max(self.started_unix);
+        let ready = self.ready.load(Ordering::Relaxed);
+
+        HealthReport {
+            healthy: ready && unix_now().saturating_sub(reference) <= max_age_secs,
+            ready,
+            last_success_unix: (last_success > 0).then_some(last_success),
+            last_submitted_slot: metrics::LAST_SUBMITTED_SLOT.get().max(0) as u64,
+            consecutive_failures: self.consecutive_failures.load(Ordering::Relaxed),
+            circuit_open: self.circuit_open.load(Ordering::Relaxed),
+            l1_lag_slots: self.l1_lag_slots.lock().unwrap().clone(),
+        }
+    }
+}
+
+/// Handler for `/health`, returning 503 during warmup and once the last success is older than the set maximum age
+pub async fn handler() -> (StatusCode, Json<HealthReport>) {
+    let report = HEALTH.report(HEALTH.max_age_secs.load(Ordering::Relaxed));
+    let status = if report.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+/// Seconds since the unix epoch
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
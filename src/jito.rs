@@ -0,0 +1,103 @@
+//! Submitting transactions as Jito block-engine bundles.
+
+use crate::config::parse_pubkey;
+use base64::Engine;
+use eyre::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::VersionedTransaction;
+use std::time::Duration;
+
+/// Bundle settings as read from the optional `[jito]` config section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct JitoConfig {
+    /// Block engine base URL, such as `https://mainnet.block-engine.jito.wtf`; bundles are disabled when unset
+    pub block_engine_url: Option<String>,
+    /// One of the block engine's tip accounts, receiving the tip of every bundle
+    pub tip_account: Option<String>,
+    /// Lamports tipped with every bundle
+    pub tip_lamports: u64,
+}
+
+impl JitoConfig {
+    /// Checks the tip account is set and valid whenever bundles are enabled
+    pub fn validate(&self) -> Result<()> {
+        if self.block_engine_url.is_some() {
+            let tip_account = self
+                .tip_account
+                .as_deref()
+                .ok_or_else(|| eyre::eyre!("jito.tip_account: required with block_engine_url"))?;
+            parse_pubkey("jito.tip_account", tip_account)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends single-transaction bundles to the block engine
+pub struct JitoClient {
+    bundles_url: String,
+    tip_account: Pubkey,
+    tip_lamports: u64,
+    http: reqwest::Client,
+}
+
+impl JitoClient {
+    /// Builds the client when `jito.block_engine_url` is set
+    pub fn from_config(config: &JitoConfig, timeout: Duration) -> Result<Option<Self>> {
+        let Some(url) = &config.block_engine_url else {
+            return Ok(None);
+        };
+        config.validate()?;
+        let tip_account = parse_pubkey("jito.tip_account", config.tip_account.as_deref().unwrap_or_default())?;
+        Ok(Some(Self {
+            bundles_url: format!("{}/api/v1/bundles", url.trim_end_matches('/')),
+            tip_account,
+            tip_lamports: config.tip_lamports,
+            http: reqwest::Client::builder().timeout(timeout).build()?,
+        }))
+    }
+
+    pub fn tip_lamports(&self) -> u64 {
+        self.tip_lamports
+    }
+
+    /// Transfer of the tip from `payer`, appended to the bundled transaction
+    pub fn tip_instruction(&self, payer: &Pubkey) -> Instruction {
+        system_instruction::transfer(payer, &self.tip_account, self.tip_lamports)
+    }
+
+    /// Sends `transaction` as a bundle, returning the bundle id the block engine accepted it under
+    pub async fn send_bundle(&self, transaction: &VersionedTransaction) -> Result<String> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bincode::serialize(transaction)?);
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sendBundle",
+            "params": [[encoded], { "encoding": "base64" }],
+        });
+        let body = self
+            .http
+            .post(&self.bundles_url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(&request)?)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let response: Value = serde_json::from_slice(&body)?;
+
+        if let Some(error) = response.get("error") {
+            return Err(eyre::eyre!("bundle rejected: {}", error));
+        }
+        response
+            .get("result")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| eyre::eyre!("unexpected block engine response: {}", response))
+    }
+}
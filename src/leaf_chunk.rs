@@ -0,0 +1,120 @@
+//! Typed parsing of the L2 leaf chunk account.
+
+use crate::error::BridgeError;
+use eyre::Result;
+
+/// Length of the Anchor account discriminator prefix
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// Where the fields of a leaf chunk live in the account data
+#[derive(Debug, Clone)]
+pub struct LeafChunkLayout {
+    /// Discriminator the account must start with; not checked when unset
+    pub discriminator: Option<[u8; DISCRIMINATOR_LEN]>,
+    /// Byte offset of the merkle tree root
+    pub mt_root_offset: usize,
+    /// Length in bytes of the merkle tree root
+    pub root_len: usize,
+    /// Byte offset of a little-endian `u64` slot stored in the account; not read when unset
+    pub slot_offset: Option<usize>,
+}
+
+/// Decoded leaf chunk account
+#[derive(Debug, Clone)]
+pub struct LeafChunk {
+    /// Anchor account discriminator
+    pub discriminator: [u8; DISCRIMINATOR_LEN],
+    /// Root of the L2 merkle tree, `root_len` bytes long
+    pub mt_root: Vec<u8>,
+    /// Slot stored in the account, when the layout has one
+    pub slot: Option<u64>,
+}
+
+impl LeafChunk {
+    /// Parses leaf chunk account data, checking the discriminator when the layout specifies one
+    pub fn from_bytes(data: &[u8], layout: &LeafChunkLayout) -> Result<Self> {
+        let prefix = data.get(..DISCRIMINATOR_LEN).ok_or(BridgeError::AccountTooShort {
+            len: data.len(),
+            needed: DISCRIMINATOR_LEN,
+        })?;
+        let mut discriminator = [0u8; DISCRIMINATOR_LEN];
+        discriminator.copy_from_slice(prefix);
+
+        if let Some(expected) = layout.discriminator {
+            if discriminator != expected {
+                return Err(BridgeError::DiscriminatorMismatch {
+                    expected,
+                    actual: discriminator,
+                }
+                .into());
+            }
+        }
+
+        Ok(Self {
+            discriminator,
+            mt_root: extract_bytes(data, layout.mt_root_offset, layout.root_len)?,
+            slot: layout.slot_offset.map(|offset| extract_slot(data, offset)).transpose()?,
+        })
+    }
+}
+
+/// Joins the data of several leaf chunk accounts into the layout of a single one
+///
+/// The result keeps the first chunk's discriminator, followed by every chunk's data
+/// without its discriminator, so offsets count from the start of the first chunk.
+/// When the layout has a discriminator every chunk must carry it.
+pub fn concat_chunks(chunks: &[&[u8]], layout: &LeafChunkLayout) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(chunks.iter().map(|chunk| chunk.len()).sum());
+    for (index, chunk) in chunks.iter().enumerate() {
+        let prefix = chunk.get(..DISCRIMINATOR_LEN).ok_or_else(|| {
+            eyre::Report::new(BridgeError::AccountTooShort {
+                len: chunk.len(),
+                needed: DISCRIMINATOR_LEN,
+            })
+            .wrap_err(format!("leaf chunk {}", index))
+        })?;
+        if let Some(expected) = layout.discriminator {
+            if prefix != expected {
+                return Err(eyre::Report::new(BridgeError::DiscriminatorMismatch {
+                    expected,
+                    actual: prefix.try_into().expect("slice of discriminator length"),
+                })
+                .wrap_err(format!("leaf chunk {}", index)));
+            }
+        }
+        let payload = if index == 0 { chunk } else { &chunk[DISCRIMINATOR_LEN..] };
+        data.extend_from_slice(payload);
+    }
+    Ok(data)
+}
+
+/// Extracts a little-endian `u64` slot from account data at the given offset
+pub fn extract_slot(data: &[u8], offset: usize) -> Result<u64> {
+    let slot = data
+        .get(offset..offset.saturating_add(8))
+        .ok_or(BridgeError::AccountTooShort {
+            len: data.len(),
+            needed: offset.saturating_add(8),
+        })?;
+
+    Ok(u64::from_le_bytes(slot.try_into().expect("slice of slot length")))
+}
+
+/// Extracts a 32-byte root from account data at the given offset
+pub fn extract_root(data: &[u8], offset: usize) -> Result<[u8; 32]> {
+    let root = extract_bytes(data, offset, 32)?;
+    Ok(root.try_into().expect("slice of root length"))
+}
+
+/// Extracts `len` bytes from account data at the given offset
+pub fn extract_bytes(data: &[u8], offset: usize, len: usize) -> Result<Vec<u8>> {
+    data.get(offset..offset.saturating_add(len))
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| {
+            BridgeError::AccountTooShort {
+                len: data.len(),
+                needed: offset.saturating_add(len),
+            }
+            .into()
+        })
+}
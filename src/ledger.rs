@@ -0,0 +1,100 @@
+//! Signing with a key held on a Ledger hardware wallet.
+
+use eyre::Result;
+use solana_sdk::derivation_path::DerivationPath;
+#[cfg(feature = "ledger")]
+use {
+    solana_remote_wallet::locator::Locator,
+    solana_remote_wallet::remote_keypair::{generate_remote_keypair, RemoteKeypair},
+    solana_remote_wallet::remote_wallet::maybe_wallet_manager,
+    solana_sdk::pubkey::Pubkey,
+    solana_sdk::signature::Signature,
+    solana_sdk::signer::{Signer, SignerError},
+    std::sync::mpsc,
+    std::sync::Mutex,
+};
+
+/// A message to sign and where to send its signature
+#[cfg(feature = "ledger")]
+type SignRequest = (Vec<u8>, mpsc::Sender<Result<Signature, SignerError>>);
+
+/// Signer backed by the first connected Ledger
+///
+/// The remote wallet types are not `Send`, so the device is owned by a dedicated
+/// thread and every signature is requested over a channel.
+#[cfg(feature = "ledger")]
+pub struct LedgerSigner {
+    pubkey: Pubkey,
+    requests: Mutex<mpsc::Sender<SignRequest>>,
+}
+
+#[cfg(feature = "ledger")]
+impl LedgerSigner {
+    /// Connects to the Ledger and reads the pubkey at `derivation_path`, such as `m/44'/501'/0'/0'`
+    pub fn connect(derivation_path: &str) -> Result<Self> {
+        let derivation_path = parse_derivation_path(derivation_path)?;
+        let (requests, receiver) = mpsc::channel::<SignRequest>();
+        let (ready, connected) = mpsc::channel();
+        std::thread::Builder::new().name("ledger".to_string()).spawn(move || {
+            let keypair = match open_keypair(derivation_path) {
+                Ok(keypair) => keypair,
+                Err(e) => {
+                    let _ = ready.send(Err(e));
+                    return;
+                }
+            };
+            let _ = ready.send(Ok(keypair.pubkey));
+            for (message, reply) in receiver {
+                let _ = reply.send(keypair.try_sign_message(&message));
+            }
+        })?;
+
+        let pubkey = connected
+            .recv()
+            .map_err(|_| eyre::eyre!("Ledger thread exited before connecting"))??;
+        Ok(Self {
+            pubkey,
+            requests: Mutex::new(requests),
+        })
+    }
+}
+
+#[cfg(feature = "ledger")]
+impl Signer for LedgerSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    /// Blocks until the transaction is approved on the device
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let disconnected = || SignerError::Connection("Ledger thread exited".to_string());
+        let (reply, signature) = mpsc::channel();
+        self.requests
+            .lock()
+            .unwrap()
+            .send((message.to_vec(), reply))
+            .map_err(|_| disconnected())?;
+        signature.recv().map_err(|_| disconnected())?
+    }
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
+
+/// Parses an absolute derivation path such as `m/44'/501'/0'/0'`
+pub fn parse_derivation_path(value: &str) -> Result<DerivationPath> {
+    DerivationPath::from_absolute_path_str(value)
+        .map_err(|e| eyre::eyre!("wallet.ledger: invalid derivation path {:?}: {}", value, e))
+}
+
+/// Finds the first Ledger and derives the keypair at `derivation_path`
+#[cfg(feature = "ledger")]
+fn open_keypair(derivation_path: DerivationPath) -> Result<RemoteKeypair> {
+    let wallet_manager = maybe_wallet_manager()
+        .map_err(|e| eyre::eyre!("Failed to open Ledger: {}", e))?
+        .ok_or_else(|| eyre::eyre!("No Ledger device found"))?;
+    let locator = Locator::new_from_path("usb://ledger").map_err(|e| eyre::eyre!("{}", e))?;
+    generate_remote_keypair(locator, derivation_path, &wallet_manager, false, "wallet")
+        .map_err(|e| eyre::eyre!("Failed to read the Ledger key: {}", e))
+}
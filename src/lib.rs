@@ -0,0 +1,51 @@
+//! A bridge service that reads state from L2 and submits to L1.
+//!
+//! [`BridgeService`] runs the proposer, either a cycle at a time or as the full
+//! service loop, so it can be embedded in another process as well as run by the
+//! bundled binary.
+
+// `ClientError` is large, but it is what every Solana RPC call returns
+#![allow(clippy::result_large_err)]
+
+pub mod account_cache;
+pub mod alerts;
+pub mod audit;
+pub mod balance;
+pub mod bridge;
+pub mod circuit;
+pub mod client;
+pub mod config;
+pub mod confirm;
+pub mod doctor;
+pub mod error;
+pub mod fees;
+pub mod health;
+pub mod jito;
+pub mod leaf_chunk;
+pub mod ledger;
+pub mod metrics;
+#[cfg(test)]
+mod mock;
+pub mod multisig;
+pub mod nonce;
+pub mod output;
+pub mod pending;
+pub mod program;
+pub mod program_errors;
+pub mod rate_limit;
+pub mod reload;
+pub mod retry;
+pub mod rpc;
+pub mod service;
+pub mod state;
+pub mod submission_log;
+pub mod subscribe;
+pub mod target;
+pub mod trigger;
+pub mod wallet;
+pub mod world_state;
+
+pub use bridge::{BridgeContext, CycleOutcome, CycleReport, TargetResult};
+pub use config::{load_config, Config};
+pub use reload::ConfigSource;
+pub use service::BridgeService;
@@ -1,187 +1,172 @@
-//! A bridge service that reads state from L2 and submits to L1.
-
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{
-    commitment_config::CommitmentConfig,
-    pubkey::Pubkey,
-    signature::{read_keypair_file, Keypair},
-    signer::Signer,
-    transaction::Transaction,
-    instruction::{AccountMeta, Instruction},
-};
-use eyre::Result;
-use serde::Deserialize;
-use std::str::FromStr;
-use tokio::time::{interval, Duration};
-use std::path::Path;
-
-/// Configuration structure for the bridge service
-#[derive(Debug, Deserialize)]
-struct Config {
-    network: NetworkConfig,
-    account: AccountConfig,
-    wallet: WalletConfig,
-    settings: SettingsConfig,
-}
+//! Command-line entry point of the bridge service.
 
-/// Network-related configuration
-#[derive(Debug, Deserialize)]
-struct NetworkConfig {
-    l1_rpc_url: String,
-    l2_rpc_url: String,
-    l1_program_id: String,
-}
+mod cli;
 
-/// Account addresses configuration
-#[derive(Debug, Deserialize)]
-struct AccountConfig {
-    leaf_chunk_address: String,
-    slots_account: String,
-}
+use clap::Parser;
+use cli::{Cli, Command};
+use eyre::Result;
+use l2_state_bridge::bridge::{self, BackfillSlot, BridgeContext, CycleOutcome, L2Roots, TargetResult};
+use l2_state_bridge::config::{load_config, parse_root, LogFormat, OutputFormat, SettingsConfig};
+use l2_state_bridge::submission_log::SubmissionLog;
+use l2_state_bridge::{doctor, program, target, BridgeService, ConfigSource};
+use std::process::ExitCode;
+use tracing::info;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::EnvFilter;
 
-/// Wallet configuration
-#[derive(Debug, Deserialize)]
-struct WalletConfig {
-    wallet_path: String,
-}
+/// Installs the global tracing subscriber, preferring `RUST_LOG` over the configured level
+fn init_tracing(settings: &SettingsConfig) -> Result<()> {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(&settings.log_level))?;
 
-/// General settings configuration
-#[derive(Debug, Deserialize)]
-struct SettingsConfig {
-    check_interval_secs: u64,
-}
+    // Keep stdout free for result records in JSON output mode
+    let writer = match settings.output_format {
+        OutputFormat::Text => BoxMakeWriter::new(std::io::stdout),
+        OutputFormat::Json => BoxMakeWriter::new(std::io::stderr),
+    };
 
-/// Loads configuration from config.toml file
-fn load_config() -> Result<Config> {
-    let settings = config::Config::builder()
-        .add_source(config::File::with_name("config"))
-        .build()?;
-    
-    Ok(settings.try_deserialize()?)
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(writer);
+    match settings.log_format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder.json().try_init(),
+    }
+    .map_err(|e| eyre::eyre!("Failed to initialize logging: {}", e))
 }
 
-/// Loads wallet keypair from the specified path
-async fn load_wallet(wallet_path: &str) -> Result<Keypair> {
-    let expanded_path = shellexpand::tilde(wallet_path);
-    let wallet_path = Path::new(expanded_path.as_ref());
-    let keypair = read_keypair_file(wallet_path)
-        .map_err(|e| eyre::eyre!("Failed to read wallet file: {}", e))?;
-    Ok(keypair)
-}
+/// Entry point of the bridge service
+#[tokio::main]
+async fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
+    let mut config = load_config(&cli.config)?;
+    cli.apply(&mut config);
+    init_tracing(&config.settings)?;
+    info!(path = %cli.config, "loaded configuration file");
 
-/// Main function to check L2 state and submit to L1
-async fn check_and_submit(config: &Config) -> Result<()> {
-    // Initialize RPC clients
-    let l2_client = RpcClient::new_with_commitment(
-        config.network.l2_rpc_url.clone(),
-        CommitmentConfig::confirmed(),
-    );
-    let l1_client = RpcClient::new_with_commitment(
-        config.network.l1_rpc_url.clone(),
-        CommitmentConfig::confirmed(),
-    );
-    
-    // Load wallet
-    let wallet = load_wallet(&config.wallet.wallet_path).await?;
-    
-    // Get L2 account data and corresponding slot
-    let leaf_chunk_pubkey = Pubkey::from_str(&config.account.leaf_chunk_address)?;
-    let response = l2_client.get_account_with_commitment(
-        &leaf_chunk_pubkey,
-        CommitmentConfig::confirmed(),
-    )?;
-    
-    let account = response.value.ok_or_else(|| {
-        eyre::eyre!("Account not found")
-    })?;
-    
-    let account_slot = response.context.slot;
-    
-    // Extract merkle tree root from account data
-    let account_data = account.data;
-    let mut mt_root = [0u8; 32];
-    mt_root.copy_from_slice(&account_data[8..40]);
-    // Use zero bytes for world state root (placeholder)
-    let ws_root = [0u8; 32];
-    
-    println!("Merkle tree root from L2: 0x{}", hex::encode(mt_root));
-    println!("World state root (fixed): 0x{}", hex::encode(ws_root));
-    println!("Current slot: {}", account_slot);
-    
-    // Get program and account addresses
-    let l1_program_id = Pubkey::from_str(&config.network.l1_program_id)?;
-    let slots_account = Pubkey::from_str(&config.account.slots_account)?;
-    
-    // Calculate PDA for slot roots account
-    let roots_seeds = &[b"roots".as_ref(), &account_slot.to_le_bytes()];
-    let (slot_roots_account, _) = Pubkey::find_program_address(roots_seeds, &l1_program_id);
-    
-    // Create instruction data
-    let mut instruction_data = Vec::new();
-    instruction_data.extend_from_slice(&[249, 209, 47, 60, 18, 3, 81, 219]); // Instruction discriminator
-    instruction_data.extend_from_slice(&account_slot.to_le_bytes());
-    instruction_data.extend_from_slice(&mt_root);
-    instruction_data.extend_from_slice(&ws_root);
-    
-    // Create and send transaction
-    let instruction = Instruction::new_with_bytes(
-        l1_program_id,
-        &instruction_data,
-        vec![
-            AccountMeta::new(slots_account, false),
-            AccountMeta::new_readonly(solana_program::system_program::id(), false),
-            AccountMeta::new(slot_roots_account, false),
-            AccountMeta::new(wallet.pubkey(), true),
-        ],
-    );
-    
-    let recent_blockhash = l1_client.get_latest_blockhash()?;
-    let transaction = Transaction::new_signed_with_payer(
-        &[instruction],
-        Some(&wallet.pubkey()),
-        &[&wallet],
-        recent_blockhash,
-    );
-    
-    let signature = l1_client.send_and_confirm_transaction(&transaction)?;
-    println!("Transaction confirmed: {}", signature);
-    
-    Ok(())
-}
+    // Reports an invalid config as a failed check rather than an error
+    if let Some(Command::Doctor) = cli.command {
+        let checks = doctor::run(config).await;
+        return Ok(if doctor::print_checklist(&checks) {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        });
+    }
+    config.validate()?;
 
-/// Retry mechanism for async operations
-async fn with_retry<F, Fut, T>(f: F) -> Result<T>
-where
-    F: Fn() -> Fut,
-    Fut: std::future::Future<Output = Result<T>>,
-{
-    let mut retries = 3;
-    let mut last_error = None;
-    
-    while retries > 0 {
-        match f().await {
-            Ok(result) => return Ok(result),
-            Err(e) => {
-                last_error = Some(e);
-                retries -= 1;
-                tokio::time::sleep(Duration::from_secs(1)).await;
+    if let Some(slot) = cli.print_pda {
+        println!("slot: {}", slot);
+        for target in target::L1Target::all(&config)? {
+            let (pda, bump) = program::derive_slot_roots_pda(&target.pda_seeds, config.program_slot(slot)?, &target.program_id);
+            println!("target: {}\npda: {}\nbump: {}", target.name, pda, bump);
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Encode { slot, mt_root, ws_root }) = &cli.command {
+        let roots = L2Roots {
+            slot: *slot,
+            mt_root: parse_root("--mt-root", mt_root, config.account.root_len)?,
+            ws_root: parse_root("--ws-root", ws_root, 32)?.try_into().expect("root of world state length"),
+        };
+        println!("slot: {}", slot);
+        let roots = roots.for_program(&config)?;
+        for target in target::L1Target::all(&config)? {
+            println!("target: {}", target.name);
+            for instruction in &target.instructions {
+                println!("data: {}", hex::encode(bridge::instruction_data(&config.settings, instruction, &roots)));
             }
+            let (pda, bump) = target.slot_roots_pda(roots.slot);
+            println!("pda: {}\nbump: {}", pda, bump);
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Submissions { slot, program_id }) = &cli.command {
+        let path = config
+            .settings
+            .submission_log
+            .as_deref()
+            .ok_or_else(|| eyre::eyre!("settings.submission_log is not set"))?;
+        let entries = SubmissionLog::entries(path)?.into_iter().filter(|entry| {
+            slot.is_none_or(|slot| entry.slot == slot)
+                && program_id.as_ref().is_none_or(|program_id| &entry.program_id == program_id)
+        });
+        for entry in entries {
+            println!(
+                "{} {} {} mt_root={} ws_root={} at={}",
+                entry.slot, entry.program_id, entry.signature, entry.mt_root, entry.ws_root, entry.timestamp
+            );
         }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let service = BridgeService::new(config).await?;
+
+    if let Some(Command::Backfill { from, to }) = cli.command {
+        return run_backfill(service.context(), from, to).await;
     }
-    
-    Err(last_error.unwrap())
+
+    service.serve_metrics();
+
+    // Single-shot mode: exit 0 when submitted or nothing to do, 1 on error
+    if cli.once {
+        return Ok(if service.run_once().await {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        });
+    }
+
+    service.warm_up().await;
+    let source = ConfigSource {
+        path: cli.config.clone(),
+        overrides: Box::new(move |config| cli.apply(config)),
+    };
+    service.with_reload(source).run_loop().await?;
+    Ok(ExitCode::SUCCESS)
 }
 
-/// Entry point of the bridge service
-#[tokio::main]
-async fn main() -> Result<()> {
-    let config = load_config()?;
-    let mut interval = interval(Duration::from_secs(config.settings.check_interval_secs));
-    
-    loop {
-        interval.tick().await;
-        if let Err(e) = check_and_submit(&config).await {
-            eprintln!("Error: {}", e);
+/// Submits every slot in `from..=to`, printing one line per slot and target
+///
+/// Exits with 1 if any slot failed to read or submit.
+async fn run_backfill(ctx: &BridgeContext, from: u64, to: u64) -> Result<ExitCode> {
+    if from > to {
+        return Err(eyre::eyre!("backfill: --from {} is after --to {}", from, to));
+    }
+
+    let mut failed = 0;
+    for slot in from..=to {
+        match bridge::backfill_slot(ctx, slot).await {
+            BackfillSlot::Unavailable => println!("{}: skipped: historical L2 state unavailable", slot),
+            BackfillSlot::ReadFailed(e) => {
+                failed += 1;
+                println!("{}: failed: {}", slot, e);
+            }
+            BackfillSlot::Offered(targets) => {
+                for TargetResult { target, result } in targets {
+                    match result {
+                        Ok(CycleOutcome::Submitted { signature, .. }) => {
+                            println!("{} {}: submitted {}", slot, target, signature)
+                        }
+                        Ok(CycleOutcome::Proposed { proposal, .. }) => {
+                            println!("{} {}: proposed {}", slot, target, proposal)
+                        }
+                        Ok(CycleOutcome::Skipped { reason, .. }) => println!("{} {}: skipped: {}", slot, target, reason),
+                        Ok(CycleOutcome::DryRun { .. }) => println!("{} {}: would submit", slot, target),
+                        Ok(CycleOutcome::Observed { l1_lag_slots: Some(0), .. }) => {
+                            println!("{} {}: on L1", slot, target)
+                        }
+                        Ok(CycleOutcome::Observed { .. }) => println!("{} {}: missing on L1", slot, target),
+                        Err(e) => {
+                            failed += 1;
+                            println!("{} {}: failed: {}", slot, target, e);
+                        }
+                    }
+                }
+            }
         }
     }
-} 
\ No newline at end of file
+
+    info!(from, to, failed, "backfill finished");
+    Ok(if failed == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
@@ -0,0 +1,219 @@
+//! Prometheus metrics exported over HTTP.
+
+use crate::health;
+use crate::trigger::{self, Trigger};
+use axum::routing::{get, post};
+use axum::Router;
+use eyre::Result;
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, Gauge, GaugeVec, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tracing::{error, info};
+
+static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+/// Number of proposals confirmed on L1
+pub static PROPOSALS_SUBMITTED: Lazy<IntCounter> = Lazy::new(|| {
+    register(IntCounter::new("proposals_submitted_total", "Proposals confirmed on L1").unwrap())
+});
+
+/// Number of failed submission cycles
+pub static SUBMISSION_FAILURES: Lazy<IntCounter> = Lazy::new(|| {
+    register(IntCounter::new("submission_failures_total", "Failed submission cycles").unwrap())
+});
+
+/// Number of failed RPC calls, by endpoint
+pub static RPC_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register(
+        IntCounterVec::new(Opts::new("rpc_errors_total", "Failed RPC calls"), &["endpoint"]).unwrap(),
+    )
+});
+
+/// RPC calls, by endpoint and result
+pub static RPC_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register(
+        IntCounterVec::new(Opts::new("rpc_requests_total", "RPC calls by result"), &["endpoint", "result"]).unwrap(),
+    )
+});
+
+/// Share of RPC calls that failed over the current window, by endpoint
+pub static RPC_ERROR_RATE: Lazy<GaugeVec> = Lazy::new(|| {
+    register(
+        GaugeVec::new(
+            Opts::new("rpc_error_rate", "Share of RPC calls that failed over the current window"),
+            &["endpoint"],
+        )
+        .unwrap(),
+    )
+});
+
+/// Cycle outcomes per L1 target, by result
+pub static TARGET_CYCLES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new("target_cycles_total", "Cycle outcomes per L1 target"),
+            &["target", "result"],
+        )
+        .unwrap(),
+    )
+});
+
+/// Last slot whose roots were submitted to L1
+pub static LAST_SUBMITTED_SLOT: Lazy<IntGauge> = Lazy::new(|| {
+    register(IntGauge::new("last_submitted_slot", "Last slot submitted to L1").unwrap())
+});
+
+/// Lamports paid in fees for confirmed submissions
+pub static LAMPORTS_SPENT: Lazy<IntCounter> = Lazy::new(|| {
+    register(IntCounter::new("lamports_spent_total", "Lamports paid in transaction fees").unwrap())
+});
+
+/// Part of `LAMPORTS_SPENT` paid as priority fees
+pub static PRIORITY_LAMPORTS_SPENT: Lazy<IntCounter> = Lazy::new(|| {
+    register(IntCounter::new("priority_lamports_spent_total", "Lamports paid in priority fees").unwrap())
+});
+
+/// Hours the fee payer balance lasts at the recent spend rate; +Inf while nothing is spent
+pub static ESTIMATED_RUNWAY_HOURS: Lazy<Gauge> = Lazy::new(|| {
+    register(Gauge::new("estimated_runway_hours", "Hours until the fee payer balance runs out at the recent spend rate").unwrap())
+});
+
+/// Histogram buckets in seconds, from a fast RPC read up to a slow finalized confirmation
+const DURATION_BUCKETS: [f64; 12] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0];
+
+/// Time spent per stage: `fetch` of the L2 roots, `build` of a transaction, `send`,
+/// `confirm` waiting for the commitment, and the whole `cycle`
+pub static SUBMISSION_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new("submission_duration_seconds", "Time spent per submission stage")
+                .buckets(DURATION_BUCKETS.to_vec()),
+            &["stage"],
+        )
+        .unwrap(),
+    )
+});
+
+/// Records how long `stage` took
+pub fn observe_duration(stage: &str, duration: Duration) {
+    SUBMISSION_DURATION
+        .with_label_values(&[stage])
+        .observe(duration.as_secs_f64());
+}
+
+/// Current L2 slot minus the highest slot recorded on L1, per L1 target
+pub static L1_LAG_SLOTS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new("l1_lag_slots", "Current L2 slot minus the highest slot recorded on L1"),
+            &["target"],
+        )
+        .unwrap(),
+    )
+});
+
+/// Slots L1 trails L2 by in observer mode, per L1 target
+pub static OBSERVER_LAG_SLOTS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new("observer_l1_lag_slots", "Slots L1 trails L2 by in observer mode"),
+            &["target"],
+        )
+        .unwrap(),
+    )
+});
+
+/// Roots read from L2 while L1 was unreachable and not yet submitted, per L1 target
+pub static PENDING_ROOTS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new("pending_roots", "Roots queued while L1 was unreachable, awaiting submission"),
+            &["target"],
+        )
+        .unwrap(),
+    )
+});
+
+/// Cycles that found a leaf chunk account closed or uninitialized
+pub static LEAF_CHUNK_MISSING: Lazy<IntCounter> = Lazy::new(|| {
+    register(IntCounter::new("leaf_chunk_missing_total", "Cycles that found a leaf chunk account missing").unwrap())
+});
+
+/// Local time minus the time of the latest L1 block
+pub static CLOCK_SKEW_SECONDS: Lazy<IntGauge> = Lazy::new(|| {
+    register(IntGauge::new("clock_skew_seconds", "Local time minus the time of the latest L1 block").unwrap())
+});
+
+/// Whether the circuit breaker is open (1) or closed (0)
+pub static CIRCUIT_OPEN: Lazy<IntGauge> = Lazy::new(|| {
+    register(IntGauge::new("circuit_breaker_open", "Circuit breaker state, 1 when open").unwrap())
+});
+
+/// Registers a collector with the bridge registry and hands it back
+fn register<T: prometheus::core::Collector + Clone + 'static>(collector: T) -> T {
+    REGISTRY
+        .register(Box::new(collector.clone()))
+        .expect("metric registered once");
+    collector
+}
+
+/// Returns an endpoint label without path or query, which may carry API keys
+pub fn endpoint_label(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => match (parsed.host_str(), parsed.port()) {
+            (Some(host), Some(port)) => format!("{}://{}:{}", parsed.scheme(), host, port),
+            (Some(host), None) => format!("{}://{}", parsed.scheme(), host),
+            _ => "unknown".to_string(),
+        },
+        Err(_) => "unknown".to_string(),
+    }
+}
+
+/// Renders all metrics in the Prometheus text format
+fn render() -> String {
+    // Touch every metric so they are exported before their first update
+    Lazy::force(&PROPOSALS_SUBMITTED);
+    Lazy::force(&SUBMISSION_FAILURES);
+    Lazy::force(&RPC_ERRORS);
+    Lazy::force(&RPC_REQUESTS);
+    Lazy::force(&RPC_ERROR_RATE);
+    Lazy::force(&TARGET_CYCLES);
+    Lazy::force(&LAST_SUBMITTED_SLOT);
+    Lazy::force(&LAMPORTS_SPENT);
+    Lazy::force(&PRIORITY_LAMPORTS_SPENT);
+    Lazy::force(&CIRCUIT_OPEN);
+    Lazy::force(&LEAF_CHUNK_MISSING);
+    Lazy::force(&CLOCK_SKEW_SECONDS);
+    Lazy::force(&SUBMISSION_DURATION);
+    Lazy::force(&OBSERVER_LAG_SLOTS);
+    Lazy::force(&L1_LAG_SLOTS);
+    Lazy::force(&PENDING_ROOTS);
+    Lazy::force(&ESTIMATED_RUNWAY_HOURS);
+
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&REGISTRY.gather(), &mut buffer) {
+        error!(error = %e, "failed to encode metrics");
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+/// Serves `/metrics` and `/health` on the given port until the process exits
+///
+/// `/health` reports unhealthy once no cycle has succeeded for the age set with
+/// `HealthState::set_max_age`. With a `trigger`, `POST /trigger` runs a cycle on request.
+pub async fn serve(port: u16, trigger: Option<Trigger>) -> Result<()> {
+    let mut app = Router::new()
+        .route("/metrics", get(|| async { render() }))
+        .route("/health", get(health::handler));
+    if let Some(trigger) = trigger {
+        app = app.route("/trigger", post(trigger::handler).with_state(trigger));
+    }
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+
+    info!(%addr, "metrics server listening");
+    axum::Server::bind(&addr).serve(app.into_make_service()).await?;
+    Ok(())
+}
@@ -0,0 +1,380 @@
+//! In-memory L1 and L2 client for tests.
+
+use crate::balance::LAMPORTS_PER_SIGNATURE;
+use crate::client::{L1Submitter, L2Reader, RpcNode};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::client_error::{ClientErrorKind, Result as ClientResult};
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig};
+use solana_client::rpc_response::{
+    Response, RpcPrioritizationFee, RpcResponseContext, RpcResult, RpcSimulateTransactionResult,
+};
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::clock::{UnixTimestamp, MAX_PROCESSING_AGE};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::{TransactionError, VersionedTransaction};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Shared state behind every clone of a `MockClient`
+struct MockState {
+    slot: u64,
+    /// Slot reported as finalized; the context slot when unset
+    finalized_slot: Option<u64>,
+    accounts: HashMap<Pubkey, Account>,
+    balance: u64,
+    sent: Vec<VersionedTransaction>,
+    /// Config of every send attempt, failed ones included
+    send_configs: Vec<RpcSendTransactionConfig>,
+    send_failures: Vec<TransactionError>,
+    simulation_failures: Vec<TransactionError>,
+    /// Sent transactions never land and every blockhash reads as expired
+    dropping: bool,
+    /// Number of upcoming blockhashes served `STALE_BLOCKHASH_AGE` blocks old
+    stale_blockhashes: usize,
+    /// Seconds block times run ahead of the local clock
+    block_time_offset: UnixTimestamp,
+    /// Number of upcoming health checks that fail
+    unhealthy_checks: usize,
+    health_checks: usize,
+    /// Balance lookups fail with a connection error, as if L1 were down
+    l1_unreachable: bool,
+    /// Sent transactions land but never get past `confirmed`
+    unfinalized: bool,
+    /// Number of upcoming signature status lookups that fail with a connection reset
+    failing_status_lookups: usize,
+    /// Encoding requested by every account fetch with a config
+    account_encodings: Vec<Option<UiAccountEncoding>>,
+}
+
+/// Blocks a stale blockhash has aged by when it is served
+pub const STALE_BLOCKHASH_AGE: u64 = 100;
+
+/// A client serving accounts from memory and recording sent transactions
+///
+/// Clones share state, so a test can keep one clone to inspect what the bridge sent.
+#[derive(Clone)]
+pub struct MockClient {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockClient {
+    /// Creates a client reporting `slot` as the current context slot
+    pub fn new(slot: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockState {
+                slot,
+                finalized_slot: None,
+                accounts: HashMap::new(),
+                balance: 10_000_000_000,
+                sent: Vec::new(),
+                send_configs: Vec::new(),
+                send_failures: Vec::new(),
+                simulation_failures: Vec::new(),
+                dropping: false,
+                stale_blockhashes: 0,
+                unhealthy_checks: 0,
+                health_checks: 0,
+                block_time_offset: 0,
+                l1_unreachable: false,
+                unfinalized: false,
+                failing_status_lookups: 0,
+                account_encodings: Vec::new(),
+            })),
+        }
+    }
+
+    /// Stores `data` as the account at `pubkey`
+    pub fn set_account(&self, pubkey: Pubkey, data: Vec<u8>) {
+        let account = Account {
+            lamports: 1_000_000,
+            data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.insert_account(pubkey, account);
+    }
+
+    /// Stores a fully specified account at `pubkey`
+    pub fn insert_account(&self, pubkey: Pubkey, account: Account) {
+        self.state.lock().unwrap().accounts.insert(pubkey, account);
+    }
+
+    /// Removes the account at `pubkey`, as if closed
+    pub fn remove_account(&self, pubkey: &Pubkey) {
+        self.state.lock().unwrap().accounts.remove(pubkey);
+    }
+
+    /// Makes sent transactions never land, as if dropped, with their blockhash expired
+    pub fn drop_transactions(&self) {
+        self.state.lock().unwrap().dropping = true;
+    }
+
+    /// Fails the next `count` health checks, as a node still catching up would
+    pub fn fail_health_checks(&self, count: usize) {
+        self.state.lock().unwrap().unhealthy_checks = count;
+    }
+
+    /// Number of health checks made so far
+    pub fn health_checks(&self) -> usize {
+        self.state.lock().unwrap().health_checks
+    }
+
+    /// Makes balance lookups fail with a connection error, as if L1 were down
+    pub fn set_l1_unreachable(&self, unreachable: bool) {
+        self.state.lock().unwrap().l1_unreachable = unreachable;
+    }
+
+    /// Fails the next `count` signature status lookups, as a flaky RPC node would
+    pub fn fail_status_lookups(&self, count: usize) {
+        self.state.lock().unwrap().failing_status_lookups = count;
+    }
+
+    /// Makes sent transactions stop at `confirmed`, as if never finalized
+    pub fn hold_unfinalized(&self, unfinalized: bool) {
+        self.state.lock().unwrap().unfinalized = unfinalized;
+    }
+
+    /// Serves the next `count` blockhashes `STALE_BLOCKHASH_AGE` blocks old, as a lagging node would
+    pub fn serve_stale_blockhashes(&self, count: usize) {
+        self.state.lock().unwrap().stale_blockhashes = count;
+    }
+
+    /// Makes block times run `offset` seconds ahead of the local clock
+    pub fn set_block_time_offset(&self, offset: UnixTimestamp) {
+        self.state.lock().unwrap().block_time_offset = offset;
+    }
+
+    /// Changes the current context slot
+    pub fn set_slot(&self, slot: u64) {
+        self.state.lock().unwrap().slot = slot;
+    }
+
+    /// Changes the slot reported as finalized
+    pub fn set_finalized_slot(&self, slot: u64) {
+        self.state.lock().unwrap().finalized_slot = Some(slot);
+    }
+
+    /// Makes the next sends fail with `errors`, in order
+    pub fn fail_sends(&self, errors: Vec<TransactionError>) {
+        self.state.lock().unwrap().send_failures = errors;
+    }
+
+    /// Makes the next simulations fail with `errors`, in order
+    pub fn fail_simulations(&self, errors: Vec<TransactionError>) {
+        self.state.lock().unwrap().simulation_failures = errors;
+    }
+
+    /// Returns the config of every send attempt so far, oldest first
+    pub fn send_configs(&self) -> Vec<RpcSendTransactionConfig> {
+        self.state.lock().unwrap().send_configs.clone()
+    }
+
+    /// Returns the encoding requested by every account fetch with a config so far, oldest first
+    pub fn account_encodings(&self) -> Vec<Option<UiAccountEncoding>> {
+        self.state.lock().unwrap().account_encodings.clone()
+    }
+
+    /// Returns every transaction sent so far, oldest first
+    pub fn sent_transactions(&self) -> Vec<VersionedTransaction> {
+        self.state.lock().unwrap().sent.clone()
+    }
+
+    fn account(&self, pubkey: &Pubkey) -> RpcResult<Option<Account>> {
+        let state = self.state.lock().unwrap();
+        Ok(Response {
+            context: RpcResponseContext {
+                slot: state.slot,
+                api_version: None,
+            },
+            value: state.accounts.get(pubkey).cloned(),
+        })
+    }
+}
+
+impl RpcNode for MockClient {
+    fn get_genesis_hash(&self) -> ClientResult<Hash> {
+        Ok(Hash::default())
+    }
+
+    fn get_health(&self) -> ClientResult<()> {
+        let mut state = self.state.lock().unwrap();
+        state.health_checks += 1;
+        if state.unhealthy_checks > 0 {
+            state.unhealthy_checks -= 1;
+            return Err(ClientErrorKind::Custom("node is behind".to_string()).into());
+        }
+        Ok(())
+    }
+
+    fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<u64> {
+        let state = self.state.lock().unwrap();
+        if commitment.is_finalized() {
+            return Ok(state.finalized_slot.unwrap_or(state.slot));
+        }
+        Ok(state.slot)
+    }
+}
+
+impl L2Reader for MockClient {
+    fn get_account_with_config(&self, pubkey: &Pubkey, config: RpcAccountInfoConfig) -> RpcResult<Option<Account>> {
+        let mut response = self.get_multiple_accounts_with_config(std::slice::from_ref(pubkey), config)?;
+        Ok(Response {
+            context: response.context,
+            value: response.value.remove(0),
+        })
+    }
+
+    fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: RpcAccountInfoConfig,
+    ) -> RpcResult<Vec<Option<Account>>> {
+        let mut state = self.state.lock().unwrap();
+        state.account_encodings.push(config.encoding);
+        // Encoded and decoded as the RPC client would, so a compressed encoding round-trips
+        let encoding = config.encoding.unwrap_or(UiAccountEncoding::Base64);
+        let value = pubkeys
+            .iter()
+            .map(|pubkey| {
+                let account = state.accounts.get(pubkey)?;
+                UiAccount::encode(pubkey, account, encoding, None, config.data_slice).decode()
+            })
+            .collect();
+        Ok(Response {
+            context: RpcResponseContext {
+                slot: state.slot,
+                api_version: None,
+            },
+            value,
+        })
+    }
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        let state = self.state.lock().unwrap();
+        let filters = config.filters.unwrap_or_default();
+        Ok(state
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.owner == *program_id)
+            .filter(|(_, account)| {
+                let shared = AccountSharedData::from((*account).clone());
+                filters.iter().all(|filter| filter.allows(&shared))
+            })
+            .map(|(pubkey, account)| (*pubkey, account.clone()))
+            .collect())
+    }
+}
+
+impl L1Submitter for MockClient {
+    fn get_account_with_commitment(&self, pubkey: &Pubkey, _commitment: CommitmentConfig) -> RpcResult<Option<Account>> {
+        self.account(pubkey)
+    }
+
+    fn get_balance(&self, _pubkey: &Pubkey) -> ClientResult<u64> {
+        let state = self.state.lock().unwrap();
+        if state.l1_unreachable {
+            let refused = std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "connection refused");
+            return Err(ClientErrorKind::Io(refused).into());
+        }
+        Ok(state.balance)
+    }
+
+    fn get_latest_blockhash_with_commitment(&self, _commitment: CommitmentConfig) -> ClientResult<(Hash, u64)> {
+        let mut state = self.state.lock().unwrap();
+        let age = if state.stale_blockhashes > 0 {
+            state.stale_blockhashes -= 1;
+            STALE_BLOCKHASH_AGE
+        } else {
+            0
+        };
+        // The block height follows the slot
+        Ok((Hash::new_unique(), state.slot + MAX_PROCESSING_AGE as u64 - age))
+    }
+
+    fn get_block_height(&self, _commitment: CommitmentConfig) -> ClientResult<u64> {
+        Ok(self.state.lock().unwrap().slot)
+    }
+
+    fn get_block_time(&self, _slot: u64) -> ClientResult<UnixTimestamp> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as UnixTimestamp;
+        Ok(now + self.state.lock().unwrap().block_time_offset)
+    }
+
+    fn get_recent_prioritization_fees(&self, _accounts: &[Pubkey]) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        Ok(Vec::new())
+    }
+
+    fn simulate_transaction(&self, _transaction: &VersionedTransaction) -> RpcResult<RpcSimulateTransactionResult> {
+        let mut state = self.state.lock().unwrap();
+        let err = (!state.simulation_failures.is_empty()).then(|| state.simulation_failures.remove(0));
+        Ok(Response {
+            context: RpcResponseContext {
+                slot: state.slot,
+                api_version: None,
+            },
+            value: RpcSimulateTransactionResult {
+                logs: Some(vec!["Program log: simulated".to_string()]),
+                err,
+                accounts: None,
+                units_consumed: Some(0),
+                return_data: None,
+                inner_instructions: None,
+            },
+        })
+    }
+
+    fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        let mut state = self.state.lock().unwrap();
+        state.send_configs.push(config);
+        if !state.send_failures.is_empty() {
+            return Err(state.send_failures.remove(0).into());
+        }
+        state.sent.push(transaction.clone());
+        Ok(transaction.signatures[0])
+    }
+
+    fn get_signature_status(&self, _signature: &Signature) -> ClientResult<Option<TransactionStatus>> {
+        let mut state = self.state.lock().unwrap();
+        if state.failing_status_lookups > 0 {
+            state.failing_status_lookups -= 1;
+            let reset = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset");
+            return Err(ClientErrorKind::Io(reset).into());
+        }
+        Ok((!state.dropping).then(|| TransactionStatus {
+            slot: state.slot,
+            // Only rooted statuses have no confirmation count
+            confirmations: state.unfinalized.then_some(1),
+            status: Ok(()),
+            err: None,
+            confirmation_status: Some(if state.unfinalized {
+                TransactionConfirmationStatus::Confirmed
+            } else {
+                TransactionConfirmationStatus::Finalized
+            }),
+        }))
+    }
+
+    fn is_blockhash_valid(&self, _blockhash: &Hash, _commitment: CommitmentConfig) -> ClientResult<bool> {
+        Ok(!self.state.lock().unwrap().dropping)
+    }
+
+    fn get_transaction_fee(&self, signature: &Signature, _commitment: CommitmentConfig) -> ClientResult<Option<u64>> {
+        let state = self.state.lock().unwrap();
+        let transaction = state.sent.iter().find(|transaction| transaction.signatures[0] == *signature);
+        Ok(transaction.map(|transaction| transaction.signatures.len() as u64 * LAMPORTS_PER_SIGNATURE))
+    }
+}
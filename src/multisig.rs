@@ -0,0 +1,227 @@
+//! Proposing the bridge instruction to a Squads v4 multisig instead of signing it directly.
+
+use crate::config::parse_pubkey;
+use crate::error::BridgeError;
+use crate::program::anchor_sighash;
+use eyre::Result;
+use serde::Deserialize;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_program;
+
+/// Squads v4 program, used when `multisig.program_id` is unset
+const SQUADS_PROGRAM_ID: &str = "SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf";
+
+/// Byte offset of the `u64` transaction index in a multisig account: after the
+/// discriminator, create key, config authority, threshold and time lock
+const TRANSACTION_INDEX_OFFSET: usize = 8 + 32 + 32 + 2 + 4;
+
+/// Multisig settings as read from the optional `[multisig]` config section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MultisigConfig {
+    /// Squads multisig account the bridge instruction is proposed to; direct signing when unset
+    pub address: Option<String>,
+    /// Squads program; Squads v4 when unset
+    pub program_id: Option<String>,
+    /// Index of the vault acting as the program's authority
+    pub vault_index: u8,
+    /// Memo attached to every vault transaction
+    pub memo: Option<String>,
+}
+
+impl MultisigConfig {
+    /// Checks the configured addresses parse
+    pub fn validate(&self) -> Result<()> {
+        if let Some(address) = &self.address {
+            parse_pubkey("multisig.address", address)?;
+        }
+        if let Some(program_id) = &self.program_id {
+            parse_pubkey("multisig.program_id", program_id)?;
+        }
+        Ok(())
+    }
+}
+
+/// A parsed Squads multisig and the vault it proposes transactions for
+#[derive(Debug, Clone)]
+pub struct Multisig {
+    pub program_id: Pubkey,
+    pub address: Pubkey,
+    pub vault_index: u8,
+    /// Vault PDA signing the bridge instruction once the proposal executes
+    pub vault: Pubkey,
+    pub memo: Option<String>,
+}
+
+impl Multisig {
+    /// Parses the multisig when `multisig.address` is set
+    pub fn from_config(config: &MultisigConfig) -> Result<Option<Self>> {
+        let Some(address) = &config.address else {
+            return Ok(None);
+        };
+        let address = parse_pubkey("multisig.address", address)?;
+        let program_id = parse_pubkey("multisig.program_id", config.program_id.as_deref().unwrap_or(SQUADS_PROGRAM_ID))?;
+        let (vault, _) = Pubkey::find_program_address(
+            &[b"multisig", address.as_ref(), b"vault", &[config.vault_index]],
+            &program_id,
+        );
+        Ok(Some(Self {
+            program_id,
+            address,
+            vault_index: config.vault_index,
+            vault,
+            memo: config.memo.clone(),
+        }))
+    }
+
+    /// Vault transaction account holding the proposed message at `index`
+    pub fn transaction_pda(&self, index: u64) -> Pubkey {
+        let seeds: &[&[u8]] = &[b"multisig", self.address.as_ref(), b"transaction", &index.to_le_bytes()];
+        Pubkey::find_program_address(seeds, &self.program_id).0
+    }
+
+    /// Proposal account approvers vote on for the transaction at `index`
+    pub fn proposal_pda(&self, index: u64) -> Pubkey {
+        let seeds: &[&[u8]] = &[b"multisig", self.address.as_ref(), b"transaction", &index.to_le_bytes(), b"proposal"];
+        Pubkey::find_program_address(seeds, &self.program_id).0
+    }
+
+    /// Reads the index of the last transaction created on the multisig from its account data
+    pub fn transaction_index(&self, data: &[u8]) -> Result<u64> {
+        data.get(TRANSACTION_INDEX_OFFSET..TRANSACTION_INDEX_OFFSET + 8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("8 bytes")))
+            .ok_or_else(|| {
+                BridgeError::AccountTooShort {
+                    len: data.len(),
+                    needed: TRANSACTION_INDEX_OFFSET + 8,
+                }
+                .into()
+            })
+    }
+
+    /// Instructions creating a vault transaction for `instructions` at `index` and opening its proposal
+    ///
+    /// `creator` must be a multisig member allowed to initiate; `rent_payer` funds both accounts.
+    pub fn propose_instructions(&self, instructions: &[Instruction], index: u64, creator: &Pubkey, rent_payer: &Pubkey) -> Vec<Instruction> {
+        let transaction = self.transaction_pda(index);
+        let proposal = self.proposal_pda(index);
+
+        let mut create_data = anchor_sighash("vault_transaction_create").to_vec();
+        create_data.push(self.vault_index);
+        // No ephemeral signers
+        create_data.push(0);
+        let message = self.transaction_message(instructions);
+        create_data.extend_from_slice(&(message.len() as u32).to_le_bytes());
+        create_data.extend_from_slice(&message);
+        match &self.memo {
+            Some(memo) => {
+                create_data.push(1);
+                create_data.extend_from_slice(&(memo.len() as u32).to_le_bytes());
+                create_data.extend_from_slice(memo.as_bytes());
+            }
+            None => create_data.push(0),
+        }
+        let create = Instruction::new_with_bytes(
+            self.program_id,
+            &create_data,
+            vec![
+                AccountMeta::new(self.address, false),
+                AccountMeta::new(transaction, false),
+                AccountMeta::new_readonly(*creator, true),
+                AccountMeta::new(*rent_payer, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        );
+
+        let mut proposal_data = anchor_sighash("proposal_create").to_vec();
+        proposal_data.extend_from_slice(&index.to_le_bytes());
+        // Not a draft, so approvers can vote right away
+        proposal_data.push(0);
+        let propose = Instruction::new_with_bytes(
+            self.program_id,
+            &proposal_data,
+            vec![
+                AccountMeta::new_readonly(self.address, false),
+                AccountMeta::new(proposal, false),
+                AccountMeta::new_readonly(*creator, true),
+                AccountMeta::new(*rent_payer, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        );
+        vec![create, propose]
+    }
+
+    /// Serializes `instructions` as the Squads transaction message executed by the vault
+    fn transaction_message(&self, instructions: &[Instruction]) -> Vec<u8> {
+        let message = Message::new(instructions, Some(&self.vault));
+        let header = message.header;
+        let signers = header.num_required_signatures;
+        let non_signers = message.account_keys.len() as u8 - signers;
+
+        let mut data = vec![
+            signers,
+            signers - header.num_readonly_signed_accounts,
+            non_signers - header.num_readonly_unsigned_accounts,
+            message.account_keys.len() as u8,
+        ];
+        for key in &message.account_keys {
+            data.extend_from_slice(key.as_ref());
+        }
+        data.push(message.instructions.len() as u8);
+        for compiled in &message.instructions {
+            data.push(compiled.program_id_index);
+            data.push(compiled.accounts.len() as u8);
+            data.extend_from_slice(&compiled.accounts);
+            data.extend_from_slice(&(compiled.data.len() as u16).to_le_bytes());
+            data.extend_from_slice(&compiled.data);
+        }
+        // No address table lookups
+        data.push(0);
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_vault_transaction_proposal() {
+        let config = MultisigConfig {
+            address: Some(Pubkey::new_unique().to_string()),
+            ..MultisigConfig::default()
+        };
+        let multisig = Multisig::from_config(&config).unwrap().unwrap();
+        let (creator, payer, program) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let account = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            program,
+            &[7, 8, 9],
+            vec![AccountMeta::new(account, false), AccountMeta::new(multisig.vault, true)],
+        );
+
+        let instructions = multisig.propose_instructions(&[instruction], 5, &creator, &payer);
+        assert_eq!(instructions[0].accounts[1].pubkey, multisig.transaction_pda(5));
+        assert_eq!(instructions[1].accounts[1].pubkey, multisig.proposal_pda(5));
+        assert_eq!(instructions[1].data[8..], [5, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        // Vault index, no ephemeral signers, then the length-prefixed message
+        let data = &instructions[0].data;
+        assert_eq!(data[8..10], [0, 0]);
+        let len = u32::from_le_bytes(data[10..14].try_into().unwrap()) as usize;
+        let message = &data[14..14 + len];
+        // The vault is the only, writable, signer ahead of the two other accounts
+        assert_eq!(message[..4], [1, 1, 1, 3]);
+        assert_eq!(message[4..36], multisig.vault.to_bytes());
+        assert_eq!(message[message.len() - 6..], [3, 0, 7, 8, 9, 0]);
+        // No memo
+        assert_eq!(data[14 + len..], [0]);
+
+        let mut account_data = vec![0; TRANSACTION_INDEX_OFFSET + 8];
+        account_data[TRANSACTION_INDEX_OFFSET..].copy_from_slice(&4u64.to_le_bytes());
+        assert_eq!(multisig.transaction_index(&account_data).unwrap(), 4);
+        assert!(multisig.transaction_index(&account_data[..20]).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+//! Durable nonce accounts used in place of a recent blockhash.
+
+use crate::client::L1Submitter;
+use eyre::Result;
+use serde::Deserialize;
+use solana_client::nonce_utils;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction;
+
+/// Durable nonce settings as read from the optional `[nonce]` config section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NonceConfig {
+    /// Nonce account whose stored hash replaces the recent blockhash; disabled when unset
+    pub nonce_account: Option<String>,
+    /// Authority of the nonce account; defaults to the wallet
+    pub nonce_authority: Option<String>,
+}
+
+/// A parsed durable nonce account
+#[derive(Debug, Clone)]
+pub struct DurableNonce {
+    pub account: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl DurableNonce {
+    /// Instruction that must come first in every transaction using the nonce
+    pub fn advance_instruction(&self) -> Instruction {
+        system_instruction::advance_nonce_account(&self.account, &self.authority)
+    }
+
+    /// Fetches the hash currently stored in the nonce account
+    pub fn fetch_blockhash(&self, client: &dyn L1Submitter, commitment: CommitmentConfig) -> Result<Hash> {
+        let account = client
+            .get_account_with_commitment(&self.account, commitment)?
+            .value
+            .ok_or_else(|| eyre::eyre!("nonce account {} not found", self.account))?;
+        let data = nonce_utils::data_from_account(&account)
+            .map_err(|e| eyre::eyre!("nonce account {}: {}", self.account, e))?;
+
+        if data.authority != self.authority {
+            return Err(eyre::eyre!(
+                "nonce account {} has authority {}, expected {}",
+                self.account,
+                data.authority,
+                self.authority
+            ));
+        }
+        Ok(data.blockhash())
+    }
+}
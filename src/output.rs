@@ -0,0 +1,90 @@
+//! Per-cycle result records for machine consumption.
+
+use crate::bridge::{CycleOutcome, CycleReport};
+use eyre::Result;
+use serde::Serialize;
+use std::io::Write;
+use tokio::time::Duration;
+use tracing::warn;
+
+/// One line of JSON output, describing a cycle's result for one L1 target
+#[derive(Debug, Serialize)]
+struct CycleRecord<'a> {
+    target: Option<&'a str>,
+    slot: Option<u64>,
+    mt_root: Option<String>,
+    ws_root: Option<String>,
+    signature: Option<String>,
+    status: &'static str,
+    reason: Option<&'static str>,
+    l1_lag_slots: Option<u64>,
+    error: Option<String>,
+    duration_ms: u64,
+}
+
+/// Writes one JSON line per target to stdout, or a single failure line when L2 could not be read
+pub fn write_json(result: &Result<CycleReport>, duration: Duration) {
+    let mut stdout = std::io::stdout().lock();
+    for record in records(result, duration) {
+        let written = serde_json::to_string(&record)
+            .map_err(eyre::Report::from)
+            .and_then(|line| Ok(writeln!(stdout, "{}", line)?));
+        if let Err(e) = written {
+            warn!(error = %e, "failed to write cycle result");
+        }
+    }
+}
+
+/// The records `write_json` writes, as a single JSON array
+pub fn to_json(result: &Result<CycleReport>, duration: Duration) -> serde_json::Value {
+    serde_json::to_value(records(result, duration)).unwrap_or_default()
+}
+
+/// One record per target, or a single failure record when L2 could not be read
+fn records(result: &Result<CycleReport>, duration: Duration) -> Vec<CycleRecord<'_>> {
+    let duration_ms = duration.as_millis() as u64;
+    match result {
+        Ok(report) => report
+            .targets
+            .iter()
+            .map(|target| {
+                let (signature, reason, error) = match &target.result {
+                    Ok(CycleOutcome::Submitted { signature, .. } | CycleOutcome::Proposed { signature, .. }) => {
+                        (Some(signature.to_string()), None, None)
+                    }
+                    Ok(CycleOutcome::Skipped { reason, .. }) => (None, Some(*reason), None),
+                    Ok(CycleOutcome::DryRun { .. } | CycleOutcome::Observed { .. }) => (None, None, None),
+                    Err(e) => (None, None, Some(e.to_string())),
+                };
+                let l1_lag_slots = match &target.result {
+                    Ok(CycleOutcome::Observed { l1_lag_slots, .. }) => *l1_lag_slots,
+                    _ => None,
+                };
+                CycleRecord {
+                    target: Some(&target.target),
+                    slot: Some(report.roots.slot),
+                    mt_root: Some(hex::encode(&report.roots.mt_root)),
+                    ws_root: Some(hex::encode(report.roots.ws_root)),
+                    signature,
+                    status: target.status(),
+                    reason,
+                    l1_lag_slots,
+                    error,
+                    duration_ms,
+                }
+            })
+            .collect(),
+        Err(e) => vec![CycleRecord {
+            target: None,
+            slot: None,
+            mt_root: None,
+            ws_root: None,
+            signature: None,
+            status: "failed",
+            reason: None,
+            l1_lag_slots: None,
+            error: Some(e.to_string()),
+            duration_ms,
+        }],
+    }
+}
@@ -0,0 +1,147 @@
+//! Roots read from L2 while L1 was unreachable, kept until L1 takes them.
+
+use crate::bridge::L2Roots;
+use crate::metrics;
+use crate::state::{expand_path, tmp_path_for};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::warn;
+
+/// One queued read as stored in the pending file
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingEntry {
+    slot: u64,
+    /// Hex-encoded merkle tree root
+    mt_root: String,
+    /// Hex-encoded world state root
+    ws_root: String,
+}
+
+/// Bounded queue of roots awaiting one L1 target, oldest first
+///
+/// Every change is written through to a file next to the target's state file,
+/// so queued roots survive a restart.
+pub struct PendingRoots {
+    target: String,
+    path: PathBuf,
+    capacity: usize,
+    roots: Mutex<VecDeque<L2Roots>>,
+}
+
+impl PendingRoots {
+    /// Opens the queue of `target`, restoring the roots queued before a restart
+    ///
+    /// A missing or corrupt file holds no roots.
+    pub fn open(target: &str, state_file: &str, capacity: usize) -> Self {
+        let path = pending_path(state_file);
+        let mut roots: VecDeque<L2Roots> = read_pending(&path).into_iter().collect();
+        while roots.len() > capacity {
+            roots.pop_front();
+        }
+        metrics::PENDING_ROOTS.with_label_values(&[target]).set(roots.len() as i64);
+        Self {
+            target: target.to_string(),
+            path,
+            capacity,
+            roots: Mutex::new(roots),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.roots.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The oldest queued roots
+    pub fn front(&self) -> Option<L2Roots> {
+        self.roots.lock().unwrap().front().cloned()
+    }
+
+    /// Queues `roots` behind the ones already waiting, dropping the oldest when full
+    ///
+    /// Roots for a slot no newer than the last queued one are ignored. Returns
+    /// whether `roots` were queued.
+    pub fn push(&self, roots: &L2Roots) -> Result<bool> {
+        let mut queue = self.roots.lock().unwrap();
+        if self.capacity == 0 || queue.back().is_some_and(|last| last.slot >= roots.slot) {
+            return Ok(false);
+        }
+        if queue.len() == self.capacity {
+            if let Some(dropped) = queue.pop_front() {
+                warn!(l1_target = %self.target, slot = dropped.slot, "pending queue full, dropping oldest roots");
+            }
+        }
+        queue.push_back(roots.clone());
+        self.persist(&queue)?;
+        Ok(true)
+    }
+
+    /// Removes the oldest queued roots, once L1 took them or refused them for good
+    pub fn pop_front(&self) -> Result<()> {
+        let mut queue = self.roots.lock().unwrap();
+        queue.pop_front();
+        self.persist(&queue)
+    }
+
+    /// Writes the queue through a temporary file, removing the file once the queue is empty
+    fn persist(&self, queue: &VecDeque<L2Roots>) -> Result<()> {
+        metrics::PENDING_ROOTS.with_label_values(&[&self.target]).set(queue.len() as i64);
+        if queue.is_empty() {
+            return match fs::remove_file(&self.path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            };
+        }
+        let entries: Vec<PendingEntry> = queue
+            .iter()
+            .map(|roots| PendingEntry {
+                slot: roots.slot,
+                mt_root: hex::encode(&roots.mt_root),
+                ws_root: hex::encode(roots.ws_root),
+            })
+            .collect();
+        let tmp_path = tmp_path_for(&self.path);
+        fs::write(&tmp_path, serde_json::to_string(&entries)?)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+/// The pending file kept next to `state_file`
+fn pending_path(state_file: &str) -> PathBuf {
+    let mut path = expand_path(state_file).into_os_string();
+    path.push(".pending");
+    PathBuf::from(path)
+}
+
+/// Reads the queued roots, skipping the file when it is missing or corrupt
+fn read_pending(path: &Path) -> Vec<L2Roots> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let entries: Vec<PendingEntry> = match serde_json::from_str(&contents) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "ignoring corrupt pending file");
+            return Vec::new();
+        }
+    };
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let roots = L2Roots {
+                slot: entry.slot,
+                mt_root: hex::decode(&entry.mt_root).ok()?,
+                ws_root: hex::decode(&entry.ws_root).ok()?.try_into().ok()?,
+            };
+            Some(roots)
+        })
+        .collect()
+}
@@ -0,0 +1,426 @@
+//! Description of the L1 program instruction the bridge calls.
+
+use crate::config::parse_pubkey;
+use eyre::{Result, WrapErr};
+use serde::Deserialize;
+use solana_sdk::hash::hashv;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::{Pubkey, MAX_SEEDS, MAX_SEED_LEN};
+
+/// Anchor instruction called when no discriminator is configured
+const DEFAULT_INSTRUCTION: &str = "add_roots";
+
+/// L1 program configuration as read from the `[program]` config section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProgramConfig {
+    /// Anchor instruction name the discriminator is derived from
+    pub instruction: Option<String>,
+    /// Raw 8-byte instruction discriminator, used instead of `instruction`
+    pub discriminator: Option<Vec<u8>>,
+    /// Byte offset of the merkle root in the slot roots account, after the
+    /// discriminator and the `u64` slot
+    pub roots_mt_root_offset: usize,
+    /// Byte offset of the little-endian `u64` highest slot recorded in the slots account;
+    /// the L1 lag is not measured when unset
+    pub last_slot_offset: Option<usize>,
+    /// Role, flags and position of each instruction account; the built-in layout when empty
+    pub accounts: Vec<AccountSpec>,
+    /// Accounts appended after the instruction accounts
+    pub extra_accounts: Vec<ExtraAccount>,
+    /// Instructions sent together in one transaction, in order, instead of the single one above
+    pub instructions: Vec<InstructionConfig>,
+    /// Seeds of the slot roots PDA
+    pub pda: PdaConfig,
+}
+
+/// One instruction of a multi-instruction submission as read from a `[[program.instructions]]` entry
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct InstructionConfig {
+    /// Anchor instruction name the discriminator is derived from
+    pub instruction: Option<String>,
+    /// Raw 8-byte instruction discriminator, used instead of `instruction`
+    pub discriminator: Option<Vec<u8>>,
+    /// Hex data following the discriminator, with `{slot}`, `{slot_be}`, `{mt_root}`, `{ws_root}`
+    /// and `{roots}` placeholders; `{slot}{roots}` when unset
+    pub data: Option<String>,
+    /// Role, flags and position of each instruction account; the built-in layout when empty
+    pub accounts: Vec<AccountSpec>,
+    /// Accounts appended after the instruction accounts
+    pub extra_accounts: Vec<ExtraAccount>,
+}
+
+/// A piece of instruction data as written in a data template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataPart {
+    Literal(Vec<u8>),
+    /// The slot as a little-endian `u64`
+    Slot,
+    /// The slot as a big-endian `u64`
+    SlotBe,
+    MtRoot,
+    WsRoot,
+    /// `mt_root` then `ws_root`, or the `settings.root_transform` output in their place
+    Roots,
+}
+
+/// A parsed instruction: its discriminator, data template and accounts
+#[derive(Debug, Clone)]
+pub struct InstructionLayout {
+    pub discriminator: [u8; 8],
+    pub data: Vec<DataPart>,
+    /// Instruction accounts in order, ahead of the extra accounts
+    pub accounts: Vec<AccountSpec>,
+    pub extra_accounts: Vec<AccountMeta>,
+}
+
+/// Data following the discriminator when no template is given, as `add_roots` takes it
+const DEFAULT_DATA_TEMPLATE: &str = "{slot}{roots}";
+
+/// Parses a data template of hex literals and placeholders
+pub fn parse_data_template(key: &str, template: &str) -> Result<Vec<DataPart>> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while !rest.is_empty() {
+        let end = rest.find('{').unwrap_or(rest.len());
+        let literal: String = rest[..end].chars().filter(|c| !c.is_whitespace()).collect();
+        if !literal.is_empty() {
+            let bytes = hex::decode(&literal).map_err(|e| eyre::eyre!("{}: invalid hex {:?}: {}", key, literal, e))?;
+            parts.push(DataPart::Literal(bytes));
+        }
+        rest = &rest[end..];
+        if rest.is_empty() {
+            break;
+        }
+        let close = rest.find('}').ok_or_else(|| eyre::eyre!("{}: unclosed placeholder in {:?}", key, template))?;
+        parts.push(match &rest[1..close] {
+            "slot" => DataPart::Slot,
+            "slot_be" => DataPart::SlotBe,
+            "mt_root" => DataPart::MtRoot,
+            "ws_root" => DataPart::WsRoot,
+            "roots" => DataPart::Roots,
+            other => return Err(eyre::eyre!("{}: unknown placeholder {{{}}}", key, other)),
+        });
+        rest = &rest[close + 1..];
+    }
+    Ok(parts)
+}
+
+/// PDA derivation as read from the `[program.pda]` config section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PdaConfig {
+    /// Ordered seeds: literal strings, literal byte arrays, or `"slot_le"`/`"slot_be"`
+    pub seeds: Vec<SeedSpec>,
+}
+
+impl Default for PdaConfig {
+    fn default() -> Self {
+        Self {
+            seeds: vec![SeedSpec::Text("roots".to_string()), SeedSpec::Text("slot_le".to_string())],
+        }
+    }
+}
+
+/// A seed component as written in config
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SeedSpec {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+/// A parsed PDA seed component
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Seed {
+    Literal(Vec<u8>),
+    SlotLe,
+    SlotBe,
+}
+
+impl PdaConfig {
+    /// Parses and validates the seed list
+    pub fn parse_seeds(&self) -> Result<Vec<Seed>> {
+        if self.seeds.len() > MAX_SEEDS {
+            return Err(eyre::eyre!(
+                "program.pda.seeds: at most {} seeds allowed, got {}",
+                MAX_SEEDS,
+                self.seeds.len()
+            ));
+        }
+
+        self.seeds
+            .iter()
+            .map(|spec| {
+                let seed = match spec {
+                    SeedSpec::Text(text) if text == "slot_le" => Seed::SlotLe,
+                    SeedSpec::Text(text) if text == "slot_be" => Seed::SlotBe,
+                    SeedSpec::Text(text) => Seed::Literal(text.as_bytes().to_vec()),
+                    SeedSpec::Bytes(bytes) => Seed::Literal(bytes.clone()),
+                };
+                if let Seed::Literal(bytes) = &seed {
+                    if bytes.len() > MAX_SEED_LEN {
+                        return Err(eyre::eyre!(
+                            "program.pda.seeds: seed longer than {} bytes: {:?}",
+                            MAX_SEED_LEN,
+                            spec
+                        ));
+                    }
+                }
+                Ok(seed)
+            })
+            .collect()
+    }
+}
+
+/// Builds the seed bytes for `slot` from parsed seed components
+pub fn seeds_for_slot(seeds: &[Seed], slot: u64) -> Vec<Vec<u8>> {
+    seeds
+        .iter()
+        .map(|seed| match seed {
+            Seed::Literal(bytes) => bytes.clone(),
+            Seed::SlotLe => slot.to_le_bytes().to_vec(),
+            Seed::SlotBe => slot.to_be_bytes().to_vec(),
+        })
+        .collect()
+}
+
+/// Derives the slot roots PDA and its bump for `slot`
+pub fn derive_slot_roots_pda(seeds: &[Seed], slot: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    let seeds = seeds_for_slot(seeds, slot);
+    let seed_refs: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    Pubkey::find_program_address(&seed_refs, program_id)
+}
+
+/// What an instruction account stands for, resolved to a pubkey per submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountRole {
+    /// The target's `slots_account`
+    Slots,
+    SystemProgram,
+    /// The slot roots PDA derived for the submitted slot
+    SlotRootsPda,
+    /// The wallet
+    Authority,
+}
+
+/// One instruction account as read from a `[[program.accounts]]` config entry
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AccountSpec {
+    pub role: AccountRole,
+    /// Zero-based position of the account in the instruction
+    pub ordinal: usize,
+    #[serde(default)]
+    pub is_signer: bool,
+    #[serde(default)]
+    pub is_writable: bool,
+}
+
+impl AccountSpec {
+    fn new(role: AccountRole, ordinal: usize, is_signer: bool, is_writable: bool) -> Self {
+        Self {
+            role,
+            ordinal,
+            is_signer,
+            is_writable,
+        }
+    }
+
+    /// The account meta of this entry for `pubkey`
+    pub fn to_account_meta(&self, pubkey: Pubkey) -> AccountMeta {
+        if self.is_writable {
+            AccountMeta::new(pubkey, self.is_signer)
+        } else {
+            AccountMeta::new_readonly(pubkey, self.is_signer)
+        }
+    }
+}
+
+/// The account layout of the `add_roots` instruction the bridge was written against
+fn default_account_layout() -> Vec<AccountSpec> {
+    vec![
+        AccountSpec::new(AccountRole::Slots, 0, false, true),
+        AccountSpec::new(AccountRole::SystemProgram, 1, false, false),
+        AccountSpec::new(AccountRole::SlotRootsPda, 2, false, true),
+        AccountSpec::new(AccountRole::Authority, 3, true, true),
+    ]
+}
+
+/// An additional account passed to the L1 instruction
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExtraAccount {
+    pub pubkey: String,
+    #[serde(default)]
+    pub is_signer: bool,
+    #[serde(default)]
+    pub is_writable: bool,
+}
+
+impl ExtraAccount {
+    /// Converts the entry into an account meta, validating the pubkey
+    pub fn to_account_meta(&self) -> Result<AccountMeta> {
+        let pubkey = parse_pubkey("program.extra_accounts.pubkey", &self.pubkey)?;
+        Ok(if self.is_writable {
+            AccountMeta::new(pubkey, self.is_signer)
+        } else {
+            AccountMeta::new_readonly(pubkey, self.is_signer)
+        })
+    }
+}
+
+impl Default for ProgramConfig {
+    fn default() -> Self {
+        Self {
+            instruction: None,
+            discriminator: None,
+            roots_mt_root_offset: 16,
+            last_slot_offset: None,
+            accounts: Vec::new(),
+            extra_accounts: Vec::new(),
+            instructions: Vec::new(),
+            pda: PdaConfig::default(),
+        }
+    }
+}
+
+impl ProgramConfig {
+    /// The instruction accounts in ordinal order, validated
+    ///
+    /// The PDA, authority and system program must each appear exactly once and the
+    /// slots account at most once, the ordinals must number the entries from 0 without
+    /// gaps, and the authority must sign.
+    pub fn account_layout(&self) -> Result<Vec<AccountSpec>> {
+        if self.accounts.is_empty() {
+            return Ok(default_account_layout());
+        }
+        validated_layout("program.accounts", &self.accounts)
+    }
+
+    /// The instructions of every submission, in order
+    ///
+    /// Without `[[program.instructions]]` this is the single instruction described by the
+    /// other `[program]` settings, which must then be left unset.
+    pub fn instruction_layouts(&self) -> Result<Vec<InstructionLayout>> {
+        if self.instructions.is_empty() {
+            return Ok(vec![InstructionLayout {
+                discriminator: self.instruction_discriminator()?,
+                data: parse_data_template("program", DEFAULT_DATA_TEMPLATE)?,
+                accounts: self.account_layout()?,
+                extra_accounts: self.extra_account_metas()?,
+            }]);
+        }
+        if self.instruction.is_some()
+            || self.discriminator.is_some()
+            || !self.accounts.is_empty()
+            || !self.extra_accounts.is_empty()
+        {
+            return Err(eyre::eyre!(
+                "program.instructions: move instruction, discriminator, accounts and extra_accounts into the entries"
+            ));
+        }
+        self.instructions
+            .iter()
+            .enumerate()
+            .map(|(index, instruction)| {
+                let key = format!("program.instructions[{}]", index);
+                let program = ProgramConfig {
+                    instruction: instruction.instruction.clone(),
+                    discriminator: instruction.discriminator.clone(),
+                    ..ProgramConfig::default()
+                };
+                let accounts = if instruction.accounts.is_empty() {
+                    default_account_layout()
+                } else {
+                    validated_layout(&format!("{}.accounts", key), &instruction.accounts)?
+                };
+                Ok(InstructionLayout {
+                    discriminator: program.instruction_discriminator().wrap_err_with(|| key.clone())?,
+                    data: parse_data_template(&key, instruction.data.as_deref().unwrap_or(DEFAULT_DATA_TEMPLATE))?,
+                    accounts,
+                    extra_accounts: instruction.extra_accounts.iter().map(ExtraAccount::to_account_meta).collect::<Result<_>>()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Parses the extra accounts into account metas
+    pub fn extra_account_metas(&self) -> Result<Vec<AccountMeta>> {
+        self.extra_accounts.iter().map(ExtraAccount::to_account_meta).collect()
+    }
+
+    /// Resolves the 8-byte instruction discriminator
+    pub fn instruction_discriminator(&self) -> Result<[u8; 8]> {
+        match (&self.discriminator, &self.instruction) {
+            (Some(_), Some(_)) => Err(eyre::eyre!(
+                "program: set either discriminator or instruction, not both"
+            )),
+            (Some(bytes), None) => bytes.as_slice().try_into().map_err(|_| {
+                eyre::eyre!("program.discriminator: expected 8 bytes, got {}", bytes.len())
+            }),
+            (None, Some(name)) => Ok(anchor_sighash(name)),
+            (None, None) => Ok(anchor_sighash(DEFAULT_INSTRUCTION)),
+        }
+    }
+}
+
+/// Sorts and checks a configured account layout, as described on `ProgramConfig::account_layout`
+fn validated_layout(key: &str, accounts: &[AccountSpec]) -> Result<Vec<AccountSpec>> {
+    let accounts = sorted_layout(key, accounts)?;
+    check_roles(key, &accounts)?;
+    check_authority_signs(key, &accounts)?;
+    Ok(accounts)
+}
+
+/// Checks the PDA, authority and system program each appear exactly once and the slots account at most once
+fn check_roles(key: &str, accounts: &[AccountSpec]) -> Result<()> {
+    let required = [
+        (AccountRole::SlotRootsPda, true),
+        (AccountRole::Authority, true),
+        (AccountRole::SystemProgram, true),
+        (AccountRole::Slots, false),
+    ];
+    for (role, required) in required {
+        let count = accounts.iter().filter(|account| account.role == role).count();
+        if count > 1 || (required && count == 0) {
+            let expected = if required { "exactly once" } else { "at most once" };
+            return Err(eyre::eyre!("{}: role {:?} must appear {}, got {}", key, role, expected, count));
+        }
+    }
+    Ok(())
+}
+
+/// Sorts `accounts` by ordinal, checking the ordinals number them from 0 without gaps
+fn sorted_layout(key: &str, accounts: &[AccountSpec]) -> Result<Vec<AccountSpec>> {
+    let mut accounts = accounts.to_vec();
+    accounts.sort_by_key(|account| account.ordinal);
+
+    for (ordinal, account) in accounts.iter().enumerate() {
+        if account.ordinal != ordinal {
+            return Err(eyre::eyre!(
+                "{}: ordinals must run from 0 to {} without gaps or repeats, got {}",
+                key,
+                accounts.len() - 1,
+                account.ordinal
+            ));
+        }
+    }
+    Ok(accounts)
+}
+
+/// Checks every authority entry in `accounts` signs
+fn check_authority_signs(key: &str, accounts: &[AccountSpec]) -> Result<()> {
+    if accounts.iter().any(|account| account.role == AccountRole::Authority && !account.is_signer) {
+        return Err(eyre::eyre!("{}: the authority must be a signer", key));
+    }
+    Ok(())
+}
+
+/// Computes the Anchor instruction sighash, `sha256("global:<name>")[..8]`
+pub fn anchor_sighash(name: &str) -> [u8; 8] {
+    let hash = hashv(&[b"global:", name.as_bytes()]);
+    let mut sighash = [0u8; 8];
+    sighash.copy_from_slice(&hash.to_bytes()[..8]);
+    sighash
+}
@@ -0,0 +1,94 @@
+//! Decoding of custom errors returned by the L1 program.
+
+use eyre::Result;
+use serde::Deserialize;
+use solana_client::client_error::ClientError;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+
+/// Program error handling as read from the `[program_errors]` config section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProgramErrorsConfig {
+    /// Custom error codes meaning the slot's roots account already exists
+    pub already_initialized: Vec<u32>,
+    /// Custom error codes that are benign: the slot is recorded as submitted and the cycle moves on
+    pub ignore: Vec<u32>,
+    /// Custom error codes retried like a transient error
+    pub retry: Vec<u32>,
+    /// Custom error codes that abort the whole cycle, skipping the remaining targets
+    pub fatal: Vec<u32>,
+}
+
+/// What to do about a custom error code returned by the L1 program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorAction {
+    Ignore,
+    Retry,
+    Fatal,
+}
+
+impl Default for ProgramErrorsConfig {
+    fn default() -> Self {
+        // The system program reports `AccountAlreadyInUse` as custom error 0 when
+        // an Anchor `init` targets an existing account
+        Self {
+            already_initialized: vec![0],
+            ignore: Vec::new(),
+            retry: Vec::new(),
+            fatal: Vec::new(),
+        }
+    }
+}
+
+impl ProgramErrorsConfig {
+    /// Returns true if the error means another submission already created the slot's account
+    pub fn is_already_initialized(&self, err: &eyre::Report) -> bool {
+        custom_error_code(err).is_some_and(|code| self.already_initialized.contains(&code))
+    }
+
+    /// The action configured for the error's custom code, if any
+    pub fn action(&self, err: &eyre::Report) -> Option<ErrorAction> {
+        let code = custom_error_code(err)?;
+        [
+            (&self.ignore, ErrorAction::Ignore),
+            (&self.retry, ErrorAction::Retry),
+            (&self.fatal, ErrorAction::Fatal),
+        ]
+        .into_iter()
+        .find_map(|(codes, action)| codes.contains(&code).then_some(action))
+    }
+
+    /// Checks no code is given more than one action
+    pub fn validate(&self) -> Result<()> {
+        let lists = [
+            ("already_initialized", &self.already_initialized),
+            ("ignore", &self.ignore),
+            ("retry", &self.retry),
+            ("fatal", &self.fatal),
+        ];
+        for (i, (name, codes)) in lists.iter().enumerate() {
+            for (other, other_codes) in &lists[i + 1..] {
+                if let Some(code) = codes.iter().find(|code| other_codes.contains(code)) {
+                    return Err(eyre::eyre!("program_errors: code {} is in both {} and {}", code, name, other));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the transaction error from a failed send or confirmation, if any
+pub fn transaction_error(err: &eyre::Report) -> Option<TransactionError> {
+    err.downcast_ref::<TransactionError>()
+        .cloned()
+        .or_else(|| err.downcast_ref::<ClientError>().and_then(|e| e.get_transaction_error()))
+}
+
+/// Extracts the `InstructionError::Custom` code from a failed transaction, if any
+pub fn custom_error_code(err: &eyre::Report) -> Option<u32> {
+    match transaction_error(err)? {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(code),
+        _ => None,
+    }
+}
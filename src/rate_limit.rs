@@ -0,0 +1,131 @@
+//! Token bucket limiting the rate of RPC requests.
+
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::Instant;
+use tokio::time::Duration;
+
+/// Rate limiting as read from the `[rate_limit]` config section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Sustained requests per second to each network; 0 disables the limiter
+    pub requests_per_second: f64,
+    /// Requests that may be made back to back before the rate applies
+    pub burst: u32,
+    /// Pause after an endpoint answers 429 Too Many Requests
+    pub throttled_backoff_ms: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 0.0,
+            burst: 10,
+            throttled_backoff_ms: 1_000,
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// A token bucket shared by every call made through one RPC client
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    throttled_backoff: Duration,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter, or `None` when the config disables it
+    pub fn new(config: &RateLimitConfig) -> Option<Self> {
+        if config.requests_per_second <= 0.0 {
+            return None;
+        }
+        let burst = f64::from(config.burst.max(1));
+        Some(Self {
+            rate: config.requests_per_second,
+            burst,
+            throttled_backoff: Duration::from_millis(config.throttled_backoff_ms),
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                refilled_at: Instant::now(),
+            }),
+        })
+    }
+
+    /// Takes a token, returning how long the caller must wait before sending
+    ///
+    /// The bucket may go into debt, so concurrent callers queue up behind each other.
+    pub fn reserve(&self) -> Duration {
+        self.reserve_at(Instant::now())
+    }
+
+    fn reserve_at(&self, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+        let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.refilled_at = bucket.refilled_at.max(now);
+        bucket.tokens -= 1.0;
+
+        // Refilling resumes at `refilled_at`, which is in the future while backing off
+        let debt = Duration::from_secs_f64((-bucket.tokens).max(0.0) / self.rate);
+        bucket.refilled_at.saturating_duration_since(now) + debt
+    }
+
+    /// Empties the bucket and holds further requests back after a 429
+    pub fn throttled(&self) {
+        self.throttled_at(Instant::now());
+    }
+
+    fn throttled_at(&self, now: Instant) {
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.tokens = bucket.tokens.min(0.0);
+        bucket.refilled_at = bucket.refilled_at.max(now + self.throttled_backoff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(&RateLimitConfig {
+            requests_per_second: 2.0,
+            burst: 2,
+            throttled_backoff_ms: 3_000,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn disabled_without_rate() {
+        assert!(RateLimiter::new(&RateLimitConfig::default()).is_none());
+    }
+
+    #[test]
+    fn bursts_then_spaces_requests() {
+        let limiter = limiter();
+        let now = Instant::now();
+        assert_eq!(limiter.reserve_at(now), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(now), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(now), Duration::from_millis(500));
+        assert_eq!(limiter.reserve_at(now), Duration::from_millis(1_000));
+
+        // Tokens refill at the configured rate, paying off the debt first
+        assert_eq!(limiter.reserve_at(now + Duration::from_secs(2)), Duration::ZERO);
+    }
+
+    #[test]
+    fn backs_off_after_throttling() {
+        let limiter = limiter();
+        let now = Instant::now();
+        limiter.throttled_at(now);
+        assert_eq!(limiter.reserve_at(now), Duration::from_millis(3_500));
+        assert_eq!(limiter.reserve_at(now + Duration::from_secs(4)), Duration::ZERO);
+    }
+}
@@ -0,0 +1,121 @@
+//! Reloading the configuration on SIGHUP.
+
+use crate::bridge::{self, BridgeContext};
+use crate::config::{load_config, Config};
+use eyre::{Result, WrapErr};
+use std::sync::atomic::Ordering;
+use tracing::{error, info, warn};
+
+/// Where a reloaded config is read from
+pub struct ConfigSource {
+    /// Config file path, as passed to `load_config`
+    pub path: String,
+    /// Applied to every reloaded config before it is validated, such as command-line overrides
+    pub overrides: Box<dyn Fn(&mut Config) + Send + Sync>,
+}
+
+/// Resolves whenever a config reload is requested; never fires off unix
+pub struct ReloadSignal {
+    #[cfg(unix)]
+    hangup: Option<tokio::signal::unix::Signal>,
+}
+
+impl ReloadSignal {
+    /// Starts listening for SIGHUP, unless `enabled` is false
+    pub fn new(enabled: bool) -> Self {
+        #[cfg(unix)]
+        {
+            let hangup = enabled
+                .then(|| tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()))
+                .transpose()
+                .map_err(|e| error!(error = %e, "failed to listen for SIGHUP, config reload disabled"))
+                .ok()
+                .flatten();
+            Self { hangup }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = enabled;
+            Self {}
+        }
+    }
+
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(hangup) = &mut self.hangup {
+            hangup.recv().await;
+            return;
+        }
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Loads and validates the config again, building a new context from it
+///
+/// Nothing changes unless the new config is valid and its clusters check out;
+/// the caller keeps running on `current` when this fails.
+pub async fn reload_context(source: &ConfigSource, current: &BridgeContext) -> Result<BridgeContext> {
+    let mut config = load_config(&source.path).wrap_err("config reload")?;
+    (source.overrides)(&mut config);
+    config.validate().wrap_err("config reload")?;
+
+    let changed = changed_sections(&current.config, &config);
+    warn_restart_only_settings(&current.config, &config);
+
+    let mut ctx = BridgeContext::new(config).await.wrap_err("config reload")?;
+    bridge::check_clusters(&ctx).await.wrap_err("config reload")?;
+
+    // Keep regression protection across the swap
+    ctx.max_seen_slot
+        .fetch_max(current.max_seen_slot.load(Ordering::Relaxed), Ordering::Relaxed);
+    ctx.spend.inherit(&current.spend);
+
+    info!(?changed, "configuration reloaded");
+    Ok(ctx)
+}
+
+/// Warns about settings that are only read at startup
+fn warn_restart_only_settings(old: &Config, new: &Config) {
+    let (old, new) = (&old.settings, &new.settings);
+    let restart_only = [
+        ("metrics_port", old.metrics_port != new.metrics_port),
+        ("admin_token", old.admin_token != new.admin_token),
+        ("log_level", old.log_level != new.log_level),
+        ("log_format", old.log_format != new.log_format),
+        ("output_format", old.output_format != new.output_format),
+        ("startup_jitter_secs", old.startup_jitter_secs != new.startup_jitter_secs),
+        ("warmup_timeout_secs", old.warmup_timeout_secs != new.warmup_timeout_secs),
+        ("max_runtime_secs", old.max_runtime_secs != new.max_runtime_secs),
+    ];
+    for (setting, changed) in restart_only {
+        if changed {
+            warn!(setting, "settings.{} only takes effect after a restart", setting);
+        }
+    }
+}
+
+/// Names the top-level config sections that differ between `old` and `new`
+fn changed_sections(old: &Config, new: &Config) -> Vec<&'static str> {
+    let differs = |a: &dyn std::fmt::Debug, b: &dyn std::fmt::Debug| format!("{:?}", a) != format!("{:?}", b);
+    [
+        ("network", differs(&old.network, &new.network)),
+        ("account", differs(&old.account, &new.account)),
+        ("wallet", differs(&old.wallet, &new.wallet)),
+        ("settings", differs(&old.settings, &new.settings)),
+        ("retry", differs(&old.retry, &new.retry)),
+        ("fees", differs(&old.fees, &new.fees)),
+        ("rate_limit", differs(&old.rate_limit, &new.rate_limit)),
+        ("program", differs(&old.program, &new.program)),
+        ("program_errors", differs(&old.program_errors, &new.program_errors)),
+        ("l1_targets", differs(&old.l1_targets, &new.l1_targets)),
+        ("alerts", differs(&old.alerts, &new.alerts)),
+        ("nonce", differs(&old.nonce, &new.nonce)),
+        ("jito", differs(&old.jito, &new.jito)),
+        ("audit", differs(&old.audit, &new.audit)),
+        ("send", differs(&old.send, &new.send)),
+        ("multisig", differs(&old.multisig, &new.multisig)),
+    ]
+    .into_iter()
+    .filter_map(|(section, changed)| changed.then_some(section))
+    .collect()
+}
@@ -0,0 +1,337 @@
+//! Retry helpers with exponential backoff and jitter.
+
+use eyre::Result;
+use rand::Rng;
+use serde::Deserialize;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_custom_error::{
+    JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE, JSON_RPC_SERVER_ERROR_BLOCK_STATUS_NOT_AVAILABLE_YET,
+    JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+};
+use solana_client::rpc_request::RpcError;
+use crate::error::BridgeError;
+use crate::program_errors::custom_error_code;
+use crate::rpc::is_connection_error;
+use std::future::Future;
+use tokio::time::Duration;
+
+/// Retry configuration as read from the `[retry]` config section, applying to RPC reads
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub multiplier: f64,
+    pub jitter_fraction: f64,
+    /// Times a transaction is re-signed with a fresh blockhash after "blockhash not found"
+    pub blockhash_retries: u32,
+    /// Error message fragments retried on top of the built-in transient errors
+    pub transient_messages: Vec<String>,
+    /// Retries of the `sendTransaction` call, resending the same signed transaction
+    pub send: BackoffOverrides,
+    /// Retries of a submission that failed to confirm, each re-signed with a fresh blockhash
+    pub confirm: BackoffOverrides,
+}
+
+/// Backoff settings of one operation, as read from `[retry.send]` or `[retry.confirm]`
+///
+/// Unset values fall back to the ones in `[retry]`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BackoffOverrides {
+    pub max_attempts: Option<u32>,
+    pub base_delay_ms: Option<u64>,
+    pub max_delay_ms: Option<u64>,
+    pub multiplier: Option<f64>,
+    pub jitter_fraction: Option<f64>,
+}
+
+impl RetryConfig {
+    /// Policy for resending a signed transaction; a single attempt unless `retry.send.max_attempts`
+    /// is set, leaving failed sends to the confirmation retry
+    pub fn send_policy(&self) -> RetryPolicy {
+        let send = BackoffOverrides {
+            max_attempts: Some(self.send.max_attempts.unwrap_or(1)),
+            ..self.send.clone()
+        };
+        self.policy_with(&send)
+    }
+
+    /// Policy for rebuilding and resending a submission that did not confirm
+    pub fn confirm_policy(&self) -> RetryPolicy {
+        self.policy_with(&self.confirm)
+    }
+
+    /// The `[retry]` policy with `overrides` applied
+    fn policy_with(&self, overrides: &BackoffOverrides) -> RetryPolicy {
+        RetryPolicy::from(&RetryConfig {
+            max_attempts: overrides.max_attempts.unwrap_or(self.max_attempts),
+            base_delay_ms: overrides.base_delay_ms.unwrap_or(self.base_delay_ms),
+            max_delay_ms: overrides.max_delay_ms.unwrap_or(self.max_delay_ms),
+            multiplier: overrides.multiplier.unwrap_or(self.multiplier),
+            jitter_fraction: overrides.jitter_fraction.unwrap_or(self.jitter_fraction),
+            ..self.clone()
+        })
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 1_000,
+            max_delay_ms: 30_000,
+            multiplier: 2.0,
+            jitter_fraction: 0.1,
+            blockhash_retries: 3,
+            transient_messages: Vec::new(),
+            send: BackoffOverrides::default(),
+            confirm: BackoffOverrides::default(),
+        }
+    }
+}
+
+/// Backoff policy used by `with_retry`
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    pub jitter_fraction: f64,
+    pub transient_messages: Vec<String>,
+    /// L1 program custom error codes retried on top of the built-in transient errors
+    pub retry_codes: Vec<u32>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::from(&RetryConfig::default())
+    }
+}
+
+impl From<&RetryConfig> for RetryPolicy {
+    fn from(config: &RetryConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            base_delay: Duration::from_millis(config.base_delay_ms),
+            max_delay: Duration::from_millis(config.max_delay_ms),
+            multiplier: config.multiplier,
+            jitter_fraction: config.jitter_fraction,
+            transient_messages: config.transient_messages.clone(),
+            retry_codes: Vec::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Computes the delay before the next attempt, `attempt` being zero-based
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exp = self.multiplier.powi(attempt as i32);
+        let backoff = self.base_delay.as_secs_f64() * exp;
+        let capped = backoff.min(self.max_delay.as_secs_f64());
+
+        let jitter = if self.jitter_fraction > 0.0 {
+            rand::thread_rng().gen_range(0.0..=self.jitter_fraction) * capped
+        } else {
+            0.0
+        };
+
+        Duration::from_secs_f64(capped + jitter)
+    }
+
+    /// Whether `err` is worth another attempt: a transient error, one matching `transient_messages`,
+    /// or a program error with one of the `retry_codes`
+    pub fn should_retry(&self, err: &eyre::Report) -> bool {
+        if is_transient(err) || custom_error_code(err).is_some_and(|code| self.retry_codes.contains(&code)) {
+            return true;
+        }
+        let message = format!("{:#}", err);
+        self.transient_messages.iter().any(|fragment| message.contains(fragment.as_str()))
+    }
+}
+
+/// JSON-RPC error codes from a node that is behind or has not caught up with a request yet
+const TRANSIENT_RPC_CODES: [i64; 4] = [
+    JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+    JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    JSON_RPC_SERVER_ERROR_BLOCK_STATUS_NOT_AVAILABLE_YET,
+    JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED,
+];
+
+/// Returns true for errors that may succeed if the call is repeated, such as
+/// timeouts, connection resets, server-side HTTP failures, lagging nodes and
+/// unconfirmed transactions
+///
+/// Everything else, like invalid config, missing accounts or transactions the
+/// program rejected, fails the same way on every attempt.
+pub fn is_transient(err: &eyre::Report) -> bool {
+    if let Some(client_err) = err.downcast_ref::<ClientError>() {
+        return is_connection_error(client_err)
+            || match client_err.kind() {
+                ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+                ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+                    TRANSIENT_RPC_CODES.contains(code)
+                }
+                _ => false,
+            };
+    }
+
+    if let Some(bridge_err) = BridgeError::find(err) {
+        return bridge_err.is_transient();
+    }
+    err.downcast_ref::<std::io::Error>().is_some_and(is_network_io)
+}
+
+/// Returns true for IO errors from a network connection, leaving local file failures such
+/// as an unwritable state file to fail right away
+fn is_network_io(err: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        err.kind(),
+        ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe
+            | ErrorKind::TimedOut
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+/// Retry mechanism for async operations; only transient errors are retried
+pub async fn with_retry<F, Fut, T>(policy: &RetryPolicy, f: F) -> Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        match f().await {
+            Ok(result) => return Ok(result),
+            Err(e) => {
+                attempt += 1;
+                if attempt >= policy.max_attempts || !policy.should_retry(&e) {
+                    return Err(e);
+                }
+                tokio::time::sleep(policy.delay_for(attempt - 1)).await;
+            }
+        }
+    }
+}
+
+/// Retries with the default policy
+pub async fn with_retry_default<F, Fut, T>(f: F) -> Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    with_retry(&RetryPolicy::default(), f).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::rpc_request::RpcResponseErrorData;
+    use solana_sdk::signature::Signature;
+    use solana_sdk::transaction::TransactionError;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn rpc_response_error(code: i64) -> eyre::Report {
+        ClientError::from(RpcError::RpcResponseError {
+            code,
+            message: "rpc error".to_string(),
+            data: RpcResponseErrorData::Empty,
+        })
+        .into()
+    }
+
+    fn policy(transient_messages: &[&str]) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            multiplier: 1.0,
+            jitter_fraction: 0.0,
+            transient_messages: transient_messages.iter().map(|m| m.to_string()).collect(),
+            retry_codes: Vec::new(),
+        }
+    }
+
+    /// Runs `with_retry` on an operation that always fails with `err`, returning the attempt count
+    async fn attempts(policy: &RetryPolicy, err: fn() -> eyre::Report) -> u32 {
+        let attempts = AtomicU32::new(0);
+        let result: Result<()> = with_retry(policy, || async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(err())
+        })
+        .await;
+        assert!(result.is_err());
+        attempts.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn classifies_transient_errors() {
+        let io = || std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset");
+        assert!(is_transient(&io().into()));
+        assert!(is_transient(&ClientError::from(io()).into()));
+        assert!(is_transient(&BridgeError::RpcTransient(Box::new(ClientError::from(io()))).into()));
+        let wrapped = eyre::Report::new(BridgeError::TransactionDropped { signature: Signature::default() });
+        assert!(is_transient(&wrapped.wrap_err("target primary")));
+        assert!(is_transient(&BridgeError::RpcTimeout { timeout: Duration::from_secs(1) }.into()));
+        assert!(is_transient(
+            &BridgeError::ConfirmationTimeout {
+                signature: Signature::default(),
+                timeout: Duration::from_secs(1),
+            }
+            .into()
+        ));
+        assert!(is_transient(&rpc_response_error(JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY)));
+        assert!(is_transient(&rpc_response_error(JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED)));
+    }
+
+    #[test]
+    fn classifies_permanent_errors() {
+        assert!(!is_transient(&eyre::eyre!("account.slots_account: invalid pubkey")));
+        assert!(!is_transient(&eyre::eyre!("Account not found")));
+        let address = solana_sdk::pubkey::Pubkey::new_unique();
+        assert!(!is_transient(&BridgeError::AccountNotFound { account: "slots", address }.into()));
+        assert!(!is_transient(&BridgeError::InsufficientBalance { have: 0, need_estimate: 5_000 }.into()));
+        assert!(!is_transient(&TransactionError::AccountNotFound.into()));
+        assert!(!is_transient(&ClientError::from(TransactionError::InsufficientFundsForFee).into()));
+        assert!(!is_transient(&rpc_response_error(-32602)));
+        let unwritable = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "state file");
+        assert!(!is_transient(&unwritable.into()));
+    }
+
+    #[tokio::test]
+    async fn retries_only_transient_errors() {
+        let policy = policy(&[]);
+        assert_eq!(attempts(&policy, || BridgeError::RpcTimeout { timeout: Duration::ZERO }.into()).await, 3);
+        assert_eq!(attempts(&policy, || eyre::eyre!("invalid pubkey")).await, 1);
+    }
+
+    #[tokio::test]
+    async fn retries_with_default_policy() {
+        assert_eq!(with_retry_default(|| async { Ok(7) }).await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn retries_configured_messages() {
+        let policy = policy(&["rate limited"]);
+        assert_eq!(attempts(&policy, || eyre::eyre!("upstream rate limited, slow down")).await, 3);
+        assert_eq!(attempts(&policy, || eyre::eyre!("invalid pubkey")).await, 1);
+    }
+
+    #[test]
+    fn splits_policies_per_operation() {
+        let config: RetryConfig = toml::from_str("max_attempts = 4\n[confirm]\nmax_attempts = 6\n[send]\nbase_delay_ms = 5").unwrap();
+        assert_eq!(RetryPolicy::from(&config).max_attempts, 4);
+        assert_eq!(config.confirm_policy().max_attempts, 6);
+        assert_eq!(config.confirm_policy().base_delay, Duration::from_secs(1));
+        assert_eq!(config.send_policy().max_attempts, 1);
+        assert_eq!(config.send_policy().base_delay, Duration::from_millis(5));
+    }
+}
@@ -0,0 +1,475 @@
+//! RPC client wrapper that fails over between multiple endpoints.
+
+use crate::error::BridgeError;
+use crate::metrics;
+use crate::rate_limit::{RateLimitConfig, RateLimiter};
+use eyre::{Result, WrapErr};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::client_error::Result as ClientResult;
+use solana_client::rpc_client::RpcClient;
+use solana_rpc_client::http_sender::HttpSender;
+use solana_rpc_client::rpc_client::RpcClientConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tokio::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Authentication of one endpoint as read from `[network.endpoint_auth."<url>"]`
+///
+/// `${VAR}` in a header or query value is replaced with the environment variable,
+/// keeping API keys out of the config file.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EndpointAuth {
+    /// HTTP headers sent with every request, such as `x-api-key`
+    pub headers: BTreeMap<String, String>,
+    /// Query parameters appended to the endpoint URL
+    pub query: BTreeMap<String, String>,
+    /// PEM file holding the TLS client certificate followed by its private key
+    pub client_cert_path: Option<String>,
+}
+
+// Only names are printed, the values are usually secrets
+impl fmt::Debug for EndpointAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EndpointAuth")
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .field("query", &self.query.keys().collect::<Vec<_>>())
+            .field("client_cert_path", &self.client_cert_path)
+            .finish()
+    }
+}
+
+impl EndpointAuth {
+    /// Resolves the configured headers
+    pub fn header_map(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let header = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| eyre::eyre!("headers: invalid header name {:?}: {}", name, e))?;
+            let value = HeaderValue::from_str(&expand_env(&format!("headers.{}", name), value)?)
+                .map_err(|e| eyre::eyre!("headers.{}: invalid header value: {}", name, e))?;
+            headers.insert(header, value);
+        }
+        Ok(headers)
+    }
+
+    /// Appends the configured query parameters to `url`
+    pub fn url_with_query(&self, url: &str) -> Result<String> {
+        if self.query.is_empty() {
+            return Ok(url.to_string());
+        }
+        let mut parsed = reqwest::Url::parse(url).map_err(|e| eyre::eyre!("invalid URL {:?}: {}", url, e))?;
+        for (name, value) in &self.query {
+            let value = expand_env(&format!("query.{}", name), value)?;
+            parsed.query_pairs_mut().append_pair(name, &value);
+        }
+        Ok(parsed.into())
+    }
+
+    /// Loads the TLS client certificate, when one is configured
+    pub fn identity(&self) -> Result<Option<reqwest::Identity>> {
+        let Some(path) = &self.client_cert_path else {
+            return Ok(None);
+        };
+        let pem = std::fs::read(shellexpand::tilde(path).as_ref())
+            .map_err(|e| eyre::eyre!("client_cert_path: cannot read {}: {}", path, e))?;
+        let identity = reqwest::Identity::from_pem(&pem)
+            .map_err(|e| eyre::eyre!("client_cert_path: invalid certificate or key in {}: {}", path, e))?;
+        Ok(Some(identity))
+    }
+}
+
+/// Expands `${VAR}` references, naming the missing variable but never the value
+fn expand_env(key: &str, value: &str) -> Result<String> {
+    shellexpand::env(value)
+        .map(|expanded| expanded.into_owned())
+        .map_err(|e| eyre::eyre!("{}: {}", key, e))
+}
+
+/// Builds the client of one endpoint, sending its headers and client certificate when configured
+fn endpoint_client(
+    url: &str,
+    auth: Option<&EndpointAuth>,
+    timeout: Duration,
+    commitment: CommitmentConfig,
+) -> Result<RpcClient> {
+    let Some(auth) = auth else {
+        return Ok(RpcClient::new_with_timeout_and_commitment(url.to_string(), timeout, commitment));
+    };
+
+    // Same defaults as `HttpSender::new_with_timeout`, plus the configured headers
+    let mut headers = HttpSender::default_headers();
+    headers.extend(auth.header_map()?);
+    let mut builder = reqwest::Client::builder()
+        .default_headers(headers)
+        .timeout(timeout)
+        .pool_idle_timeout(timeout);
+    if let Some(identity) = auth.identity()? {
+        builder = builder.use_rustls_tls().identity(identity);
+    }
+    let client = builder.build()?;
+
+    let sender = HttpSender::new_with_client(auth.url_with_query(url)?, client);
+    Ok(RpcClient::new_sender(sender, RpcClientConfig::with_commitment(commitment)))
+}
+
+/// Calls that succeeded and failed on one endpoint since its window started
+#[derive(Debug)]
+struct EndpointStats {
+    window_start: Instant,
+    successes: u64,
+    errors: u64,
+}
+
+impl EndpointStats {
+    fn new() -> Self {
+        Self {
+            window_start: Instant::now(),
+            successes: 0,
+            errors: 0,
+        }
+    }
+
+    /// Share of the window's calls that failed, 0 before the first call
+    fn error_rate(&self) -> f64 {
+        let calls = self.successes + self.errors;
+        if calls == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / calls as f64
+    }
+}
+
+/// An RPC client over an ordered list of endpoints
+///
+/// Calls go to the endpoint that last succeeded; on a connection failure the
+/// next endpoint is tried, wrapping around until every endpoint has been tried.
+pub struct FailoverRpcClient {
+    endpoints: Vec<(String, RpcClient)>,
+    /// Metric and log label of each endpoint, its alias when configured
+    labels: Vec<String>,
+    stats: Vec<Mutex<EndpointStats>>,
+    stats_window: Duration,
+    current: AtomicUsize,
+    rate_limiter: Option<RateLimiter>,
+}
+
+impl FailoverRpcClient {
+    /// Creates a client over the given endpoint URLs, preferring them in order
+    ///
+    /// `timeout` bounds every HTTP request made to an endpoint, and every request,
+    /// whichever endpoint it goes to, takes a token from the `rate_limit` bucket.
+    /// Endpoints with an entry in `auth` authenticate with it, and those with an
+    /// entry in `aliases` are labeled with it instead of their URL. Each endpoint's
+    /// error rate is computed over, logged and reset every `stats_window`.
+    pub fn new(
+        urls: &[String],
+        commitment: CommitmentConfig,
+        timeout: Duration,
+        rate_limit: &RateLimitConfig,
+        auth: &BTreeMap<String, EndpointAuth>,
+        aliases: &BTreeMap<String, String>,
+        stats_window: Duration,
+    ) -> Result<Self> {
+        if urls.is_empty() {
+            return Err(eyre::eyre!("At least one RPC endpoint is required"));
+        }
+
+        let endpoints = urls
+            .iter()
+            .map(|url| {
+                let client = endpoint_client(url, auth.get(url), timeout, commitment)
+                    .wrap_err_with(|| format!("network.endpoint_auth.{:?}", metrics::endpoint_label(url)))?;
+                Ok((url.clone(), client))
+            })
+            .collect::<Result<_>>()?;
+
+        let labels = urls
+            .iter()
+            .map(|url| aliases.get(url).cloned().unwrap_or_else(|| metrics::endpoint_label(url)))
+            .collect();
+
+        Ok(Self {
+            endpoints,
+            labels,
+            stats: urls.iter().map(|_| Mutex::new(EndpointStats::new())).collect(),
+            stats_window,
+            current: AtomicUsize::new(0),
+            rate_limiter: RateLimiter::new(rate_limit),
+        })
+    }
+
+    /// Runs `f` against the preferred endpoint, failing over on connection errors
+    ///
+    /// This blocks the calling thread, so async code runs it through [`blocking`].
+    ///
+    /// `RpcClient` itself retries a 429 response a few times, honoring `Retry-After`;
+    /// a 429 that still comes back also pauses the rate limiter.
+    pub fn call<T, F>(&self, f: F) -> ClientResult<T>
+    where
+        F: Fn(&RpcClient) -> ClientResult<T>,
+    {
+        let start = self.current.load(Ordering::Relaxed);
+        let count = self.endpoints.len();
+        let mut last_error = None;
+
+        for step in 0..count {
+            let index = (start + step) % count;
+            let (_, client) = &self.endpoints[index];
+            let endpoint = &self.labels[index];
+
+            self.wait_for_token();
+            let result = f(client);
+            self.record(index, result.is_ok());
+            match result {
+                Ok(result) => {
+                    if index != start {
+                        self.current.store(index, Ordering::Relaxed);
+                    }
+                    return Ok(result);
+                }
+                Err(e) if is_connection_error(&e) => {
+                    if is_too_many_requests(&e) {
+                        if let Some(limiter) = &self.rate_limiter {
+                            limiter.throttled();
+                        }
+                    }
+                    warn!(%endpoint, error = %e, "RPC endpoint failed");
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error.expect("at least one endpoint was tried"))
+    }
+
+    /// Counts one call to the endpoint at `index`, logging and resetting its window once it has run its course
+    fn record(&self, index: usize, ok: bool) {
+        let endpoint = &self.labels[index];
+        let result = if ok { "ok" } else { "error" };
+        metrics::RPC_REQUESTS.with_label_values(&[endpoint, result]).inc();
+        if !ok {
+            metrics::RPC_ERRORS.with_label_values(&[endpoint]).inc();
+        }
+
+        let mut stats = self.stats[index].lock().unwrap();
+        if stats.window_start.elapsed() >= self.stats_window {
+            info!(
+                %endpoint,
+                successes = stats.successes,
+                errors = stats.errors,
+                error_rate = stats.error_rate(),
+                window_secs = self.stats_window.as_secs(),
+                "RPC endpoint error rate"
+            );
+            *stats = EndpointStats::new();
+        }
+        if ok {
+            stats.successes += 1;
+        } else {
+            stats.errors += 1;
+        }
+        metrics::RPC_ERROR_RATE.with_label_values(&[endpoint]).set(stats.error_rate());
+    }
+
+    /// Blocks until the rate limiter allows another request
+    fn wait_for_token(&self) {
+        let Some(limiter) = &self.rate_limiter else {
+            return;
+        };
+        let wait = limiter.reserve();
+        if !wait.is_zero() {
+            debug!(wait_ms = wait.as_millis() as u64, "rate limited, delaying RPC request");
+            tokio::task::block_in_place(|| std::thread::sleep(wait));
+        }
+    }
+}
+
+/// Runs a blocking RPC call on tokio's blocking pool, keeping it off the async workers
+///
+/// The blocking `RpcClient` enters `block_in_place` for every request, which panics on a
+/// current-thread runtime; on a blocking pool thread it runs on either runtime.
+pub async fn blocking<T, F>(call: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(call).await?
+}
+
+/// Awaits an RPC call, failing with `RpcTimeout` once `timeout` elapses
+pub async fn with_timeout<T>(timeout: Duration, call: impl Future<Output = Result<T>>) -> Result<T> {
+    tokio::time::timeout(timeout, call)
+        .await
+        .map_err(|_| BridgeError::RpcTimeout { timeout })?
+}
+
+/// Returns true when the endpoint answered 429 Too Many Requests
+fn is_too_many_requests(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Reqwest(e) => e.status().is_some_and(|status| status.as_u16() == 429),
+        _ => false,
+    }
+}
+
+/// Returns true if the request ran into the HTTP client's timeout, `settings.rpc_timeout_secs`
+pub fn is_timeout(err: &ClientError) -> bool {
+    matches!(err.kind(), ClientErrorKind::Reqwest(e) if e.is_timeout())
+}
+
+/// Returns true when the endpoint itself appears unreachable or unhealthy
+pub fn is_connection_error(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Io(_) => true,
+        ClientErrorKind::Reqwest(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.is_request()
+                || e
+                    .status()
+                    .map(|status| status.is_server_error() || status.as_u16() == 429)
+                    .unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::RpcNode;
+    use crate::metrics;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    /// Returns a port that was free a moment ago
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn hung_endpoint_does_not_stall_health_server() {
+        // Accepts connections but never answers them
+        let hung = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", hung.local_addr().unwrap());
+        let client = Arc::new(FailoverRpcClient::new(
+            &[url],
+            CommitmentConfig::confirmed(),
+            Duration::from_secs(3),
+            &RateLimitConfig::default(),
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            Duration::from_secs(300),
+        )
+        .unwrap());
+
+        let port = free_port();
+        tokio::spawn(metrics::serve(port, None));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let call = tokio::spawn(async move { client.get_genesis_hash() });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // The only worker is inside the hung call, yet the health endpoint still answers
+        let health = tokio::time::timeout(
+            Duration::from_secs(1),
+            reqwest::get(format!("http://127.0.0.1:{}/health", port)),
+        )
+        .await;
+        assert!(matches!(health, Ok(Ok(_))), "health endpoint unresponsive: {:?}", health);
+
+        assert!(is_timeout(&call.await.unwrap().unwrap_err()));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn sends_configured_headers_and_query() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            use std::io::{Read, Write};
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !String::from_utf8_lossy(&request).contains("getGenesisHash") {
+                let read = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..read]);
+            }
+            let body = format!(r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#, solana_sdk::hash::Hash::default());
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+
+        std::env::set_var("SOL_PROPOSER_TEST_API_KEY", "secret-key");
+        let auth = EndpointAuth {
+            headers: BTreeMap::from([("x-api-key".to_string(), "${SOL_PROPOSER_TEST_API_KEY}".to_string())]),
+            query: BTreeMap::from([("api-key".to_string(), "abc".to_string())]),
+            client_cert_path: None,
+        };
+        assert!(!format!("{:?}", auth).contains("secret-key"));
+
+        let client = FailoverRpcClient::new(
+            std::slice::from_ref(&url),
+            CommitmentConfig::confirmed(),
+            Duration::from_secs(3),
+            &RateLimitConfig::default(),
+            &BTreeMap::from([(url.clone(), auth)]),
+            &BTreeMap::new(),
+            Duration::from_secs(300),
+        )
+        .unwrap();
+        client.get_genesis_hash().unwrap();
+
+        let request = server.join().unwrap().to_lowercase();
+        assert!(request.starts_with("post /?api-key=abc "), "{}", request);
+        assert!(request.contains("x-api-key: secret-key"), "{}", request);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn tracks_error_rate_per_endpoint() {
+        let url = format!("http://127.0.0.1:{}", free_port());
+        let client = FailoverRpcClient::new(
+            std::slice::from_ref(&url),
+            CommitmentConfig::confirmed(),
+            Duration::from_secs(3),
+            &RateLimitConfig::default(),
+            &BTreeMap::new(),
+            &BTreeMap::from([(url.clone(), "refused".to_string())]),
+            Duration::from_secs(300),
+        )
+        .unwrap();
+
+        assert!(client.get_genesis_hash().is_err());
+        client.record(0, true);
+        assert_eq!(metrics::RPC_ERROR_RATE.with_label_values(&["refused"]).get(), 0.5);
+        assert_eq!(metrics::RPC_REQUESTS.with_label_values(&["refused", "error"]).get(), 1);
+
+        // A new window starts from zero
+        client.stats[0].lock().unwrap().window_start -= Duration::from_secs(300);
+        client.record(0, true);
+        assert_eq!(metrics::RPC_ERROR_RATE.with_label_values(&["refused"]).get(), 0.0);
+    }
+
+    #[test]
+    fn rejects_missing_env_var() {
+        let auth = EndpointAuth {
+            headers: BTreeMap::from([("x-api-key".to_string(), "${SOL_PROPOSER_TEST_UNSET}".to_string())]),
+            ..EndpointAuth::default()
+        };
+        let err = auth.header_map().unwrap_err().to_string();
+        assert!(err.contains("SOL_PROPOSER_TEST_UNSET"), "{}", err);
+    }
+}
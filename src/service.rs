@@ -0,0 +1,584 @@
+//! The bridge service loop, usable from the binary or embedded in another process.
+
+use crate::alerts::{self, Alerter};
+use crate::bridge::{self, check_and_submit, BridgeContext, CycleOutcome, CycleReport, TargetResult};
+use crate::circuit::{CircuitBreaker, Transition};
+use crate::config::{Config, OutputFormat, SettingsConfig};
+use crate::error::BridgeError;
+use crate::health;
+use crate::metrics;
+use crate::output;
+use crate::reload::{self, ConfigSource, ReloadSignal};
+use crate::rpc;
+use crate::state;
+use crate::subscribe::{self, AccountWatcher};
+use crate::trigger::{Trigger, TriggerReply};
+use eyre::Result;
+use rand::Rng;
+use std::future::Future;
+use tokio::sync::mpsc;
+use tokio::time::{interval, interval_at, Duration, Instant, Interval, MissedTickBehavior};
+use tracing::{error, info, warn};
+
+/// A configured bridge, ready to run single cycles or the service loop
+pub struct BridgeService {
+    ctx: BridgeContext,
+    reload_source: Option<ConfigSource>,
+    trigger: Trigger,
+    /// Cycles requested over `POST /trigger`, run by the service loop
+    trigger_requests: mpsc::Receiver<TriggerReply>,
+}
+
+impl BridgeService {
+    /// Validates the config, builds the clients and checks they point at the expected clusters
+    pub async fn new(config: Config) -> Result<Self> {
+        config.validate()?;
+        let ctx = BridgeContext::new(config).await?;
+        bridge::check_clusters(&ctx).await?;
+        bridge::log_startup_banner(&ctx).await;
+
+        if let Some(last_slot) = state::load_last_slot(&ctx.config.settings.state_file) {
+            metrics::LAST_SUBMITTED_SLOT.set(last_slot as i64);
+        }
+        let (trigger, trigger_requests) = Trigger::new(ctx.config.settings.admin_token.clone());
+        Ok(Self {
+            ctx,
+            reload_source: None,
+            trigger,
+            trigger_requests,
+        })
+    }
+
+    /// Reloads the config from `source` whenever the process receives SIGHUP
+    pub fn with_reload(mut self, source: ConfigSource) -> Self {
+        self.reload_source = Some(source);
+        self
+    }
+
+    pub fn context(&self) -> &BridgeContext {
+        &self.ctx
+    }
+
+    /// Spawns the Prometheus metrics and health server when `settings.metrics_port` is set
+    ///
+    /// Its `POST /trigger` cycles only run once the service loop is running.
+    pub fn serve_metrics(&self) {
+        let settings = &self.ctx.config.settings;
+        let Some(port) = settings.metrics_port else {
+            return;
+        };
+        health::HEALTH.set_max_age(health_max_age_secs(settings));
+        let trigger = self.trigger.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(port, Some(trigger)).await {
+                error!(error = %e, "metrics server stopped");
+            }
+        });
+    }
+
+    /// Waits until both RPC endpoints report healthy, for at most `settings.warmup_timeout_secs`
+    ///
+    /// `/health` reports not ready meanwhile. Cycles start after the timeout regardless,
+    /// leaving a still unhealthy endpoint to the circuit breaker.
+    pub async fn warm_up(&self) {
+        let timeout = Duration::from_secs(self.ctx.config.settings.warmup_timeout_secs);
+        if timeout.is_zero() {
+            return;
+        }
+        health::HEALTH.set_ready(false);
+        let started = Instant::now();
+        let mut interval = ticker(interval(WARMUP_POLL_INTERVAL));
+        loop {
+            interval.tick().await;
+            let (l1_client, l2_client) = (self.ctx.l1_client.clone(), self.ctx.l2_client.clone());
+            let l1 = rpc::blocking(move || Ok(l1_client.get_health()?)).await;
+            let l2 = rpc::blocking(move || Ok(l2_client.get_health()?)).await;
+            if l1.is_ok() && l2.is_ok() {
+                info!(elapsed_ms = started.elapsed().as_millis() as u64, "RPC endpoints healthy, warmup complete");
+                break;
+            }
+            if started.elapsed() >= timeout {
+                warn!(
+                    timeout_secs = timeout.as_secs(),
+                    l1_error = l1.err().map(|e| e.to_string()),
+                    l2_error = l2.err().map(|e| e.to_string()),
+                    "RPC endpoints still unhealthy after warmup timeout, starting anyway"
+                );
+                break;
+            }
+            info!(l1_healthy = l1.is_ok(), l2_healthy = l2.is_ok(), "waiting for RPC endpoints to become healthy");
+        }
+        health::HEALTH.set_ready(true);
+    }
+
+    /// Runs and reports a single cycle, returning whether every target succeeded
+    ///
+    /// Returns false without running when another cycle on this context is still in flight.
+    pub async fn run_once(&self) -> bool {
+        match run_cycle(&self.ctx).await {
+            Some(result) => report_cycle(&self.ctx.config.settings, result),
+            None => false,
+        }
+    }
+
+    /// Runs cycles until SIGINT or SIGTERM
+    pub async fn run_loop(self) -> Result<()> {
+        self.run_until(shutdown_signal()).await
+    }
+
+    /// Runs cycles until `shutdown` resolves, letting an in-flight cycle finish within the shutdown timeout
+    pub async fn run_until(self, shutdown: impl Future<Output = ()>) -> Result<()> {
+        let Self {
+            mut ctx,
+            reload_source,
+            mut trigger_requests,
+            ..
+        } = self;
+        let settings = &ctx.config.settings;
+
+        // In subscribe mode account changes trigger cycles; polling only runs while disconnected
+        let mut watcher = spawn_watcher(&ctx);
+        let mut debounce = Duration::from_secs(settings.subscribe_debounce_secs);
+        let mut last_cycle: Option<Instant> = None;
+        let mut last_skew_check: Option<Instant> = None;
+
+        let mut check_interval = Duration::from_secs(settings.check_interval_secs);
+        let mut open_interval = Duration::from_secs(settings.circuit_open_interval_secs);
+        let mut missing_interval = Duration::from_secs(settings.leaf_chunk_missing_interval_secs);
+        let mut leaf_chunk_missing = false;
+        let mut breaker = CircuitBreaker::new(settings.max_consecutive_failures);
+        let mut alerter = Alerter::new(ctx.config.alerts.clone());
+
+        let mut reload_signal = ReloadSignal::new(reload_source.is_some());
+        tokio::pin!(shutdown);
+        let deadline = (settings.max_runtime_secs > 0)
+            .then(|| Instant::now() + Duration::from_secs(settings.max_runtime_secs));
+
+        let startup_delay = jitter(settings.startup_jitter_secs);
+        if !startup_delay.is_zero() {
+            info!(delay_ms = startup_delay.as_millis() as u64, "delaying first cycle by startup jitter");
+            tokio::select! {
+                _ = tokio::time::sleep(startup_delay) => {}
+                _ = &mut shutdown => {
+                    info!("shutting down gracefully");
+                    return Ok(());
+                }
+            }
+        }
+        let mut interval = ticker(interval(check_interval));
+
+        loop {
+            let mut triggered = None;
+            tokio::select! {
+                _ = reload_signal.recv() => {
+                    let Some(source) = &reload_source else {
+                        continue;
+                    };
+                    info!("SIGHUP received, reloading configuration");
+                    let reloaded = match reload::reload_context(source, &ctx).await {
+                        Ok(reloaded) => reloaded,
+                        Err(e) => {
+                            error!(error = format!("{:#}", e), "config reload rejected, keeping current configuration");
+                            continue;
+                        }
+                    };
+                    let settings = &reloaded.config.settings;
+                    debounce = Duration::from_secs(settings.subscribe_debounce_secs);
+                    check_interval = Duration::from_secs(settings.check_interval_secs);
+                    health::HEALTH.set_max_age(health_max_age_secs(settings));
+                    open_interval = Duration::from_secs(settings.circuit_open_interval_secs);
+                    missing_interval = Duration::from_secs(settings.leaf_chunk_missing_interval_secs);
+                    breaker.set_threshold(settings.max_consecutive_failures);
+                    alerter.reconfigure(reloaded.config.alerts.clone());
+                    let next = if breaker.is_open() { open_interval } else { check_interval };
+                    interval = ticker(interval_at(Instant::now() + next, next));
+
+                    let old = &ctx.config;
+                    if reloaded.leaf_chunks != ctx.leaf_chunks
+                        || settings.subscribe != old.settings.subscribe
+                        || reloaded.config.network.l2_ws_url != old.network.l2_ws_url
+                        || reloaded.config.network.l2_rpc_url != old.network.l2_rpc_url
+                        || reloaded.config.network.l2_commitment != old.network.l2_commitment
+                    {
+                        watcher = spawn_watcher(&reloaded);
+                    }
+                    ctx = reloaded;
+                    continue;
+                }
+                _ = interval.tick() => {
+                    if watcher.as_ref().is_some_and(AccountWatcher::is_connected) {
+                        continue;
+                    }
+                }
+                // Runs even while the circuit breaker is open, as an operator asked for it
+                Some(reply) = trigger_requests.recv() => {
+                    info!("cycle triggered over HTTP");
+                    triggered = Some(reply);
+                }
+                _ = account_changed(watcher.as_ref()) => {
+                    if breaker.is_open() {
+                        continue;
+                    }
+                    if let Some(last) = last_cycle {
+                        tokio::select! {
+                            _ = tokio::time::sleep_until(last + debounce) => {}
+                            _ = &mut shutdown => {
+                                info!("shutting down gracefully");
+                                break;
+                            }
+                            _ = runtime_elapsed(deadline) => {
+                                info!(max_runtime_secs = ctx.config.settings.max_runtime_secs, "max runtime reached, exiting for restart");
+                                break;
+                            }
+                        }
+                    }
+                }
+                _ = &mut shutdown => {
+                    info!("shutting down gracefully");
+                    break;
+                }
+                // Only checked between cycles, so the last one always runs to completion
+                _ = runtime_elapsed(deadline) => {
+                    info!(max_runtime_secs = ctx.config.settings.max_runtime_secs, "max runtime reached, exiting for restart");
+                    break;
+                }
+            }
+            if triggered.is_none() {
+                tokio::select! {
+                    _ = tokio::time::sleep(jitter(ctx.config.settings.cycle_jitter_secs)) => {}
+                    _ = &mut shutdown => {
+                        info!("shutting down gracefully");
+                        break;
+                    }
+                }
+            }
+            last_cycle = Some(Instant::now());
+
+            let cycle = run_cycle(&ctx);
+            tokio::pin!(cycle);
+
+            let result = tokio::select! {
+                result = &mut cycle => result,
+                _ = &mut shutdown => {
+                    info!("shutting down gracefully, waiting for in-flight cycle");
+                    let grace = Duration::from_secs(ctx.config.settings.shutdown_timeout_secs);
+                    match tokio::time::timeout(grace, cycle).await {
+                        Ok(Some(result)) => {
+                            report_cycle(&ctx.config.settings, result);
+                        }
+                        Ok(None) => {}
+                        Err(_) => warn!("in-flight cycle did not finish within shutdown timeout"),
+                    }
+                    break;
+                }
+            };
+            let Some(result) = result else {
+                if let Some(reply) = triggered {
+                    let _ = reply.send(None);
+                }
+                continue;
+            };
+            if let Some(reply) = triggered {
+                let _ = reply.send(Some(output::to_json(&result.0, result.1)));
+            }
+
+            // Block times trail real time by a few seconds, so only a large skew is worth a warning
+            let skew_interval = Duration::from_secs(ctx.config.settings.clock_skew_check_interval_secs);
+            if !skew_interval.is_zero() && last_skew_check.is_none_or(|at| at.elapsed() >= skew_interval) {
+                last_skew_check = Some(Instant::now());
+                if let Err(e) = bridge::check_clock_skew(&ctx).await {
+                    warn!(error = %e, "failed to check clock skew");
+                }
+            }
+
+            let failure = alerts::describe_failure(&result.0);
+            let missing = matches!(result.0.as_ref().err().and_then(BridgeError::find), Some(BridgeError::LeafChunkMissing { .. }));
+            match breaker.record(report_cycle(&ctx.config.settings, result)) {
+                Some(Transition::Opened) => {
+                    error!(
+                        critical = true,
+                        consecutive_failures = breaker.consecutive_failures(),
+                        retry_in_secs = open_interval.as_secs(),
+                        "circuit breaker open, backing off submissions"
+                    );
+                    interval = ticker(interval_at(Instant::now() + open_interval, open_interval));
+                }
+                Some(Transition::Closed) => {
+                    info!("circuit breaker closed, resuming normal interval");
+                    interval = ticker(interval_at(Instant::now() + check_interval, check_interval));
+                }
+                None => {}
+            }
+            // A closed leaf chunk needs an operator, so poll slowly and alert without waiting for the threshold
+            if missing {
+                interval = ticker(interval_at(Instant::now() + missing_interval, missing_interval));
+            } else if leaf_chunk_missing {
+                let next = if breaker.is_open() { open_interval } else { check_interval };
+                interval = ticker(interval_at(Instant::now() + next, next));
+            }
+            leaf_chunk_missing = missing;
+            match failure {
+                Some((_, error)) if missing => alerter.leaf_chunk_missing(error, breaker.consecutive_failures()),
+                failure => alerter.record(failure, breaker.consecutive_failures()),
+            }
+            alerter.low_runway(ctx.spend.last_estimate(), ctx.config.settings.low_runway_hours);
+            metrics::CIRCUIT_OPEN.set(breaker.is_open() as i64);
+            health::HEALTH.set_circuit_open(breaker.is_open());
+        }
+
+        Ok(())
+    }
+}
+
+/// Subscribes to the leaf chunk account when `settings.subscribe` is on
+fn spawn_watcher(ctx: &BridgeContext) -> Option<AccountWatcher> {
+    let config = &ctx.config;
+    config.settings.subscribe.then(|| {
+        let ws_url = config
+            .network
+            .l2_ws_url
+            .clone()
+            .unwrap_or_else(|| subscribe::ws_url_for(&config.network.l2_rpc_url[0]));
+        AccountWatcher::spawn(ws_url, &ctx.leaf_chunks, config.network.l2_commitment.into())
+    })
+}
+
+/// Resolves on the next account change, or never when not subscribed
+async fn account_changed(watcher: Option<&AccountWatcher>) {
+    match watcher {
+        Some(watcher) => watcher.changed().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Resolves at `deadline`, or never without one
+async fn runtime_elapsed(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Pause between health probes during warmup
+const WARMUP_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A random delay of at most `max_secs`
+fn jitter(max_secs: u64) -> Duration {
+    if max_secs == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_millis(rand::thread_rng().gen_range(0..=max_secs * 1000))
+}
+
+/// Seconds without a successful cycle after which `/health` reports unhealthy
+fn health_max_age_secs(settings: &SettingsConfig) -> u64 {
+    settings.check_interval_secs * settings.health_stale_cycles
+}
+
+/// Drops ticks missed during a slow cycle instead of firing them back to back
+fn ticker(mut interval: Interval) -> Interval {
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    interval
+}
+
+/// Runs a single check-and-submit cycle, timing it
+///
+/// Returns `None` without running while another cycle on `ctx` is still in flight,
+/// so two cycles never race to submit the same slot.
+async fn run_cycle(ctx: &BridgeContext) -> Option<(Result<CycleReport>, Duration)> {
+    let Ok(_in_flight) = ctx.cycle_lock.try_lock() else {
+        warn!("skipping tick, previous cycle still running");
+        return None;
+    };
+    let started = Instant::now();
+    let result = check_and_submit(ctx).await;
+    let duration = started.elapsed();
+    metrics::observe_duration("cycle", duration);
+    Some((result, duration))
+}
+
+/// Records the outcome of a single check-and-submit cycle, returning whether every target succeeded
+fn report_cycle(settings: &SettingsConfig, (result, duration): (Result<CycleReport>, Duration)) -> bool {
+    if settings.output_format == OutputFormat::Json {
+        output::write_json(&result, duration);
+    }
+
+    let success = match result {
+        // Report every target, not just up to the first failure
+        Ok(report) => {
+            report
+                .targets
+                .into_iter()
+                .map(|target| report_target(target, duration))
+                .filter(|succeeded| !succeeded)
+                .count()
+                == 0
+        }
+        Err(e) => {
+            error!(error = %e, duration_ms = duration.as_millis() as u64, "check and submit failed");
+            false
+        }
+    };
+
+    if success {
+        health::HEALTH.record_success();
+    } else {
+        metrics::SUBMISSION_FAILURES.inc();
+        health::HEALTH.record_failure();
+    }
+    success
+}
+
+/// Logs and counts the outcome for one L1 target, returning whether it succeeded
+fn report_target(target_result: TargetResult, cycle_duration: Duration) -> bool {
+    metrics::TARGET_CYCLES
+        .with_label_values(&[&target_result.target, target_result.status()])
+        .inc();
+
+    let TargetResult { target, result } = target_result;
+    let duration_ms = cycle_duration.as_millis() as u64;
+    match result {
+        Ok(CycleOutcome::Submitted { slot, signature }) => {
+            info!(l1_target = %target, slot, %signature, duration_ms, "cycle finished: submitted");
+            true
+        }
+        Ok(CycleOutcome::Proposed { slot, signature, proposal }) => {
+            info!(l1_target = %target, slot, %signature, %proposal, duration_ms, "cycle finished: proposed to multisig");
+            true
+        }
+        Ok(CycleOutcome::Skipped { slot, reason }) => {
+            info!(l1_target = %target, slot, reason, duration_ms, "cycle finished: nothing to do");
+            true
+        }
+        Ok(CycleOutcome::DryRun { slot }) => {
+            info!(l1_target = %target, slot, duration_ms, "cycle finished: dry run");
+            true
+        }
+        Ok(CycleOutcome::Observed { slot, l1_lag_slots }) => {
+            info!(l1_target = %target, slot, l1_lag_slots, duration_ms, "cycle finished: observed");
+            true
+        }
+        Err(e) => {
+            error!(l1_target = %target, error = %e, duration_ms, "check and submit failed");
+            false
+        }
+    }
+}
+
+/// Resolves once SIGINT or, on unix, SIGTERM is received
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!(error = %e, "failed to listen for ctrl-c");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut stream) => {
+                stream.recv().await;
+            }
+            Err(e) => {
+                error!(error = %e, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockClient;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::Keypair;
+
+    async fn service(settings: &str) -> (BridgeService, MockClient) {
+        let leaf_chunk = Pubkey::new_unique();
+        let state_file = std::env::temp_dir().join(format!("sol-proposer-service-{}.json", std::process::id()));
+        let config: Config = toml::from_str(&format!(
+            r#"
+            [network]
+            l1_rpc_url = "http://127.0.0.1:1"
+            l2_rpc_url = "http://127.0.0.1:2"
+            l1_program_id = "{program_id}"
+
+            [account]
+            leaf_chunk_address = "{leaf_chunk}"
+            slots_account = "{slots_account}"
+
+            [wallet]
+            base58 = "{wallet}"
+
+            [settings]
+            check_interval_secs = 1
+            dry_run = true
+            state_file = "{state_file}"
+            {settings}
+            "#,
+            program_id = Pubkey::new_unique(),
+            slots_account = Pubkey::new_unique(),
+            wallet = Keypair::new().to_base58_string(),
+            state_file = state_file.display(),
+        ))
+        .unwrap();
+
+        let mock = MockClient::new(7);
+        mock.set_account(leaf_chunk, [[0u8; 8].as_slice(), &[1; 32]].concat());
+        let ctx = BridgeContext::with_clients(config, Box::new(mock.clone()), Box::new(mock.clone()))
+            .await
+            .unwrap();
+        let (trigger, trigger_requests) = Trigger::new(None);
+        let service = BridgeService {
+            ctx,
+            reload_source: None,
+            trigger,
+            trigger_requests,
+        };
+        (service, mock)
+    }
+
+    #[tokio::test]
+    async fn skips_cycle_while_previous_one_runs() {
+        let (service, _) = service("").await;
+        assert!(service.run_once().await);
+
+        let _in_flight = service.ctx.cycle_lock.lock().await;
+        assert!(run_cycle(&service.ctx).await.is_none());
+        assert!(!service.run_once().await);
+    }
+
+    #[tokio::test]
+    async fn warms_up_until_endpoints_are_healthy() {
+        let (service, mock) = service("").await;
+        mock.fail_health_checks(2);
+        service.warm_up().await;
+        // Both clients are probed each round, the second round passing
+        assert_eq!(mock.health_checks(), 4);
+        assert!(health::HEALTH.report(u64::MAX).ready);
+    }
+
+    #[tokio::test]
+    async fn shuts_down_during_cycle_jitter() {
+        let (service, _) = service("cycle_jitter_secs = 3600").await;
+        let run = service.run_until(tokio::time::sleep(Duration::from_millis(100)));
+        tokio::time::timeout(Duration::from_secs(10), run).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn exits_after_max_runtime() {
+        let (service, _) = service("max_runtime_secs = 1").await;
+        let run = service.run_until(std::future::pending());
+        tokio::time::timeout(Duration::from_secs(10), run).await.unwrap().unwrap();
+    }
+}
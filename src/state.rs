@@ -0,0 +1,77 @@
+//! Persistence of bridge progress across restarts.
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// State persisted between runs of the bridge service
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BridgeState {
+    pub last_slot: u64,
+    /// Hex-encoded merkle tree root submitted for `last_slot`
+    #[serde(default)]
+    pub last_mt_root: Option<String>,
+    /// Hex-encoded world state root submitted for `last_slot`
+    #[serde(default)]
+    pub last_ws_root: Option<String>,
+}
+
+impl BridgeState {
+    /// Returns true when the given roots match the last submitted ones
+    pub fn roots_match(&self, mt_root: &[u8], ws_root: &[u8]) -> bool {
+        self.last_mt_root.as_deref() == Some(hex::encode(mt_root).as_str())
+            && self.last_ws_root.as_deref() == Some(hex::encode(ws_root).as_str())
+    }
+}
+
+/// Expands a user-supplied state file path
+pub(crate) fn expand_path(path: &str) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(path).as_ref())
+}
+
+/// Loads the last submitted slot, treating a missing or corrupt file as no prior slot
+pub fn load_last_slot(path: &str) -> Option<u64> {
+    load_state(path).map(|state| state.last_slot)
+}
+
+/// Loads the persisted state, treating a missing or corrupt file as no prior state
+pub fn load_state(path: &str) -> Option<BridgeState> {
+    let path = expand_path(path);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(_) => return None,
+    };
+
+    match serde_json::from_str::<BridgeState>(&contents) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "ignoring corrupt state file");
+            None
+        }
+    }
+}
+
+/// Saves the last submitted slot and its roots, writing through a temporary file so the update is atomic
+pub fn save_last_slot(path: &str, slot: u64, mt_root: &[u8], ws_root: &[u8]) -> Result<()> {
+    let path = expand_path(path);
+    let tmp_path = tmp_path_for(&path);
+
+    let state = BridgeState {
+        last_slot: slot,
+        last_mt_root: Some(hex::encode(mt_root)),
+        last_ws_root: Some(hex::encode(ws_root)),
+    };
+    let contents = serde_json::to_string(&state)?;
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+/// Returns the sibling temporary path used while writing the state file
+pub(crate) fn tmp_path_for(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    PathBuf::from(tmp)
+}
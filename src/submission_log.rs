@@ -0,0 +1,152 @@
+//! Append-only log of every confirmed submission, keyed by program and slot.
+
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// One confirmed submission, stored as a JSON line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub program_id: String,
+    pub slot: u64,
+    /// Hex-encoded merkle tree root
+    pub mt_root: String,
+    /// Hex-encoded world state root
+    pub ws_root: String,
+    pub signature: String,
+    /// Unix time of the confirmation, in seconds
+    pub timestamp: u64,
+}
+
+impl LogEntry {
+    pub fn new(program_id: &str, slot: u64, mt_root: &[u8], ws_root: &[u8], signature: &str) -> Self {
+        Self {
+            program_id: program_id.to_string(),
+            slot,
+            mt_root: hex::encode(mt_root),
+            ws_root: hex::encode(ws_root),
+            signature: signature.to_string(),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+        }
+    }
+
+    /// Returns true when the entry records exactly these roots
+    pub fn roots_match(&self, mt_root: &[u8], ws_root: &[u8]) -> bool {
+        self.mt_root == hex::encode(mt_root) && self.ws_root == hex::encode(ws_root)
+    }
+}
+
+/// The submission log file with an in-memory index of its latest entry per program and slot
+pub struct SubmissionLog {
+    path: PathBuf,
+    index: Mutex<HashMap<(String, u64), LogEntry>>,
+}
+
+impl SubmissionLog {
+    /// Opens the log at `path`, indexing the entries already in it
+    pub fn open(path: &str) -> Result<Self> {
+        let path = PathBuf::from(shellexpand::tilde(path).as_ref());
+        let contents = read_log(&path)?;
+        // Start a fresh line after one cut short by a crash, so the next entry stays readable
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            OpenOptions::new().append(true).open(&path)?.write_all(b"\n")?;
+        }
+        let index = parse_entries(&path, &contents)
+            .into_iter()
+            .map(|entry| ((entry.program_id.clone(), entry.slot), entry))
+            .collect();
+        Ok(Self {
+            path,
+            index: Mutex::new(index),
+        })
+    }
+
+    /// The latest entry recorded for `slot` on `program_id`
+    pub fn find(&self, program_id: &str, slot: u64) -> Option<LogEntry> {
+        self.index.lock().unwrap().get(&(program_id.to_string(), slot)).cloned()
+    }
+
+    /// Appends `entry` to the file, syncing it to disk before indexing it
+    pub fn append(&self, entry: LogEntry) -> Result<()> {
+        let mut index = self.index.lock().unwrap();
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .wrap_err_with(|| format!("submission log {}", self.path.display()))?;
+        file.write_all(&line)?;
+        file.sync_data()?;
+
+        index.insert((entry.program_id.clone(), entry.slot), entry);
+        Ok(())
+    }
+
+    /// Every entry in the log, oldest first
+    pub fn entries(path: &str) -> Result<Vec<LogEntry>> {
+        let path = PathBuf::from(shellexpand::tilde(path).as_ref());
+        Ok(parse_entries(&path, &read_log(&path)?))
+    }
+}
+
+/// Reads the log file, treating a missing file as empty
+fn read_log(path: &Path) -> Result<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(eyre::eyre!("submission log {}: {}", path.display(), e)),
+    }
+}
+
+/// Parses every entry, skipping lines that do not parse, such as one cut short by a crash
+fn parse_entries(path: &Path, contents: &str) -> Vec<LogEntry> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(number, line)| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!(path = %path.display(), line = number + 1, error = %e, "ignoring corrupt submission log line");
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_latest_entry_across_reopen() {
+        let path = std::env::temp_dir().join(format!("sol-proposer-log-{}.jsonl", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+
+        let log = SubmissionLog::open(path).unwrap();
+        log.append(LogEntry::new("program", 7, &[1; 32], &[2; 32], "first")).unwrap();
+        log.append(LogEntry::new("program", 7, &[3; 32], &[4; 32], "second")).unwrap();
+        log.append(LogEntry::new("other", 7, &[1; 32], &[2; 32], "third")).unwrap();
+
+        // A torn last line is skipped rather than failing the whole log
+        OpenOptions::new().append(true).open(path).unwrap().write_all(b"{\"program_id\":").unwrap();
+
+        let log = SubmissionLog::open(path).unwrap();
+        log.append(LogEntry::new("program", 8, &[5; 32], &[6; 32], "fourth")).unwrap();
+        let entry = log.find("program", 7).unwrap();
+        assert_eq!(entry.signature, "second");
+        assert!(entry.roots_match(&[3; 32], &[4; 32]));
+        assert!(log.find("program", 9).is_none());
+        assert_eq!(SubmissionLog::entries(path).unwrap().len(), 4);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+//! Websocket subscription to L2 leaf chunk account changes.
+
+use futures::StreamExt;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+/// Delay before reconnecting a dropped subscription
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Handle to background tasks watching accounts over websocket
+pub struct AccountWatcher {
+    notify: Arc<Notify>,
+    /// One subscription per account, with its connection state
+    subscriptions: Vec<(Arc<AtomicBool>, JoinHandle<()>)>,
+}
+
+impl AccountWatcher {
+    /// Spawns a task per account that subscribes to it and reconnects whenever the websocket drops
+    ///
+    /// A change to any of the accounts wakes `changed`.
+    pub fn spawn(ws_url: String, pubkeys: &[Pubkey], commitment: CommitmentConfig) -> Self {
+        let notify = Arc::new(Notify::new());
+        let subscriptions = pubkeys
+            .iter()
+            .map(|pubkey| {
+                let connected = Arc::new(AtomicBool::new(false));
+                let task = tokio::spawn(watch(ws_url.clone(), *pubkey, commitment, notify.clone(), connected.clone()));
+                (connected, task)
+            })
+            .collect();
+
+        Self { notify, subscriptions }
+    }
+
+    /// Returns true while every subscription is live
+    pub fn is_connected(&self) -> bool {
+        self.subscriptions.iter().all(|(connected, _)| connected.load(Ordering::Relaxed))
+    }
+
+    /// Resolves on the next account change notification
+    pub async fn changed(&self) {
+        self.notify.notified().await;
+    }
+}
+
+impl Drop for AccountWatcher {
+    fn drop(&mut self) {
+        for (_, task) in &self.subscriptions {
+            task.abort();
+        }
+    }
+}
+
+/// Keeps a subscription open, signalling `notify` on every account update
+async fn watch(
+    ws_url: String,
+    pubkey: Pubkey,
+    commitment: CommitmentConfig,
+    notify: Arc<Notify>,
+    connected: Arc<AtomicBool>,
+) {
+    let account_config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(commitment),
+        ..RpcAccountInfoConfig::default()
+    };
+
+    loop {
+        match PubsubClient::new(&ws_url).await {
+            Ok(client) => match client.account_subscribe(&pubkey, Some(account_config.clone())).await {
+                Ok((mut stream, unsubscribe)) => {
+                    info!(%pubkey, "subscribed to leaf chunk account changes");
+                    connected.store(true, Ordering::Relaxed);
+                    while let Some(update) = stream.next().await {
+                        info!(slot = update.context.slot, "leaf chunk account changed");
+                        notify.notify_one();
+                    }
+                    connected.store(false, Ordering::Relaxed);
+                    unsubscribe().await;
+                    warn!("account subscription closed, falling back to polling");
+                }
+                Err(e) => warn!(error = %e, "account subscription failed, falling back to polling"),
+            },
+            Err(e) => warn!(error = %e, "websocket connection failed, falling back to polling"),
+        }
+
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Derives the websocket URL of an RPC endpoint, following the validator's port+1 convention
+pub fn ws_url_for(rpc_url: &str) -> String {
+    let Ok(mut url) = reqwest::Url::parse(rpc_url) else {
+        return rpc_url.to_string();
+    };
+
+    let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+    let _ = url.set_scheme(scheme);
+    if let Some(port) = url.port() {
+        let _ = url.set_port(Some(port + 1));
+    }
+    url.to_string()
+}
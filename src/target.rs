@@ -0,0 +1,120 @@
+//! L1 programs the roots are submitted to.
+
+use crate::config::{parse_pubkey, Config};
+use crate::program::{self, InstructionLayout, ProgramConfig, Seed};
+use eyre::{Result, WrapErr};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::debug;
+
+/// Name of the target described by `network.l1_program_id`, `account.slots_account` and `[program]`
+pub const PRIMARY_TARGET: &str = "primary";
+
+/// An additional L1 target as read from a `[[l1_targets]]` config entry
+#[derive(Debug, Deserialize)]
+pub struct TargetConfig {
+    /// Name used in logs, metrics and the default state file
+    pub name: String,
+    pub l1_program_id: String,
+    pub slots_account: String,
+    /// State file of this target; defaults to `settings.state_file` with the name before the extension
+    pub state_file: Option<String>,
+    #[serde(default)]
+    pub program: ProgramConfig,
+}
+
+/// A parsed L1 target
+#[derive(Debug, Clone)]
+pub struct L1Target {
+    pub name: String,
+    pub program_id: Pubkey,
+    pub slots_account: Pubkey,
+    pub state_file: String,
+    /// Instructions sent together in every submission, in order
+    pub instructions: Vec<InstructionLayout>,
+    pub pda_seeds: Vec<Seed>,
+    pub roots_mt_root_offset: usize,
+    /// Offset of the highest recorded slot in the slots account
+    pub last_slot_offset: Option<usize>,
+}
+
+impl L1Target {
+    /// Parses the primary target followed by every `[[l1_targets]]` entry
+    pub fn all(config: &Config) -> Result<Vec<Self>> {
+        let mut targets = vec![Self::parse(
+            PRIMARY_TARGET,
+            parse_pubkey("network.l1_program_id", &config.network.l1_program_id)?,
+            parse_pubkey("account.slots_account", &config.account.slots_account)?,
+            config.settings.state_file.clone(),
+            &config.program,
+        )?];
+
+        let mut names: HashSet<&str> = HashSet::from([PRIMARY_TARGET]);
+        for target in &config.l1_targets {
+            if target.name.is_empty() || !names.insert(&target.name) {
+                return Err(eyre::eyre!("l1_targets: duplicate or empty target name {:?}", target.name));
+            }
+
+            let parsed = (|| {
+                Self::parse(
+                    &target.name,
+                    parse_pubkey("l1_program_id", &target.l1_program_id)?,
+                    parse_pubkey("slots_account", &target.slots_account)?,
+                    target
+                        .state_file
+                        .clone()
+                        .unwrap_or_else(|| target_state_file(&config.settings.state_file, &target.name)),
+                    &target.program,
+                )
+            })()
+            .wrap_err_with(|| format!("l1_targets: invalid target {:?}", target.name))?;
+            targets.push(parsed);
+        }
+
+        Ok(targets)
+    }
+
+    fn parse(
+        name: &str,
+        program_id: Pubkey,
+        slots_account: Pubkey,
+        state_file: String,
+        program: &ProgramConfig,
+    ) -> Result<Self> {
+        Ok(Self {
+            name: name.to_string(),
+            program_id,
+            slots_account,
+            state_file,
+            instructions: program.instruction_layouts()?,
+            pda_seeds: program.pda.parse_seeds()?,
+            roots_mt_root_offset: program.roots_mt_root_offset,
+            last_slot_offset: program.last_slot_offset,
+        })
+    }
+
+    /// Whether this is the target configured by the top-level sections
+    pub fn is_primary(&self) -> bool {
+        self.name == PRIMARY_TARGET
+    }
+
+    /// Derives the L1 account storing the roots for `slot`
+    pub fn slot_roots_pda(&self, slot: u64) -> (Pubkey, u8) {
+        let (slot_roots_account, bump) = program::derive_slot_roots_pda(&self.pda_seeds, slot, &self.program_id);
+        debug!(l1_target = %self.name, slot, %slot_roots_account, bump, "derived slot roots PDA");
+        (slot_roots_account, bump)
+    }
+}
+
+/// Inserts the target name before the extension of the main state file
+fn target_state_file(state_file: &str, name: &str) -> String {
+    let path = Path::new(state_file);
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("bridge_state");
+    let file_name = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => format!("{}.{}.{}", stem, name, ext),
+        None => format!("{}.{}", stem, name),
+    };
+    path.with_file_name(file_name).to_string_lossy().into_owned()
+}
@@ -0,0 +1,103 @@
+//! `POST /trigger`, running a cycle between intervals on an operator's request.
+
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::Json;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt;
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+
+/// Bearer token `POST /trigger` requires, as read from `settings.admin_token`
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct AdminToken(String);
+
+// The token grants submissions, so it never reaches the logs
+impl fmt::Debug for AdminToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl AdminToken {
+    /// Returns true if the `Authorization` header carries this token
+    ///
+    /// Compares every byte, so the time taken does not reveal how much of a guess matched.
+    fn authorizes(&self, headers: &HeaderMap) -> bool {
+        let Some(presented) = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            return false;
+        };
+        let (presented, expected) = (presented.as_bytes(), self.0.as_bytes());
+        presented.len() == expected.len() && presented.iter().zip(expected).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+}
+
+/// A triggered cycle's reply: its JSON records, or `None` when another cycle was still in flight
+pub type TriggerReply = oneshot::Sender<Option<Value>>;
+
+/// Hands triggered cycles to the service loop, which runs them like scheduled ones
+#[derive(Clone)]
+pub struct Trigger {
+    requests: mpsc::Sender<TriggerReply>,
+    token: Option<AdminToken>,
+}
+
+impl Trigger {
+    /// Creates a trigger and the receiver the service loop takes its requests from
+    pub fn new(token: Option<AdminToken>) -> (Self, mpsc::Receiver<TriggerReply>) {
+        let (requests, receiver) = mpsc::channel(1);
+        (Self { requests, token }, receiver)
+    }
+}
+
+/// Handler for `POST /trigger`, answering with the cycle's result once it finished
+///
+/// Answers 401 without the configured bearer token, 409 while another cycle is running
+/// and 503 when the service loop is not running or shuts down before the cycle ran.
+pub async fn handler(State(trigger): State<Trigger>, headers: HeaderMap) -> (StatusCode, Json<Value>) {
+    if trigger.token.as_ref().is_some_and(|token| !token.authorizes(&headers)) {
+        warn!("rejected unauthorized cycle trigger");
+        return (StatusCode::UNAUTHORIZED, Json(json!({ "error": "missing or invalid bearer token" })));
+    }
+    let (reply, result) = oneshot::channel();
+    if trigger.requests.try_send(reply).is_err() {
+        return (StatusCode::CONFLICT, Json(json!({ "error": "a triggered cycle is already pending" })));
+    }
+    match result.await {
+        Ok(Some(records)) => (StatusCode::OK, Json(records)),
+        Ok(None) => (StatusCode::CONFLICT, Json(json!({ "error": "another cycle is still running" }))),
+        Err(_) => (StatusCode::SERVICE_UNAVAILABLE, Json(json!({ "error": "service loop is not running" }))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn requires_bearer_token() {
+        let (trigger, mut receiver) = Trigger::new(Some(AdminToken("secret".to_string())));
+        let mut headers = HeaderMap::new();
+        let (status, _) = handler(State(trigger.clone()), headers.clone()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        headers.insert(header::AUTHORIZATION, "Bearer secreT".parse().unwrap());
+        let (status, _) = handler(State(trigger.clone()), headers.clone()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        tokio::spawn(async move {
+            let reply = receiver.recv().await.unwrap();
+            reply.send(Some(json!([{ "status": "submitted" }]))).unwrap();
+        });
+        let (status, Json(body)) = handler(State(trigger), headers).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["status"], "submitted");
+        assert_eq!(format!("{:?}", AdminToken("secret".to_string())), "<redacted>");
+    }
+}
@@ -0,0 +1,281 @@
+//! Loading of the signing wallet.
+
+use crate::config::parse_pubkey;
+#[cfg(feature = "ledger")]
+use crate::ledger::LedgerSigner;
+use age::armor::ArmoredReader;
+use eyre::Result;
+use secrecy::SecretString;
+use serde::Deserialize;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+use solana_sdk::signer::Signer;
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::error;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Environment variable holding the passphrase of encrypted keypair files
+pub const PASSPHRASE_ENV: &str = "WALLET_PASSPHRASE";
+
+/// The authority signer, a local keypair or a hardware wallet
+pub type WalletSigner = Arc<dyn Signer + Send + Sync>;
+
+/// Wallet configuration as read from the `[wallet]` section
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletConfig {
+    /// Where the authority key, which signs the bridge instruction, is read from
+    #[serde(flatten)]
+    pub source: WalletSource,
+    /// Keypair file of a separate fee payer; the authority pays when unset
+    pub fee_payer_path: Option<String>,
+    /// Pubkey the authority key must have; startup aborts on any other key
+    pub expected_pubkey: Option<String>,
+}
+
+/// Where a signing key is read from
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+pub enum WalletSource {
+    /// A keypair JSON file on disk, optionally encrypted with an age passphrase
+    Path {
+        #[serde(alias = "path")]
+        wallet_path: String,
+    },
+    /// An environment variable holding a base58 key or a JSON byte array
+    EnvVar { env_var: String },
+    /// An inline base58-encoded key
+    Base58 { base58: String },
+    /// The key at this derivation path on the first connected Ledger, such as `m/44'/501'/0'/0'`
+    Ledger { ledger: String },
+}
+
+impl fmt::Debug for WalletSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path { wallet_path } => f.debug_struct("Path").field("wallet_path", wallet_path).finish(),
+            Self::EnvVar { env_var } => f.debug_struct("EnvVar").field("env_var", env_var).finish(),
+            Self::Base58 { .. } => f.debug_struct("Base58").field("base58", &"<redacted>").finish(),
+            Self::Ledger { ledger } => f.debug_struct("Ledger").field("ledger", ledger).finish(),
+        }
+    }
+}
+
+/// Message signed at load time to check a keypair is intact
+const CHECK_MESSAGE: &[u8] = b"sol-proposer keypair check";
+
+/// Loads the authority signer from its configured source, checking it against `expected_pubkey`
+pub async fn load_wallet(config: &WalletConfig) -> Result<WalletSigner> {
+    let signer = load_signer(config).await?;
+    if let Some(expected) = &config.expected_pubkey {
+        let expected = parse_pubkey("wallet.expected_pubkey", expected)?;
+        if signer.pubkey() != expected {
+            error!(loaded = %signer.pubkey(), %expected, "wallet key does not match wallet.expected_pubkey");
+            return Err(eyre::eyre!(
+                "wallet key {} does not match wallet.expected_pubkey {}",
+                signer.pubkey(),
+                expected
+            ));
+        }
+    }
+    Ok(signer)
+}
+
+async fn load_signer(config: &WalletConfig) -> Result<WalletSigner> {
+    let keypair = match &config.source {
+        WalletSource::Path { wallet_path } => load_wallet_file(wallet_path)?,
+        WalletSource::EnvVar { env_var } => {
+            let mut secret = std::env::var(env_var)
+                .map_err(|e| eyre::eyre!("Failed to read wallet from ${}: {}", env_var, e))?;
+            let keypair = parse_keypair(&secret);
+            secret.zeroize();
+            keypair?
+        }
+        WalletSource::Base58 { base58 } => parse_keypair(base58)?,
+        #[cfg(feature = "ledger")]
+        WalletSource::Ledger { ledger } => {
+            let derivation_path = ledger.clone();
+            let signer = tokio::task::spawn_blocking(move || LedgerSigner::connect(&derivation_path)).await??;
+            return Ok(Arc::new(signer));
+        }
+        #[cfg(not(feature = "ledger"))]
+        WalletSource::Ledger { .. } => {
+            return Err(eyre::eyre!("wallet.ledger: built without Ledger support, rebuild with `--features ledger`"));
+        }
+    };
+    verify_keypair(&keypair)?;
+    Ok(Arc::new(keypair))
+}
+
+/// Loads the separate fee payer keypair, if one is configured
+pub fn load_fee_payer(config: &WalletConfig) -> Result<Option<Keypair>> {
+    config
+        .fee_payer_path
+        .as_deref()
+        .map(|path| {
+            let keypair = load_wallet_file(path)?;
+            verify_keypair(&keypair)?;
+            Ok(keypair)
+        })
+        .transpose()
+        .map_err(|e: eyre::Report| eyre::eyre!("wallet.fee_payer_path: {}", e))
+}
+
+/// Signs a known message and verifies the signature against the loaded pubkey,
+/// so a malformed key fails at startup rather than at its first submission
+fn verify_keypair(keypair: &Keypair) -> Result<()> {
+    let signature = keypair.sign_message(CHECK_MESSAGE);
+    if !signature.verify(keypair.pubkey().as_ref(), CHECK_MESSAGE) {
+        return Err(eyre::eyre!(
+            "wallet key {} failed verification, its secret does not match its pubkey; the keypair file may be corrupt",
+            keypair.pubkey()
+        ));
+    }
+    Ok(())
+}
+
+/// Loads wallet keypair from the specified path
+///
+/// Files encrypted with `age --passphrase`, binary or armored, are decrypted with the
+/// passphrase from `WALLET_PASSPHRASE`, or prompted for on the terminal when that is unset.
+fn load_wallet_file(wallet_path: &str) -> Result<Keypair> {
+    let expanded_path = shellexpand::tilde(wallet_path);
+    let path = Path::new(expanded_path.as_ref());
+    let contents = std::fs::read(path).map_err(|e| eyre::eyre!("Failed to read wallet file: {}", e))?;
+    if !is_encrypted(&contents) {
+        let keypair = read_keypair_file(path)
+            .map_err(|e| eyre::eyre!("Failed to read wallet file: {}", e))?;
+        return Ok(keypair);
+    }
+
+    let passphrase = read_passphrase(wallet_path)?;
+    decrypt_keypair(&contents, &passphrase).map_err(|e| eyre::eyre!("{}: {}", wallet_path, e))
+}
+
+/// Returns true for age-encrypted files
+fn is_encrypted(contents: &[u8]) -> bool {
+    contents.starts_with(b"age-encryption.org/") || contents.starts_with(b"-----BEGIN AGE ENCRYPTED FILE-----")
+}
+
+/// Reads the wallet passphrase from `WALLET_PASSPHRASE`, falling back to a terminal prompt
+fn read_passphrase(wallet_path: &str) -> Result<SecretString> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(SecretString::new(passphrase));
+    }
+    rpassword::prompt_password(format!("Passphrase for {}: ", wallet_path))
+        .map(SecretString::new)
+        .map_err(|e| eyre::eyre!("{} is encrypted; set ${} or run on a terminal: {}", wallet_path, PASSPHRASE_ENV, e))
+}
+
+/// Decrypts an age passphrase-encrypted keypair, zeroizing the plaintext once parsed
+fn decrypt_keypair(contents: &[u8], passphrase: &SecretString) -> Result<Keypair> {
+    let decryptor = match age::Decryptor::new(ArmoredReader::new(contents)) {
+        Ok(age::Decryptor::Passphrase(decryptor)) => decryptor,
+        Ok(age::Decryptor::Recipients(_)) => {
+            return Err(eyre::eyre!("encrypted to age recipients, only passphrase encryption is supported"));
+        }
+        Err(e) => return Err(eyre::eyre!("Failed to read encrypted wallet file: {}", e)),
+    };
+    let mut reader = decryptor
+        .decrypt(passphrase, None)
+        .map_err(|e| eyre::eyre!("Failed to decrypt wallet file: {}", e))?;
+
+    // Sized up front so reading never reallocates and leaves plaintext copies behind
+    let mut plaintext = Zeroizing::new(Vec::with_capacity(contents.len()));
+    reader
+        .read_to_end(&mut plaintext)
+        .map_err(|e| eyre::eyre!("Failed to decrypt wallet file: {}", e))?;
+    let secret = std::str::from_utf8(&plaintext).map_err(|_| eyre::eyre!("Decrypted wallet file is not text"))?;
+    parse_keypair(secret)
+}
+
+/// Parses a keypair given either as a JSON byte array or as a base58 string
+fn parse_keypair(secret: &str) -> Result<Keypair> {
+    let secret = secret.trim();
+    let mut bytes = if secret.starts_with('[') {
+        serde_json::from_str::<Vec<u8>>(secret)
+            .map_err(|_| eyre::eyre!("Failed to parse wallet key as a JSON byte array"))?
+    } else {
+        bs58::decode(secret)
+            .into_vec()
+            .map_err(|_| eyre::eyre!("Failed to parse wallet key as base58"))?
+    };
+
+    let keypair = Keypair::from_bytes(&bytes).map_err(|e| eyre::eyre!("Invalid wallet key: {}", e));
+    bytes.zeroize();
+    keypair
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::armor::{ArmoredWriter, Format};
+    use secrecy::Secret;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signer::Signer;
+    use std::io::Write;
+
+    fn encrypt(plaintext: &[u8], passphrase: &str, format: Format) -> Vec<u8> {
+        let encryptor = age::Encryptor::with_user_passphrase(Secret::new(passphrase.to_string()));
+        let armored = ArmoredWriter::wrap_output(Vec::new(), format).unwrap();
+        let mut writer = encryptor.wrap_output(armored).unwrap();
+        writer.write_all(plaintext).unwrap();
+        writer.finish().unwrap().finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn parses_ledger_source() {
+        let config: WalletConfig = toml::from_str(r#"ledger = "m/44'/501'/0'/0'""#).unwrap();
+        assert!(matches!(&config.source, WalletSource::Ledger { ledger } if ledger == "m/44'/501'/0'/0'"));
+        if !cfg!(feature = "ledger") {
+            let err = load_wallet(&config).await.err().unwrap();
+            assert!(err.to_string().contains("--features ledger"), "{}", err);
+        }
+
+        let config: WalletConfig = toml::from_str(r#"ledger = "44'/501'""#).unwrap();
+        assert!(load_wallet(&config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn verifies_loaded_keypair() {
+        let keypair = Keypair::new();
+        let config = |base58: &str, expected: &Pubkey| WalletConfig {
+            source: WalletSource::Base58 { base58: base58.to_string() },
+            fee_payer_path: None,
+            expected_pubkey: Some(expected.to_string()),
+        };
+        let wallet = load_wallet(&config(&keypair.to_base58_string(), &keypair.pubkey())).await.unwrap();
+        assert_eq!(wallet.pubkey(), keypair.pubkey());
+
+        let other = Pubkey::new_unique();
+        let err = load_wallet(&config(&keypair.to_base58_string(), &other)).await.err().unwrap();
+        assert!(err.to_string().contains("does not match wallet.expected_pubkey"), "{}", err);
+
+        // The secret of one key paired with the pubkey of another
+        let mut bytes = keypair.to_bytes();
+        bytes[32..].copy_from_slice(Keypair::new().pubkey().as_ref());
+        let corrupt = bs58::encode(bytes).into_string();
+        assert!(load_wallet(&config(&corrupt, &keypair.pubkey())).await.is_err());
+        assert!(load_wallet(&config(&corrupt[..corrupt.len() - 4], &keypair.pubkey())).await.is_err());
+    }
+
+    #[test]
+    fn decrypts_passphrase_encrypted_keypair() {
+        let keypair = Keypair::new();
+        let json = serde_json::to_vec(&keypair.to_bytes().to_vec()).unwrap();
+        assert!(!is_encrypted(&json));
+
+        for (format, armored) in [(Format::Binary, false), (Format::AsciiArmor, true)] {
+            let encrypted = encrypt(&json, "correct horse", format);
+            assert!(is_encrypted(&encrypted));
+            let decrypted = decrypt_keypair(&encrypted, &Secret::new("correct horse".to_string())).unwrap();
+            assert_eq!(decrypted.pubkey(), keypair.pubkey());
+
+            if !armored {
+                let err = decrypt_keypair(&encrypted, &Secret::new("battery staple".to_string())).unwrap_err();
+                assert!(err.to_string().contains("Failed to decrypt"), "{}", err);
+            }
+        }
+    }
+}
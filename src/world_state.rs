@@ -0,0 +1,127 @@
+//! Reading the world state root from its L2 account.
+
+use crate::bridge::{fetch_l2_account, BridgeContext};
+use crate::error::BridgeError;
+use crate::leaf_chunk::extract_root;
+use eyre::Result;
+use solana_sdk::pubkey::Pubkey;
+
+/// Length of the little-endian `u32` prefix of a Borsh vector
+const VEC_LEN_PREFIX: usize = 4;
+
+/// A Borsh-encoded vector stored ahead of the world state root
+#[derive(Debug, Clone)]
+pub struct VecPrefix {
+    /// Byte offset of the vector's `u32` length prefix
+    pub offset: usize,
+    /// Encoded size of one vector element
+    pub item_len: usize,
+}
+
+/// Where the world state root lives in the account data
+#[derive(Debug, Clone)]
+pub struct WorldStateLayout {
+    /// Byte offset of the root; counted from the end of `vec_prefix` when one is set
+    pub root_offset: usize,
+    /// Variable-length vector preceding the root
+    pub vec_prefix: Option<VecPrefix>,
+}
+
+impl WorldStateLayout {
+    /// Resolves the absolute offset of the root within `data`
+    pub fn resolve_root_offset(&self, data: &[u8]) -> Result<usize> {
+        let Some(prefix) = &self.vec_prefix else {
+            return Ok(self.root_offset);
+        };
+
+        let len_bytes = data.get(prefix.offset..prefix.offset + VEC_LEN_PREFIX).ok_or(BridgeError::AccountTooShort {
+            len: data.len(),
+            needed: prefix.offset + VEC_LEN_PREFIX,
+        })?;
+        let len = u32::from_le_bytes(len_bytes.try_into().expect("slice of prefix length")) as usize;
+
+        len.checked_mul(prefix.item_len)
+            .and_then(|vec_len| vec_len.checked_add(prefix.offset + VEC_LEN_PREFIX))
+            .and_then(|vec_end| vec_end.checked_add(self.root_offset))
+            .filter(|offset| offset.saturating_add(32) <= data.len())
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "world state vector of {} items overruns account data of {} bytes",
+                    len,
+                    data.len()
+                )
+            })
+    }
+
+    /// Extracts the world state root from account data
+    pub fn extract_root(&self, data: &[u8]) -> Result<[u8; 32]> {
+        extract_root(data, self.resolve_root_offset(data)?)
+    }
+}
+
+/// Reads the world state root from a configured L2 account
+#[derive(Debug, Clone)]
+pub struct WorldStateReader {
+    pub account: Pubkey,
+    pub layout: WorldStateLayout,
+}
+
+impl WorldStateReader {
+    /// Fetches the account, served from at least `min_context_slot`, and extracts the root
+    pub async fn read(&self, ctx: &BridgeContext, min_context_slot: Option<u64>) -> Result<[u8; 32]> {
+        let (account, _) = fetch_l2_account(ctx, &self.account, min_context_slot).await?;
+        let account = account.ok_or(BridgeError::AccountNotFound {
+            account: "world state",
+            address: self.account,
+        })?;
+
+        self.layout.extract_root(&account.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Discriminator, a vector of three 2-byte items, a padding byte, then the root
+    fn synthetic_account() -> Vec<u8> {
+        let mut data = vec![0xee; 8];
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(&[1, 1, 2, 2, 3, 3]);
+        data.push(0);
+        data.extend_from_slice(&[0x5a; 32]);
+        data
+    }
+
+    fn vec_layout() -> WorldStateLayout {
+        WorldStateLayout {
+            root_offset: 1,
+            vec_prefix: Some(VecPrefix { offset: 8, item_len: 2 }),
+        }
+    }
+
+    #[test]
+    fn extracts_root_after_vector() {
+        let data = synthetic_account();
+        assert_eq!(vec_layout().resolve_root_offset(&data).unwrap(), 19);
+        assert_eq!(vec_layout().extract_root(&data).unwrap(), [0x5a; 32]);
+    }
+
+    #[test]
+    fn extracts_root_at_fixed_offset() {
+        let layout = WorldStateLayout {
+            root_offset: 19,
+            vec_prefix: None,
+        };
+        assert_eq!(layout.extract_root(&synthetic_account()).unwrap(), [0x5a; 32]);
+    }
+
+    #[test]
+    fn rejects_vector_overrunning_data() {
+        let mut data = synthetic_account();
+        data[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(vec_layout().extract_root(&data).is_err());
+
+        assert!(vec_layout().extract_root(&data[..10]).is_err());
+    }
+}